@@ -1,3 +1,4 @@
+mod determinism;
 mod invoker;
 mod output_bindings;
 
@@ -9,6 +10,7 @@ use azure_functions_shared::codegen::{
     },
     get_string_value, iter_attribute_args, last_segment_in_path, macro_panic, Function, InvokerFn,
 };
+use determinism::{check_determinism, take_allow_nondeterminism, ALLOW_NONDETERMINISM_ARG};
 use invoker::Invoker;
 use output_bindings::OutputBindings;
 use proc_macro2::Span;
@@ -22,7 +24,7 @@ use syn::{
 };
 
 pub const OUTPUT_BINDING_PREFIX: &str = "output";
-const RETURN_BINDING_NAME: &str = "$return";
+pub const RETURN_BINDING_NAME: &str = "$return";
 const ORCHESTRATION_CONTEXT_TYPE: &str = "DurableOrchestrationContext";
 const ORCHESTRATION_OUTPUT_TYPE: &str = "OrchestrationOutput";
 const ACTIVITY_CONTEXT_TYPE: &str = "DurableActivityContext";
@@ -188,7 +190,7 @@ fn validate_function(func: &ItemFn) {
     }
 }
 
-fn get_generic_argument_type<'a>(
+pub(crate) fn get_generic_argument_type<'a>(
     last: &'a PathSegment,
     generic_type_name: &str,
 ) -> Option<&'a Type> {
@@ -210,6 +212,20 @@ fn get_generic_argument_type<'a>(
     }
 }
 
+fn get_result_ok_type(last: &PathSegment) -> Option<&Type> {
+    if last.ident != "Result" {
+        return None;
+    }
+
+    match &last.arguments {
+        PathArguments::AngleBracketed(gen_args) => match gen_args.args.iter().nth(0) {
+            Some(GenericArgument::Type(t)) => Some(t),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 fn get_input_binding_factory_for_vec(tp: &TypePath, mutability: Option<Mut>) -> &BindingFactory {
     let last_segment = last_segment_in_path(&tp.path);
     let type_name = last_segment.ident.to_string();
@@ -418,6 +434,31 @@ fn bind_output_type(
     }
 }
 
+fn bind_return_value_type(
+    ty: &Type,
+    binding_args: &mut HashMap<String, (AttributeArgs, Span)>,
+) -> Binding {
+    if let Type::Path(tp) = ty {
+        if let Some(inner) = get_result_ok_type(last_segment_in_path(&tp.path)) {
+            let is_http_response = match inner {
+                Type::Path(tp) => last_segment_in_path(&tp.path).ident == "HttpResponse",
+                _ => false,
+            };
+
+            if !is_http_response {
+                macro_panic(
+                    ty.span(),
+                    "a `Result` return type must have a success type of `HttpResponse`",
+                );
+            }
+
+            return bind_output_type(inner, RETURN_BINDING_NAME, binding_args, false);
+        }
+    }
+
+    bind_output_type(ty, RETURN_BINDING_NAME, binding_args, true)
+}
+
 fn bind_return_type(
     ret: &ReturnType,
     binding_args: &mut HashMap<String, (AttributeArgs, Span)>,
@@ -436,12 +477,7 @@ fn bind_return_type(
                 }
                 if i == 0 {
                     if !is_activity {
-                        bindings.push(bind_output_type(
-                            &ty,
-                            RETURN_BINDING_NAME,
-                            binding_args,
-                            true,
-                        ));
+                        bindings.push(bind_return_value_type(&ty, binding_args));
                     }
                 } else {
                     bindings.push(bind_output_type(
@@ -453,12 +489,7 @@ fn bind_return_type(
                 }
             }
         } else if !is_activity {
-            bindings.push(bind_output_type(
-                &ty,
-                RETURN_BINDING_NAME,
-                binding_args,
-                true,
-            ));
+            bindings.push(bind_return_value_type(&ty, binding_args));
         }
     }
 
@@ -524,13 +555,37 @@ pub fn func_impl(
         validate_activity_function(&target);
     }
 
-    let mut func = Function::from(match syn::parse_macro_input::parse::<AttributeArgs>(args) {
+    let mut args = match syn::parse_macro_input::parse::<AttributeArgs>(args) {
         Ok(f) => f,
         Err(e) => macro_panic(
             Span::call_site(),
             format!("failed to parse attribute arguments: {}", e),
         ),
-    });
+    };
+
+    let allow_nondeterminism = take_allow_nondeterminism(&mut args);
+    if allow_nondeterminism && !is_orchestration {
+        macro_panic(
+            Span::call_site(),
+            format!(
+                "the '{}' argument is only supported for orchestration functions",
+                ALLOW_NONDETERMINISM_ARG
+            ),
+        );
+    }
+
+    if is_orchestration && !allow_nondeterminism {
+        check_determinism(&target);
+    }
+
+    let mut func = Function::from(args);
+
+    if func.version.is_some() && !is_orchestration {
+        macro_panic(
+            Span::call_site(),
+            "the 'version' argument is only supported for orchestration functions",
+        );
+    }
 
     let mut binding_args = drain_binding_attributes(&mut target.attrs);
     let mut names = HashSet::new();
@@ -555,6 +610,20 @@ pub fn func_impl(
         );
     }
 
+    if (func.retry.is_some() || func.retry_options.is_some())
+        && !func.bindings.iter().any(|b| match b {
+            Binding::TimerTrigger(_)
+            | Binding::EventHubTrigger(_)
+            | Binding::CosmosDbTrigger(_) => true,
+            _ => false,
+        })
+    {
+        macro_panic(
+            Span::call_site(),
+            "the 'retry' and 'retry_options' arguments are only supported for timer, Event Hub, and Cosmos DB triggered functions",
+        );
+    }
+
     if !is_orchestration {
         for binding in
             bind_return_type(&target.sig.output, &mut binding_args, is_activity).into_iter()
@@ -613,9 +682,71 @@ pub fn func_impl(
         });
     }
 
+    let http_output = func.bindings.iter().find_map(|b| match b {
+        Binding::Http(http) => Some(http.name.to_string()),
+        _ => None,
+    });
+
+    let has_http_trigger = func.bindings.iter().any(|b| match b {
+        Binding::HttpTrigger(_) => true,
+        _ => false,
+    });
+
+    if !func.roles.is_empty() {
+        if !has_http_trigger {
+            macro_panic(
+                Span::call_site(),
+                "the 'roles' argument is only supported for HTTP triggered functions",
+            );
+        }
+
+        if http_output.is_none() {
+            macro_panic(
+                Span::call_site(),
+                "the 'roles' argument requires the function to have a HTTP output binding",
+            );
+        }
+    }
+
+    if func.cors.is_some() {
+        if !has_http_trigger {
+            macro_panic(
+                Span::call_site(),
+                "the 'cors_origins' argument is only supported for HTTP triggered functions",
+            );
+        }
+
+        if http_output.is_none() {
+            macro_panic(
+                Span::call_site(),
+                "the 'cors_origins' argument requires the function to have a HTTP output binding",
+            );
+        }
+
+        // Preflight requests only reach the function if the trigger accepts the OPTIONS method
+        if !func.bindings.iter().any(|b| match b {
+            Binding::HttpTrigger(trigger) => {
+                trigger.methods.is_empty()
+                    || trigger
+                        .methods
+                        .iter()
+                        .any(|m| m.eq_ignore_ascii_case("options"))
+            }
+            _ => false,
+        }) {
+            macro_panic(
+                Span::call_site(),
+                "the 'cors_origins' argument requires the HTTP trigger to accept preflight requests; add 'options' to the trigger's 'methods' argument",
+            );
+        }
+    }
+
     let invoker = Invoker {
         func: &target,
         is_orchestration,
+        version: func.version.as_ref().map(|v| v.to_string()),
+        roles: func.roles.iter().map(|r| r.to_string()).collect(),
+        http_output,
     };
 
     let target_name = target.sig.ident.to_string();
@@ -0,0 +1,475 @@
+use azure_functions_shared::codegen::{get_boolean_value, last_segment_in_path, macro_panic};
+use std::collections::HashMap;
+use syn::{
+    punctuated::Punctuated,
+    spanned::Spanned,
+    token::Comma,
+    visit::{self, Visit},
+    AttributeArgs, Expr, ExprPath, ItemFn, ItemUse, Macro, Meta, NestedMeta, Path, UseTree,
+};
+
+pub const ALLOW_NONDETERMINISM_ARG: &str = "allow_nondeterminism";
+
+const CURRENT_TIME: &str = "use `DurableOrchestrationContext::current_time` instead";
+const NEW_GUID: &str = "use `DurableOrchestrationContext::new_guid` instead";
+const CREATE_TIMER: &str = "use `DurableOrchestrationContext::create_timer` instead";
+const ACTIVITY: &str = "perform the operation in an activity function instead";
+const INPUT: &str =
+    "pass the value as orchestration input or read it in an activity function instead";
+
+#[derive(Clone, Copy)]
+enum Match {
+    // The path is the function (e.g. `Utc::now`)
+    Function,
+    // The path is within the module or type (e.g. `tokio::fs::read` or `OsRng::default`)
+    Prefix,
+    // The path is the associated function of any type (e.g. `StdRng::from_entropy`)
+    AssociatedFunction,
+}
+
+// Each entry is the full path of a non-deterministic function, module or type along with the
+// fewest trailing segments of the path that identify it (e.g. `Utc::now` but not `now`).
+const NON_DETERMINISTIC_PATHS: &[(&[&str], usize, Match, &str)] = &[
+    (&["chrono", "Utc", "now"], 2, Match::Function, CURRENT_TIME),
+    (
+        &["chrono", "Local", "now"],
+        2,
+        Match::Function,
+        CURRENT_TIME,
+    ),
+    (
+        &["std", "time", "SystemTime", "now"],
+        2,
+        Match::Function,
+        CURRENT_TIME,
+    ),
+    (&["uuid", "Uuid", "new_v4"], 2, Match::Function, NEW_GUID),
+    (&["rand", "random"], 2, Match::Function, ACTIVITY),
+    (&["rand", "thread_rng"], 1, Match::Function, ACTIVITY),
+    (&["rand", "rngs", "OsRng"], 1, Match::Prefix, ACTIVITY),
+    (&["rand", "rngs", "ThreadRng"], 1, Match::Prefix, ACTIVITY),
+    (&["rand", "rngs", "EntropyRng"], 1, Match::Prefix, ACTIVITY),
+    (
+        &["rand", "SeedableRng", "from_entropy"],
+        2,
+        Match::AssociatedFunction,
+        ACTIVITY,
+    ),
+    (
+        &["std", "thread", "sleep"],
+        2,
+        Match::Function,
+        CREATE_TIMER,
+    ),
+    (
+        &["tokio", "timer", "delay"],
+        2,
+        Match::Function,
+        CREATE_TIMER,
+    ),
+    (
+        &["tokio", "timer", "delay_for"],
+        2,
+        Match::Function,
+        CREATE_TIMER,
+    ),
+    (
+        &["tokio", "time", "delay_for"],
+        2,
+        Match::Function,
+        CREATE_TIMER,
+    ),
+    (
+        &["tokio", "time", "sleep"],
+        2,
+        Match::Function,
+        CREATE_TIMER,
+    ),
+    (&["tokio", "fs"], 2, Match::Prefix, ACTIVITY),
+    (&["tokio", "net"], 2, Match::Prefix, ACTIVITY),
+    (&["std", "fs", "read"], 2, Match::Function, ACTIVITY),
+    (
+        &["std", "fs", "read_to_string"],
+        2,
+        Match::Function,
+        ACTIVITY,
+    ),
+    (&["std", "fs", "write"], 2, Match::Function, ACTIVITY),
+    (&["std", "fs", "File", "open"], 2, Match::Function, ACTIVITY),
+    (
+        &["std", "fs", "File", "create"],
+        2,
+        Match::Function,
+        ACTIVITY,
+    ),
+    (
+        &["std", "net", "TcpStream", "connect"],
+        2,
+        Match::Function,
+        ACTIVITY,
+    ),
+    (&["std", "env", "var"], 2, Match::Function, INPUT),
+    (&["std", "env", "var_os"], 2, Match::Function, INPUT),
+    (&["std", "env", "vars"], 2, Match::Function, INPUT),
+];
+
+fn is_match(path: &[&str], min: usize, kind: Match, segments: &[String]) -> bool {
+    // Whether the segments start with the last `len` segments of the path
+    let starts_with = |len: usize| {
+        segments.len() >= len
+            && path[path.len() - len..]
+                .iter()
+                .zip(segments.iter())
+                .all(|(p, s)| p == s)
+    };
+
+    match kind {
+        Match::Function => {
+            segments.len() >= min && segments.len() <= path.len() && starts_with(segments.len())
+        }
+        Match::Prefix => (min..=path.len()).any(starts_with),
+        Match::AssociatedFunction => {
+            segments.len() >= min && segments.last().map(String::as_str) == path.last().copied()
+        }
+    }
+}
+
+fn find_non_deterministic_path(segments: &[String]) -> Option<&'static str> {
+    NON_DETERMINISTIC_PATHS
+        .iter()
+        .find(|(path, min, kind, _)| is_match(path, *min, *kind, segments))
+        .map(|(_, _, _, suggestion)| *suggestion)
+}
+
+// Maps the names brought into scope by `use` declarations in the function to their full paths.
+#[derive(Default)]
+struct UseVisitor {
+    imports: HashMap<String, Vec<String>>,
+}
+
+impl UseVisitor {
+    fn add_tree(&mut self, prefix: &mut Vec<String>, tree: &UseTree) {
+        match tree {
+            UseTree::Path(path) => {
+                prefix.push(path.ident.to_string());
+                self.add_tree(prefix, &path.tree);
+                prefix.pop();
+            }
+            UseTree::Name(name) => {
+                let mut path = prefix.clone();
+                if name.ident != "self" {
+                    path.push(name.ident.to_string());
+                }
+                if let Some(last) = path.last().cloned() {
+                    self.imports.insert(last, path);
+                }
+            }
+            UseTree::Rename(rename) => {
+                let mut path = prefix.clone();
+                if rename.ident != "self" {
+                    path.push(rename.ident.to_string());
+                }
+                self.imports.insert(rename.rename.to_string(), path);
+            }
+            UseTree::Group(group) => {
+                for tree in group.items.iter() {
+                    self.add_tree(prefix, tree);
+                }
+            }
+            UseTree::Glob(_) => {}
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for UseVisitor {
+    fn visit_item_use(&mut self, item: &'ast ItemUse) {
+        self.add_tree(&mut Vec::new(), &item.tree);
+    }
+}
+
+struct DeterminismVisitor {
+    imports: HashMap<String, Vec<String>>,
+}
+
+impl DeterminismVisitor {
+    fn check_path(&self, path: &Path) {
+        let mut segments: Vec<String> = path.segments.iter().map(|s| s.ident.to_string()).collect();
+
+        if let Some(import) = segments.first().and_then(|first| self.imports.get(first)) {
+            segments.splice(0..1, import.iter().cloned());
+        }
+
+        if let Some(suggestion) = find_non_deterministic_path(&segments) {
+            macro_panic(
+                path.span(),
+                format!(
+                    "`{}` is non-deterministic and cannot be used in orchestration functions; {}",
+                    segments.join("::"),
+                    suggestion
+                ),
+            );
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for DeterminismVisitor {
+    fn visit_expr_path(&mut self, expr: &'ast ExprPath) {
+        self.check_path(&expr.path);
+        visit::visit_expr_path(self, expr);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast Macro) {
+        // Check the arguments of function-like macros (e.g. `format!` or `info!`)
+        if let Ok(args) = mac.parse_body_with(Punctuated::<Expr, Comma>::parse_terminated) {
+            for arg in args.iter() {
+                self.visit_expr(arg);
+            }
+        }
+    }
+}
+
+/// Removes the `allow_nondeterminism` argument from the `func` attribute arguments.
+///
+/// Returns `true` if the argument was present and not set to `false`.
+pub fn take_allow_nondeterminism(args: &mut AttributeArgs) -> bool {
+    let mut allowed = false;
+
+    args.retain(|arg| {
+        let meta = match arg {
+            NestedMeta::Meta(meta) => meta,
+            _ => return true,
+        };
+
+        if last_segment_in_path(meta.path()).ident != ALLOW_NONDETERMINISM_ARG {
+            return true;
+        }
+
+        allowed = match meta {
+            Meta::Path(_) => true,
+            Meta::NameValue(nv) => get_boolean_value(ALLOW_NONDETERMINISM_ARG, &nv.lit),
+            Meta::List(list) => macro_panic(
+                list.span(),
+                format!(
+                    "expected a literal boolean value for the '{}' argument",
+                    ALLOW_NONDETERMINISM_ARG
+                ),
+            ),
+        };
+        false
+    });
+
+    allowed
+}
+
+/// Checks the body of an orchestration function for calls that are known to be non-deterministic.
+///
+/// Orchestration functions are replayed from history, so any call whose result can differ
+/// between replays will corrupt the orchestration.
+///
+/// Paths are matched against the full paths of known non-deterministic functions; only `use`
+/// declarations within the function itself are resolved.
+pub fn check_determinism(func: &ItemFn) {
+    let mut uses = UseVisitor::default();
+    uses.visit_block(&func.block);
+
+    DeterminismVisitor {
+        imports: uses.imports,
+    }
+    .visit_block(&func.block);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::should_panic;
+    use syn::parse_str;
+
+    fn check(func: &str) {
+        check_determinism(&parse_str::<ItemFn>(func).unwrap());
+    }
+
+    #[test]
+    fn it_rejects_known_non_deterministic_calls() {
+        should_panic(
+            || check("async fn f(context: DurableOrchestrationContext) { let now = Utc::now(); }"),
+            "`Utc::now` is non-deterministic and cannot be used in orchestration functions; use `DurableOrchestrationContext::current_time` instead",
+        );
+        should_panic(
+            || check("async fn f(context: DurableOrchestrationContext) { ::uuid::Uuid::new_v4(); }"),
+            "`uuid::Uuid::new_v4` is non-deterministic and cannot be used in orchestration functions; use `DurableOrchestrationContext::new_guid` instead",
+        );
+        should_panic(
+            || check("async fn f(context: DurableOrchestrationContext) { let x: u32 = thread_rng().gen(); }"),
+            "`thread_rng` is non-deterministic and cannot be used in orchestration functions; perform the operation in an activity function instead",
+        );
+    }
+
+    #[test]
+    fn it_resolves_use_declarations() {
+        should_panic(
+            || {
+                check(
+                    "async fn f(context: DurableOrchestrationContext) {
+                        use std::thread::sleep;
+                        sleep(Duration::from_secs(1));
+                    }",
+                )
+            },
+            "`std::thread::sleep` is non-deterministic and cannot be used in orchestration functions; use `DurableOrchestrationContext::create_timer` instead",
+        );
+        should_panic(
+            || {
+                check(
+                    "async fn f(context: DurableOrchestrationContext) {
+                        use std::{env::var as setting, fs};
+                        let x = setting(\"X\");
+                    }",
+                )
+            },
+            "`std::env::var` is non-deterministic and cannot be used in orchestration functions; pass the value as orchestration input or read it in an activity function instead",
+        );
+    }
+
+    #[test]
+    fn it_rejects_calls_in_macro_arguments() {
+        should_panic(
+            || {
+                check(
+                    "async fn f(context: DurableOrchestrationContext) { info!(\"now: {}\", chrono::Utc::now()); }",
+                )
+            },
+            "`chrono::Utc::now` is non-deterministic and cannot be used in orchestration functions; use `DurableOrchestrationContext::current_time` instead",
+        );
+    }
+
+    #[test]
+    fn it_rejects_tokio_io() {
+        should_panic(
+            || check("async fn f(context: DurableOrchestrationContext) { tokio::fs::read(\"x\").await; }"),
+            "`tokio::fs::read` is non-deterministic and cannot be used in orchestration functions; perform the operation in an activity function instead",
+        );
+        should_panic(
+            || {
+                check(
+                    "async fn f(context: DurableOrchestrationContext) {
+                        use tokio::fs::File;
+                        let file = File::open(\"x\").await;
+                    }",
+                )
+            },
+            "`tokio::fs::File::open` is non-deterministic and cannot be used in orchestration functions; perform the operation in an activity function instead",
+        );
+        should_panic(
+            || {
+                check(
+                    "async fn f(context: DurableOrchestrationContext) {
+                        use tokio::net::TcpStream;
+                        let stream = TcpStream::connect(\"127.0.0.1:80\").await;
+                    }",
+                )
+            },
+            "`tokio::net::TcpStream::connect` is non-deterministic and cannot be used in orchestration functions; perform the operation in an activity function instead",
+        );
+        should_panic(
+            || {
+                check(
+                    "async fn f(context: DurableOrchestrationContext) {
+                        use tokio::net;
+                        let listener = net::TcpListener::bind(\"127.0.0.1:80\").await;
+                    }",
+                )
+            },
+            "`tokio::net::TcpListener::bind` is non-deterministic and cannot be used in orchestration functions; perform the operation in an activity function instead",
+        );
+    }
+
+    #[test]
+    fn it_rejects_random_number_generators() {
+        should_panic(
+            || check("async fn f(context: DurableOrchestrationContext) { let x: u32 = OsRng.gen(); }"),
+            "`OsRng` is non-deterministic and cannot be used in orchestration functions; perform the operation in an activity function instead",
+        );
+        should_panic(
+            || {
+                check(
+                    "async fn f(context: DurableOrchestrationContext) { let x = rand::rngs::OsRng::default().next_u32(); }",
+                )
+            },
+            "`rand::rngs::OsRng::default` is non-deterministic and cannot be used in orchestration functions; perform the operation in an activity function instead",
+        );
+        should_panic(
+            || {
+                check(
+                    "async fn f(context: DurableOrchestrationContext) {
+                        use rand::rngs::ThreadRng;
+                        let x: u32 = ThreadRng::default().gen();
+                    }",
+                )
+            },
+            "`rand::rngs::ThreadRng::default` is non-deterministic and cannot be used in orchestration functions; perform the operation in an activity function instead",
+        );
+        should_panic(
+            || check("async fn f(context: DurableOrchestrationContext) { let rng = EntropyRng::new(); }"),
+            "`EntropyRng::new` is non-deterministic and cannot be used in orchestration functions; perform the operation in an activity function instead",
+        );
+        should_panic(
+            || check("async fn f(context: DurableOrchestrationContext) { let rng = StdRng::from_entropy(); }"),
+            "`StdRng::from_entropy` is non-deterministic and cannot be used in orchestration functions; perform the operation in an activity function instead",
+        );
+        should_panic(
+            || check("async fn f(context: DurableOrchestrationContext) { let x: u32 = rand::random(); }"),
+            "`rand::random` is non-deterministic and cannot be used in orchestration functions; perform the operation in an activity function instead",
+        );
+    }
+
+    #[test]
+    fn it_allows_deterministic_calls() {
+        check(
+            "async fn f(context: DurableOrchestrationContext) {
+                let now = context.current_time();
+                let started = Instant::now();
+                let value = config::env::var(\"X\");
+                let handle = tokio::spawn(async {});
+                sleep(Duration::from_secs(1));
+                let rng = StdRng::seed_from_u64(42);
+                let os = os::fs::metadata(\"x\");
+                let entropy = from_entropy();
+            }",
+        );
+        check(
+            "async fn f(context: DurableOrchestrationContext) {
+                use crate::util::sleep;
+                sleep();
+                now();
+            }",
+        );
+    }
+
+    #[test]
+    fn it_takes_the_allow_nondeterminism_argument() {
+        let mut args = vec![
+            parse_str::<NestedMeta>(r#"name = "foo""#).unwrap(),
+            parse_str::<NestedMeta>("allow_nondeterminism").unwrap(),
+        ];
+        assert!(take_allow_nondeterminism(&mut args));
+        assert_eq!(args.len(), 1);
+
+        let mut args = vec![parse_str::<NestedMeta>("allow_nondeterminism = false").unwrap()];
+        assert!(!take_allow_nondeterminism(&mut args));
+        assert!(args.is_empty());
+
+        let mut args = vec![parse_str::<NestedMeta>(r#"name = "foo""#).unwrap()];
+        assert!(!take_allow_nondeterminism(&mut args));
+        assert_eq!(args.len(), 1);
+
+        should_panic(
+            || {
+                take_allow_nondeterminism(&mut vec![parse_str::<NestedMeta>(
+                    r#"allow_nondeterminism = "yes""#,
+                )
+                .unwrap()]);
+            },
+            "expected a literal boolean value for the 'allow_nondeterminism' argument",
+        );
+    }
+}
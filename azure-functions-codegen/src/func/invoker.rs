@@ -1,4 +1,4 @@
-use crate::func::{get_generic_argument_type, OutputBindings};
+use crate::func::{get_generic_argument_type, OutputBindings, RETURN_BINDING_NAME};
 use azure_functions_shared::codegen::{bindings::TRIGGERS, last_segment_in_path};
 use azure_functions_shared::util::to_camel_case;
 use proc_macro2::TokenStream;
@@ -10,6 +10,9 @@ const INVOKER_PREFIX: &str = "__invoke_";
 pub struct Invoker<'a> {
     pub func: &'a ItemFn,
     pub is_orchestration: bool,
+    pub version: Option<String>,
+    pub roles: Vec<String>,
+    pub http_output: Option<String>,
 }
 
 impl<'a> Invoker<'a> {
@@ -38,6 +41,9 @@ impl<'a> Invoker<'a> {
 struct CommonInvokerTokens<'a> {
     pub func: &'a ItemFn,
     pub is_orchestration: bool,
+    pub version: Option<&'a str>,
+    pub roles: &'a [String],
+    pub http_output: Option<&'a str>,
 }
 
 impl<'a> CommonInvokerTokens<'a> {
@@ -85,12 +91,60 @@ impl<'a> CommonInvokerTokens<'a> {
 
     fn get_state_arg(&self, trigger: &Ident) -> TokenStream {
         if self.is_orchestration {
-            quote!(let __state = #trigger.as_ref().unwrap().state();)
+            let set_version = self
+                .version
+                .map(|v| quote!(#trigger.as_mut().unwrap().set_version(#v);));
+
+            quote!(
+                #set_version
+                let __state = #trigger.as_ref().unwrap().state();
+            )
         } else {
             TokenStream::new()
         }
     }
 
+    fn get_authorization(&self, trigger: &Ident) -> TokenStream {
+        if self.roles.is_empty() {
+            return TokenStream::new();
+        }
+
+        let roles = self.roles;
+        let set_output = match self.http_output {
+            Some(RETURN_BINDING_NAME) | None => {
+                quote!(__res.return_value = Some(__denied.into());)
+            }
+            Some(name) => quote!(
+                __res.output_data.push(::azure_functions::rpc::ParameterBinding{
+                    name: #name.to_string(),
+                    data: Some(__denied.into()),
+                });
+            ),
+        };
+        let ret = if self.func.sig.asyncness.is_some() {
+            quote!(return Box::pin(::futures::future::ready(__res));)
+        } else {
+            quote!(return __res;)
+        };
+
+        quote!(
+            if let Some(__denied) = #trigger.as_ref().unwrap().authorize(&[#(#roles),*]) {
+                let mut __res = ::azure_functions::rpc::InvocationResponse {
+                    invocation_id: __req.invocation_id,
+                    result: Some(::azure_functions::rpc::StatusResult {
+                        status: ::azure_functions::rpc::status_result::Status::Success as i32,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                };
+
+                #set_output
+
+                #ret
+            }
+        )
+    }
+
     fn get_args_for_call(&self) -> Vec<TokenStream> {
         self.iter_args()
             .map(|(name, arg_type)| {
@@ -140,6 +194,8 @@ impl ToTokens for CommonInvokerTokens<'_> {
 
         let state_arg = self.get_state_arg(trigger_arg);
 
+        let authorization = self.get_authorization(trigger_arg);
+
         quote!(
             use azure_functions::{IntoVec, FromVec};
 
@@ -163,6 +219,8 @@ impl ToTokens for CommonInvokerTokens<'_> {
 
             #state_arg
 
+            #authorization
+
             let __ret = #target(#(#args_for_call,)*);
         )
         .to_tokens(tokens);
@@ -179,6 +237,9 @@ impl ToTokens for Invoker<'_> {
         let common_tokens = CommonInvokerTokens {
             func: &self.func,
             is_orchestration: self.is_orchestration,
+            version: self.version.as_ref().map(String::as_str),
+            roles: &self.roles,
+            http_output: self.http_output.as_ref().map(String::as_str),
         };
 
         let output_bindings = OutputBindings {
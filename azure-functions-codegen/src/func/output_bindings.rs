@@ -1,4 +1,4 @@
-use crate::func::get_generic_argument_type;
+use crate::func::{get_generic_argument_type, get_result_ok_type};
 use azure_functions_shared::{codegen::last_segment_in_path, util::to_camel_case};
 use proc_macro2::TokenStream;
 use quote::{quote, ToTokens};
@@ -115,6 +115,22 @@ impl<'a> OutputBindings<'a> {
         }
     }
 
+    fn is_result(ty: &Type) -> bool {
+        match ty {
+            Type::Path(tp) => get_result_ok_type(last_segment_in_path(&tp.path)).is_some(),
+            _ => false,
+        }
+    }
+
+    fn get_result_binding(value: TokenStream) -> TokenStream {
+        quote!(
+            __res.return_value = Some(match #value {
+                Ok(__ret) => __ret.into(),
+                Err(__err) => ::azure_functions::http::error_response(__err).into(),
+            });
+        )
+    }
+
     fn is_unit_tuple(t: &Type) -> bool {
         match t {
             Type::Tuple(tuple) => tuple.elems.is_empty(),
@@ -127,6 +143,14 @@ impl<'a> OutputBindings<'a> {
             return None;
         }
 
+        if OutputBindings::is_result(ty) {
+            return Some(OutputBindings::get_result_binding(if in_tuple {
+                quote!(__ret.0)
+            } else {
+                quote!(__ret)
+            }));
+        }
+
         if in_tuple {
             match OutputBindings::get_generic_argument_type(ty, "Option") {
                 Some(inner) => {
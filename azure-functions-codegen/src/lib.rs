@@ -8,6 +8,7 @@ extern crate proc_macro;
 
 mod export;
 mod func;
+mod table_entity;
 
 use azure_functions_shared::codegen::macro_panic;
 use proc_macro2::{Delimiter, Span};
@@ -86,3 +87,58 @@ pub fn func(
 ) -> proc_macro::TokenStream {
     func::func_impl(args, input)
 }
+
+/// Implements the `TableEntity` derive.
+///
+/// This derive is used to annotate the properties of an Azure Storage table entity with their EDM types.
+///
+/// Fields of type `i64`, `isize`, `DateTime`, `Uuid`, and `Vec<u8>` (or an `Option` of those)
+/// are annotated automatically; use `#[table(edm = "...")]` to annotate other fields.
+///
+/// Unsigned integers are not annotated automatically as `Edm.Int64` is a signed type.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use azure_functions::bindings::TableEntity;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize, TableEntity)]
+/// #[serde(rename_all = "PascalCase")]
+/// struct Order {
+///     total_cents: i64,
+///     #[table(edm = "Guid")]
+///     customer_id: String,
+/// }
+/// ```
+#[proc_macro_derive(TableEntity, attributes(table))]
+pub fn derive_table_entity(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    table_entity::derive_table_entity_impl(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{catch_unwind, UnwindSafe};
+
+    pub fn should_panic<T>(callback: T, msg: &str)
+    where
+        T: FnOnce() + UnwindSafe,
+    {
+        let result = catch_unwind(callback);
+        assert!(result.is_err(), "the function did not panic");
+
+        if cfg!(feature = "unstable") {
+            assert_eq!(
+                result.unwrap_err().downcast_ref::<String>().unwrap(),
+                "aborting due to previous error",
+                "the panic message is not the expected one"
+            );
+        } else {
+            assert_eq!(
+                result.unwrap_err().downcast_ref::<String>().unwrap(),
+                msg,
+                "the panic message is not the expected one"
+            );
+        }
+    }
+}
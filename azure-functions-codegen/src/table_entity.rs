@@ -0,0 +1,417 @@
+use crate::func::get_generic_argument_type;
+use azure_functions_shared::{
+    codegen::{get_string_value, last_segment_in_path, macro_panic},
+    util::to_camel_case,
+};
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{
+    parse, spanned::Spanned, Attribute, Data, DeriveInput, Field, Fields, Ident, Meta, NestedMeta,
+    Type,
+};
+
+const EDM_TYPES: &[&str] = &["Binary", "DateTime", "Guid", "Int64"];
+
+fn iter_meta_args<F>(attrs: &[Attribute], name: &str, mut callback: F)
+where
+    F: FnMut(&Meta),
+{
+    for attr in attrs
+        .iter()
+        .filter(|a| last_segment_in_path(&a.path).ident == name)
+    {
+        match attr.parse_meta() {
+            Ok(Meta::List(list)) => {
+                for nested in list.nested.iter() {
+                    if let NestedMeta::Meta(meta) = nested {
+                        callback(meta);
+                    }
+                }
+            }
+            Ok(_) => {}
+            Err(e) => macro_panic(
+                attr.span(),
+                format!("failed to parse attribute arguments: {}", e),
+            ),
+        }
+    }
+}
+
+fn meta_name(meta: &Meta) -> String {
+    last_segment_in_path(meta.path()).ident.to_string()
+}
+
+fn get_rename_all(input: &DeriveInput) -> Option<String> {
+    let mut rename_all = None;
+
+    iter_meta_args(&input.attrs, "serde", |meta| match meta {
+        Meta::NameValue(nv) if meta_name(meta) == "rename_all" => {
+            rename_all = Some(get_string_value("rename_all", &nv.lit));
+        }
+        Meta::List(list) if meta_name(meta) == "rename_all" => {
+            for nested in list.nested.iter() {
+                if let NestedMeta::Meta(Meta::NameValue(nv)) = nested {
+                    if last_segment_in_path(&nv.path).ident == "serialize" {
+                        rename_all = Some(get_string_value("serialize", &nv.lit));
+                    }
+                }
+            }
+        }
+        _ => {}
+    });
+
+    rename_all
+}
+
+fn rename(name: &str, rename_all: Option<&str>, span: Span) -> String {
+    match rename_all {
+        None | Some("snake_case") => name.to_string(),
+        Some("lowercase") => name.to_lowercase(),
+        Some("UPPERCASE") | Some("SCREAMING_SNAKE_CASE") => name.to_uppercase(),
+        Some("camelCase") => to_camel_case(name),
+        Some("PascalCase") => {
+            let mut name = to_camel_case(name);
+            if let Some(first) = name.get_mut(0..1) {
+                first.make_ascii_uppercase();
+            }
+            name
+        }
+        Some("kebab-case") => name.replace('_', "-"),
+        Some("SCREAMING-KEBAB-CASE") => name.replace('_', "-").to_uppercase(),
+        Some(v) => macro_panic(span, format!("unsupported 'rename_all' value '{}'", v)),
+    }
+}
+
+fn infer_edm_type(ty: &Type) -> Option<&'static str> {
+    let tp = match ty {
+        Type::Path(tp) => tp,
+        Type::Paren(tp) => return infer_edm_type(&tp.elem),
+        _ => return None,
+    };
+
+    let last = last_segment_in_path(&tp.path);
+
+    if let Some(inner) = get_generic_argument_type(last, "Option") {
+        return infer_edm_type(inner);
+    }
+
+    if let Some(Type::Path(inner)) = get_generic_argument_type(last, "Vec") {
+        if last_segment_in_path(&inner.path).ident == "u8" {
+            return Some("Binary");
+        }
+        return None;
+    }
+
+    match last.ident.to_string().as_str() {
+        // Edm.Int64 is signed, so unsigned integers must be annotated explicitly
+        "i64" | "isize" => Some("Int64"),
+        "DateTime" => Some("DateTime"),
+        "Uuid" => Some("Guid"),
+        _ => None,
+    }
+}
+
+fn get_field_property(field: &Field, rename_all: Option<&str>) -> Option<(String, Ident)> {
+    let ident = field.ident.as_ref().unwrap();
+    let mut name = None;
+    let mut skip = false;
+    let mut edm_type = None;
+
+    iter_meta_args(&field.attrs, "serde", |meta| match meta {
+        Meta::NameValue(nv) if meta_name(meta) == "rename" => {
+            name = Some(get_string_value("rename", &nv.lit));
+        }
+        Meta::List(list) if meta_name(meta) == "rename" => {
+            for nested in list.nested.iter() {
+                if let NestedMeta::Meta(Meta::NameValue(nv)) = nested {
+                    if last_segment_in_path(&nv.path).ident == "serialize" {
+                        name = Some(get_string_value("serialize", &nv.lit));
+                    }
+                }
+            }
+        }
+        Meta::Path(_) => {
+            if let "skip" | "skip_serializing" | "flatten" = meta_name(meta).as_str() {
+                skip = true;
+            }
+        }
+        _ => {}
+    });
+
+    iter_meta_args(&field.attrs, "table", |meta| match meta {
+        Meta::NameValue(nv) if meta_name(meta) == "edm" => {
+            let value = get_string_value("edm", &nv.lit);
+            let value = value.trim_start_matches("Edm.");
+
+            match EDM_TYPES.iter().find(|t| **t == value) {
+                Some(t) => edm_type = Some(*t),
+                None => macro_panic(
+                    nv.lit.span(),
+                    format!(
+                        "expected one of {} for the 'edm' argument",
+                        EDM_TYPES
+                            .iter()
+                            .map(|t| format!("'{}'", t))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                ),
+            }
+        }
+        _ => macro_panic(meta.span(), "expected an 'edm' argument"),
+    });
+
+    if skip {
+        return None;
+    }
+
+    let edm_type = edm_type.or_else(|| infer_edm_type(&field.ty))?;
+
+    let name = name.unwrap_or_else(|| {
+        rename(
+            ident.to_string().trim_start_matches("r#"),
+            rename_all,
+            field.span(),
+        )
+    });
+
+    Some((name, Ident::new(edm_type, field.span())))
+}
+
+fn get_properties(input: &DeriveInput) -> Vec<(String, Ident)> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => macro_panic(
+                input.ident.span(),
+                "table entities must be structs with named fields",
+            ),
+        },
+        _ => macro_panic(
+            input.ident.span(),
+            "table entities must be structs with named fields",
+        ),
+    };
+
+    let rename_all = get_rename_all(input);
+
+    fields
+        .iter()
+        .filter_map(|f| get_field_property(f, rename_all.as_ref().map(String::as_str)))
+        .collect()
+}
+
+pub fn derive_table_entity_impl(input: TokenStream) -> TokenStream {
+    let input = match parse::<DeriveInput>(input) {
+        Ok(input) => input,
+        Err(e) => macro_panic(Span::call_site(), e.to_string()),
+    };
+
+    let (names, edm_types): (Vec<_>, Vec<_>) = get_properties(&input).into_iter().unzip();
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    quote!(
+        impl #impl_generics ::azure_functions::bindings::TableEntity for #ident #ty_generics #where_clause {
+            fn edm_types() -> &'static [(&'static str, ::azure_functions::bindings::EdmType)] {
+                &[#((#names, ::azure_functions::bindings::EdmType::#edm_types),)*]
+            }
+        }
+    )
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::should_panic;
+    use syn::parse_str;
+
+    fn properties(input: &str) -> Vec<(String, String)> {
+        get_properties(&parse_str::<DeriveInput>(input).unwrap())
+            .into_iter()
+            .map(|(name, edm_type)| (name, edm_type.to_string()))
+            .collect()
+    }
+
+    fn property(name: &str, edm_type: &str) -> (String, String) {
+        (name.to_string(), edm_type.to_string())
+    }
+
+    #[test]
+    fn it_infers_edm_types() {
+        assert_eq!(
+            properties(
+                "struct Entity {
+                    a: i64,
+                    b: isize,
+                    c: Option<DateTime<Utc>>,
+                    d: uuid::Uuid,
+                    e: Vec<u8>,
+                    f: Option<Vec<u8>>,
+                    g: String,
+                    h: Vec<i64>,
+                    i: i32,
+                }"
+            ),
+            [
+                property("a", "Int64"),
+                property("b", "Int64"),
+                property("c", "DateTime"),
+                property("d", "Guid"),
+                property("e", "Binary"),
+                property("f", "Binary"),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_does_not_infer_unsigned_integers() {
+        assert!(properties("struct Entity { a: u64, b: usize, c: Option<u64> }").is_empty());
+        assert_eq!(
+            properties(
+                r#"struct Entity {
+                    #[table(edm = "Int64")]
+                    a: u64,
+                }"#
+            ),
+            [property("a", "Int64")]
+        );
+    }
+
+    #[test]
+    fn it_uses_the_edm_argument() {
+        assert_eq!(
+            properties(
+                r#"struct Entity {
+                    #[table(edm = "Guid")]
+                    a: String,
+                    #[table(edm = "Edm.DateTime")]
+                    b: String,
+                    #[table(edm = "Binary")]
+                    c: i64,
+                }"#
+            ),
+            [
+                property("a", "Guid"),
+                property("b", "DateTime"),
+                property("c", "Binary"),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_follows_serde_renames() {
+        assert_eq!(
+            properties(
+                r#"#[serde(rename_all = "camelCase")]
+                struct Entity {
+                    total_cents: i64,
+                    #[serde(rename = "Id")]
+                    customer_id: Uuid,
+                    #[serde(rename(serialize = "ser", deserialize = "de"))]
+                    placed_at: DateTime<Utc>,
+                }"#
+            ),
+            [
+                property("totalCents", "Int64"),
+                property("Id", "Guid"),
+                property("ser", "DateTime"),
+            ]
+        );
+        assert_eq!(
+            properties(
+                r#"#[serde(rename_all(serialize = "PascalCase"))]
+                struct Entity {
+                    total_cents: i64,
+                    r#type: i64,
+                }"#
+            ),
+            [property("TotalCents", "Int64"), property("Type", "Int64")]
+        );
+        assert_eq!(
+            properties(
+                r#"#[serde(rename_all = "SCREAMING-KEBAB-CASE")]
+                struct Entity {
+                    total_cents: i64,
+                }"#
+            ),
+            [property("TOTAL-CENTS", "Int64")]
+        );
+    }
+
+    #[test]
+    fn it_skips_fields() {
+        assert_eq!(
+            properties(
+                r#"struct Entity {
+                    #[serde(skip)]
+                    a: i64,
+                    #[serde(skip_serializing)]
+                    b: i64,
+                    #[serde(flatten)]
+                    #[table(edm = "Guid")]
+                    c: Inner,
+                    #[serde(skip_deserializing)]
+                    d: i64,
+                }"#
+            ),
+            [property("d", "Int64")]
+        );
+    }
+
+    #[test]
+    fn it_rejects_invalid_arguments() {
+        should_panic(
+            || {
+                properties(
+                    r#"struct Entity {
+                        #[table(edm = "Int32")]
+                        a: i32,
+                    }"#,
+                );
+            },
+            "expected one of 'Binary', 'DateTime', 'Guid', 'Int64' for the 'edm' argument",
+        );
+        should_panic(
+            || {
+                properties(
+                    r#"struct Entity {
+                        #[table(name = "A")]
+                        a: i64,
+                    }"#,
+                );
+            },
+            "expected an 'edm' argument",
+        );
+        should_panic(
+            || {
+                properties(
+                    r#"#[serde(rename_all = "Title Case")]
+                    struct Entity {
+                        a: i64,
+                    }"#,
+                );
+            },
+            "unsupported 'rename_all' value 'Title Case'",
+        );
+    }
+
+    #[test]
+    fn it_requires_a_struct_with_named_fields() {
+        should_panic(
+            || {
+                properties("struct Entity(i64);");
+            },
+            "table entities must be structs with named fields",
+        );
+        should_panic(
+            || {
+                properties("enum Entity { A }");
+            },
+            "table entities must be structs with named fields",
+        );
+    }
+}
@@ -1,16 +1,19 @@
+use crate::durable_client::DurableClient;
 use crate::endpoint::Endpoint;
 use crate::error::ClientError;
 use crate::Result;
 use chrono::{DateTime, Utc};
-use futures::TryStreamExt;
+use futures::stream::{Stream, TryStreamExt};
 use hyper::{self, Body, Request, StatusCode};
 use serde::{Deserialize, Serialize};
 use serde_json::{from_slice, to_string, Value};
 use std::fmt::{Display, Formatter};
 use url::Url;
 
+const CONTINUATION_TOKEN_HEADER: &str = "x-ms-continuation-token";
+
 /// Represents the runtime status of an orchestration.
-#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum OrchestrationRuntimeStatus {
     /// The orchestration is running.
     Running,
@@ -33,7 +36,7 @@ impl Display for OrchestrationRuntimeStatus {
 }
 
 /// Represents an orchestration history event.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct OrchestrationHistoryEvent {
     /// The event type.
@@ -42,18 +45,32 @@ pub struct OrchestrationHistoryEvent {
     pub orchestration_status: Option<OrchestrationRuntimeStatus>,
     /// The function name for the event.
     pub function_name: Option<String>,
+    /// The name of the event (used by external events).
+    pub name: Option<String>,
+    /// The input for the event.
+    pub input: Option<Value>,
     /// The result (output) for the event.
     pub result: Option<Value>,
+    /// The failure reason for the event.
+    pub reason: Option<String>,
+    /// The failure details for the event.
+    pub details: Option<String>,
     /// The scheduled time for the event.
     pub scheduled_time: Option<DateTime<Utc>>,
+    /// The fire time for timer events.
+    pub fire_at: Option<DateTime<Utc>>,
     /// The timestamp for the event.
     pub timestamp: DateTime<Utc>,
 }
 
 /// Represents an orchestration's status.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OrchestrationStatus {
+    /// The name of the orchestration.
+    pub name: Option<String>,
+    /// The instance id of the orchestration.
+    pub instance_id: Option<String>,
     /// The runtime status of the orchestration.
     pub runtime_status: OrchestrationRuntimeStatus,
     /// The input of the orchestration.
@@ -65,9 +82,84 @@ pub struct OrchestrationStatus {
     /// The created time of the orchestration.
     pub created_time: DateTime<Utc>,
     /// The event history of the orchestration.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub history_events: Option<Vec<OrchestrationHistoryEvent>>,
 }
 
+/// Represents a query for orchestration instances.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InstanceQuery {
+    /// Only return instances created at or after the given time.
+    pub created_time_from: Option<DateTime<Utc>>,
+    /// Only return instances created at or before the given time.
+    pub created_time_to: Option<DateTime<Utc>>,
+    /// Only return instances with one of the given runtime statuses.
+    pub runtime_statuses: Vec<OrchestrationRuntimeStatus>,
+    /// Only return instances with an instance id starting with the given prefix.
+    pub instance_id_prefix: Option<String>,
+    /// The maximum number of instances to return per page.
+    pub page_size: Option<u32>,
+    /// Whether or not to include the history of each instance.
+    pub show_history: bool,
+    /// Whether or not to include activity outputs in the history of each instance.
+    pub show_history_output: bool,
+    /// Whether or not to include the input of each instance.
+    pub show_input: bool,
+}
+
+impl InstanceQuery {
+    fn append_to(&self, url: &mut Url) {
+        let mut query = url.query_pairs_mut();
+
+        if let Some(t) = self.created_time_from {
+            query.append_pair("createdTimeFrom", &t.to_rfc3339());
+        }
+        if let Some(t) = self.created_time_to {
+            query.append_pair("createdTimeTo", &t.to_rfc3339());
+        }
+        if !self.runtime_statuses.is_empty() {
+            query.append_pair(
+                "runtimeStatus",
+                &self
+                    .runtime_statuses
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(","),
+            );
+        }
+        if let Some(prefix) = &self.instance_id_prefix {
+            query.append_pair("instanceIdPrefix", prefix);
+        }
+        if let Some(size) = self.page_size {
+            query.append_pair("top", &size.to_string());
+        }
+
+        query
+            .append_pair("showHistory", bool_param(self.show_history))
+            .append_pair("showHistoryOutput", bool_param(self.show_history_output))
+            .append_pair("showInput", bool_param(self.show_input));
+    }
+}
+
+/// Represents a single page of an instance query.
+#[derive(Debug, Clone)]
+pub struct InstanceQueryPage {
+    /// The instances in the page.
+    pub instances: Vec<OrchestrationStatus>,
+    /// The continuation token for the next page or `None` if this is the last page.
+    pub continuation_token: Option<String>,
+}
+
+/// Represents the progress of purging instance history by query.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PurgeProgress {
+    /// The number of instances purged from the current page.
+    pub instances_deleted: u32,
+    /// The total number of instances purged so far.
+    pub total_instances_deleted: u32,
+}
+
 /// Represents new orchestration data.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -170,6 +262,12 @@ impl Client {
     }
 
     /// Queries the status for instances in a given date range or with runtime status.
+    ///
+    /// All pages of the query are retrieved by following the continuation tokens returned by the
+    /// host, so every matching instance is returned.
+    ///
+    /// The `top` parameter is the maximum number of instances requested per page; it does not limit
+    /// the total number of instances returned. Use `query_instances_page` to retrieve a single page.
     #[allow(clippy::too_many_arguments)]
     pub async fn query_instances<I>(
         &self,
@@ -184,59 +282,73 @@ impl Client {
     where
         I: Iterator<Item = OrchestrationRuntimeStatus>,
     {
-        let mut url = self.endpoint.status_query_url(None);
+        self.query_instances_stream(InstanceQuery {
+            created_time_from,
+            created_time_to,
+            runtime_statuses: runtime_statuses.map(Iterator::collect).unwrap_or_default(),
+            instance_id_prefix: None,
+            page_size: top,
+            show_history,
+            show_history_output,
+            show_input,
+        })
+        .try_collect()
+        .await
+    }
 
-        {
-            let mut query = url.query_pairs_mut();
+    /// Queries a single page of instances.
+    ///
+    /// Pass the continuation token of the previous page to retrieve the next page.
+    pub async fn query_instances_page(
+        &self,
+        query: &InstanceQuery,
+        continuation_token: Option<&str>,
+    ) -> Result<InstanceQueryPage> {
+        let mut url = self.endpoint.status_query_url(None);
+        query.append_to(&mut url);
 
-            created_time_from.map(|t| query.append_pair("createdTimeFrom", &t.to_rfc3339()));
-            created_time_to.map(|t| query.append_pair("createdTimeTo", &t.to_rfc3339()));
-            runtime_statuses.map(|s| {
-                query.append_pair(
-                    "runtimeStatus",
-                    &s.map(|s| s.to_string()).collect::<Vec<_>>().join(","),
-                )
-            });
+        let mut builder = Request::builder();
+        builder
+            .method("GET")
+            .uri(url.into_string())
+            .header("Content-Type", "application/json");
 
-            top.map(|t| query.append_pair("top", &t.to_string()));
-
-            query
-                .append_pair("showHistory", if show_history { "true" } else { "false " })
-                .append_pair(
-                    "showHistoryOutput",
-                    if show_history_output {
-                        "true"
-                    } else {
-                        "false "
-                    },
-                )
-                .append_pair("showInput", if show_input { "true" } else { "false " });
+        if let Some(token) = continuation_token {
+            builder.header(CONTINUATION_TOKEN_HEADER, token);
         }
 
-        let req = Request::builder()
-            .method("GET")
-            .uri(url.into_string())
-            .header("Content-Type", "application/json")
-            .body(Body::empty())
-            .unwrap();
+        let req = builder.body(Body::empty()).unwrap();
 
         match self.client.request(req).await {
             Ok(res) => match res.status() {
                 StatusCode::OK | StatusCode::ACCEPTED => {
+                    let continuation_token = res
+                        .headers()
+                        .get(CONTINUATION_TOKEN_HEADER)
+                        .and_then(|v| v.to_str().ok())
+                        .filter(|t| !is_last_page_token(t))
+                        .map(ToOwned::to_owned);
+
                     let body = res.into_body().try_concat().await;
-                    body.map(|b| {
-                        from_slice(&b).map_err(|e| {
-                            ClientError::Message(format!(
-                                "failed to deserialize orchestration status: {}",
-                                e
-                            ))
+                    let instances = body
+                        .map(|b| {
+                            from_slice(&b).map_err(|e| {
+                                ClientError::Message(format!(
+                                    "failed to deserialize orchestration status: {}",
+                                    e
+                                ))
+                            })
                         })
-                    })
-                    .unwrap_or_else(|e| {
-                        Err(ClientError::Message(format!(
-                            "failed to read response: {}",
-                            e
-                        )))
+                        .unwrap_or_else(|e| {
+                            Err(ClientError::Message(format!(
+                                "failed to read response: {}",
+                                e
+                            )))
+                        })?;
+
+                    Ok(InstanceQueryPage {
+                        instances,
+                        continuation_token,
                     })
                 }
                 StatusCode::BAD_REQUEST => Err(ClientError::InstanceFailedOrTerminated),
@@ -251,6 +363,16 @@ impl Client {
         }
     }
 
+    /// Queries instances as a stream, following continuation tokens until all pages are retrieved.
+    ///
+    /// The stream ends after the first error.
+    pub fn query_instances_stream(
+        &self,
+        query: InstanceQuery,
+    ) -> impl Stream<Item = Result<OrchestrationStatus>> + '_ {
+        (self as &dyn DurableClient).query_instances_stream(query)
+    }
+
     /// Purges the history of the given orchestration instance.
     pub async fn purge_history(&self, instance_id: &str) -> Result<()> {
         let req = Request::builder()
@@ -347,7 +469,19 @@ impl Client {
         }
     }
 
+    /// Purges the history of instances matching the given query, one page at a time.
+    ///
+    /// The stream yields the progress after each page is purged and ends after the first error.
+    pub fn purge_history_stream(
+        &self,
+        query: InstanceQuery,
+    ) -> impl Stream<Item = Result<PurgeProgress>> + '_ {
+        (self as &dyn DurableClient).purge_history_stream(query)
+    }
+
     /// Raises an event for the given orchestration instance.
+    ///
+    /// The event data is serialized as JSON.
     pub async fn raise_event<D>(
         &self,
         instance_id: &str,
@@ -355,8 +489,11 @@ impl Client {
         event_data: D,
     ) -> Result<()>
     where
-        D: Into<Value>,
+        D: Serialize,
     {
+        let body = to_string(&event_data)
+            .map_err(|e| ClientError::Message(format!("failed to serialize event data: {}", e)))?;
+
         let req = Request::builder()
             .method("POST")
             .uri(
@@ -365,7 +502,7 @@ impl Client {
                     .into_string(),
             )
             .header("Content-Type", "application/json")
-            .body(Body::from(to_string(&event_data.into()).unwrap()))
+            .body(Body::from(body))
             .unwrap();
 
         match self.client.request(req).await {
@@ -485,11 +622,26 @@ impl Client {
     }
 }
 
+fn bool_param(value: bool) -> &'static str {
+    if value {
+        "true"
+    } else {
+        "false"
+    }
+}
+
+fn is_last_page_token(token: &str) -> bool {
+    // The HTTP API returns a base64 encoded "null" when there are no more pages
+    token.is_empty() || token == "bnVsbA=="
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use chrono::offset::TimeZone;
+    use mockito::{mock, Matcher};
     use serde_json::from_str;
+    use tokio::runtime::Runtime;
 
     #[test]
     fn test_instance_history() {
@@ -586,4 +738,135 @@ mod tests {
         assert_eq!(instance_status.custom_status.is_some(), true);
         assert_eq!(instance_status.custom_status.unwrap().is_object(), true);
     }
+
+    #[test]
+    fn it_builds_instance_query_parameters() {
+        let mut url = Url::parse("http://localhost/instances").unwrap();
+
+        InstanceQuery {
+            created_time_from: Some(Utc.ymd(2019, 10, 1).and_hms(0, 0, 0)),
+            created_time_to: None,
+            runtime_statuses: vec![
+                OrchestrationRuntimeStatus::Running,
+                OrchestrationRuntimeStatus::Pending,
+            ],
+            instance_id_prefix: Some("job-".to_string()),
+            page_size: Some(100),
+            show_history: true,
+            show_history_output: false,
+            show_input: false,
+        }
+        .append_to(&mut url);
+
+        assert_eq!(
+            url.query(),
+            Some("createdTimeFrom=2019-10-01T00%3A00%3A00%2B00%3A00&runtimeStatus=Running%2CPending&instanceIdPrefix=job-&top=100&showHistory=true&showHistoryOutput=false&showInput=false")
+        );
+    }
+
+    #[test]
+    fn it_detects_the_last_page_token() {
+        assert!(is_last_page_token(""));
+        assert!(is_last_page_token("bnVsbA=="));
+        assert!(!is_last_page_token("eyJ0b2tlbiI6IjEifQ=="));
+    }
+
+    #[test]
+    fn it_deserializes_instance_identity() {
+        let status: OrchestrationStatus = from_str(
+            r#"{
+                "name": "HelloSequence",
+                "instanceId": "job-1",
+                "runtimeStatus": "Running",
+                "createdTime": "2019-10-01T00:00:00Z"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            status.name.as_ref().map(String::as_str),
+            Some("HelloSequence")
+        );
+        assert_eq!(
+            status.instance_id.as_ref().map(String::as_str),
+            Some("job-1")
+        );
+    }
+
+    #[test]
+    fn it_follows_continuation_tokens() {
+        let _first = mock("GET", "/runtime/webhooks/durabletask/instances")
+            .match_query(Matcher::Any)
+            .match_header(CONTINUATION_TOKEN_HEADER, Matcher::Missing)
+            .with_header(CONTINUATION_TOKEN_HEADER, "page-2")
+            .with_body(r#"[{"instanceId":"job-1","runtimeStatus":"Completed","createdTime":"2019-10-01T00:00:00Z"},{"instanceId":"job-2","runtimeStatus":"Completed","createdTime":"2019-10-01T00:00:00Z"}]"#)
+            .expect(2)
+            .create();
+        let _second = mock("GET", "/runtime/webhooks/durabletask/instances")
+            .match_query(Matcher::Any)
+            .match_header(CONTINUATION_TOKEN_HEADER, "page-2")
+            .with_header(CONTINUATION_TOKEN_HEADER, "bnVsbA==")
+            .with_body(r#"[{"instanceId":"job-3","runtimeStatus":"Completed","createdTime":"2019-10-01T00:00:00Z"}]"#)
+            .expect(2)
+            .create();
+        let purge = mock(
+            "DELETE",
+            Matcher::Regex("^/runtime/webhooks/durabletask/instances/job-[1-3]$".to_string()),
+        )
+        .match_query(Matcher::Any)
+        .expect(3)
+        .create();
+
+        let client = Client::new(&format!(
+            "{}/runtime/webhooks/durabletask/instances?taskHub=hub&connection=Storage&code=key",
+            mockito::server_url()
+        ));
+
+        let rt = Runtime::new().unwrap();
+
+        let statuses = rt
+            .block_on(client.query_instances(
+                None,
+                None,
+                None::<std::iter::Empty<OrchestrationRuntimeStatus>>,
+                Some(2),
+                false,
+                false,
+                false,
+            ))
+            .unwrap();
+        assert_eq!(
+            statuses
+                .iter()
+                .map(|s| s.instance_id.as_ref().unwrap().as_str())
+                .collect::<Vec<_>>(),
+            vec!["job-1", "job-2", "job-3"]
+        );
+
+        let progress: Vec<_> = rt
+            .block_on(
+                client
+                    .purge_history_stream(InstanceQuery {
+                        page_size: Some(2),
+                        ..Default::default()
+                    })
+                    .try_collect::<Vec<_>>(),
+            )
+            .unwrap();
+        assert_eq!(
+            progress,
+            vec![
+                PurgeProgress {
+                    instances_deleted: 2,
+                    total_instances_deleted: 2
+                },
+                PurgeProgress {
+                    instances_deleted: 1,
+                    total_instances_deleted: 3
+                },
+            ]
+        );
+
+        purge.assert();
+    }
 }
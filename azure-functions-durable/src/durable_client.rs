@@ -0,0 +1,225 @@
+use crate::client::{
+    Client, InstanceQuery, InstanceQueryPage, OrchestrationData, OrchestrationRuntimeStatus,
+    OrchestrationStatus, PurgeProgress,
+};
+use crate::error::ClientError;
+use crate::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::stream::{self, Stream, TryStreamExt};
+use serde_json::Value;
+
+/// Represents the operations of a Durable Functions client.
+///
+/// This trait is implemented by `Client`, which calls the Durable Functions HTTP API,
+/// and by `FakeClient`, which keeps orchestration state in memory for unit testing.
+#[async_trait]
+pub trait DurableClient: Send + Sync {
+    /// Gets the task hub associated with the client.
+    fn task_hub(&self) -> &str;
+
+    /// Gets the status of an orchestration instance.
+    async fn instance_status(
+        &self,
+        instance_id: &str,
+        show_history: bool,
+        show_history_output: bool,
+        show_input: bool,
+    ) -> Result<OrchestrationStatus>;
+
+    /// Queries a single page of instances.
+    async fn query_instances_page(
+        &self,
+        query: &InstanceQuery,
+        continuation_token: Option<&str>,
+    ) -> Result<InstanceQueryPage>;
+
+    /// Purges the history of the given orchestration instance.
+    async fn purge_history(&self, instance_id: &str) -> Result<()>;
+
+    /// Purges the history of orchestrations matching the given date range or runtime statuses.
+    ///
+    /// An empty slice of runtime statuses matches any status.
+    async fn purge_history_by_query(
+        &self,
+        created_time_from: Option<DateTime<Utc>>,
+        created_time_to: Option<DateTime<Utc>>,
+        runtime_statuses: &[OrchestrationRuntimeStatus],
+    ) -> Result<u32>;
+
+    /// Raises an event for the given orchestration instance.
+    async fn raise_event(
+        &self,
+        instance_id: &str,
+        event_name: &str,
+        event_data: Value,
+    ) -> Result<()>;
+
+    /// Restores a failed orchestration instance into a running state by replaying the most recent failed operations.
+    async fn rewind(&self, instance_id: &str, reason: &str) -> Result<()>;
+
+    /// Starts a new orchestration by calling the given orchestration function.
+    async fn start_new(
+        &self,
+        function_name: &str,
+        instance_id: Option<&str>,
+        input: Value,
+    ) -> Result<OrchestrationData>;
+
+    /// Terminates a running orchestration instance.
+    async fn terminate(&self, instance_id: &str, reason: &str) -> Result<()>;
+}
+
+impl dyn DurableClient {
+    /// Queries instances as a stream, following continuation tokens until all pages are retrieved.
+    ///
+    /// The stream ends after the first error.
+    pub fn query_instances_stream(
+        &self,
+        query: InstanceQuery,
+    ) -> impl Stream<Item = Result<OrchestrationStatus>> + '_ {
+        self.query_pages(query)
+            .map_ok(|page| stream::iter(page.instances.into_iter().map(Ok)))
+            .try_flatten()
+    }
+
+    /// Purges the history of instances matching the given query, one page at a time.
+    ///
+    /// The stream yields the progress after each page is purged and ends after the first error.
+    pub fn purge_history_stream(
+        &self,
+        query: InstanceQuery,
+    ) -> impl Stream<Item = Result<PurgeProgress>> + '_ {
+        stream::unfold(Some((query, None, 0)), move |state| async move {
+            let (query, token, total): (InstanceQuery, Option<String>, u32) = state?;
+            let page = match self
+                .query_instances_page(&query, token.as_ref().map(String::as_str))
+                .await
+            {
+                Ok(page) => page,
+                Err(e) => return Some((Err(e), None)),
+            };
+
+            let mut deleted = 0;
+            for id in page.instances.iter().filter_map(|i| i.instance_id.as_ref()) {
+                match self.purge_history(id).await {
+                    Ok(()) => deleted += 1,
+                    Err(ClientError::InstanceNotFound) => {}
+                    Err(e) => return Some((Err(e), None)),
+                }
+            }
+
+            let progress = PurgeProgress {
+                instances_deleted: deleted,
+                total_instances_deleted: total + deleted,
+            };
+
+            let next = page
+                .continuation_token
+                .map(|t| (query, Some(t), progress.total_instances_deleted));
+
+            Some((Ok(progress), next))
+        })
+    }
+
+    fn query_pages(
+        &self,
+        query: InstanceQuery,
+    ) -> impl Stream<Item = Result<InstanceQueryPage>> + '_ {
+        // The state is `None` once the last page (or an error) has been returned.
+        stream::unfold(Some((query, None)), move |state| async move {
+            let (query, token): (InstanceQuery, Option<String>) = state?;
+            match self
+                .query_instances_page(&query, token.as_ref().map(String::as_str))
+                .await
+            {
+                Ok(page) => {
+                    let next = page.continuation_token.clone().map(|t| (query, Some(t)));
+                    Some((Ok(page), next))
+                }
+                Err(e) => Some((Err(e), None)),
+            }
+        })
+    }
+}
+
+#[async_trait]
+impl DurableClient for Client {
+    fn task_hub(&self) -> &str {
+        Client::task_hub(self)
+    }
+
+    async fn instance_status(
+        &self,
+        instance_id: &str,
+        show_history: bool,
+        show_history_output: bool,
+        show_input: bool,
+    ) -> Result<OrchestrationStatus> {
+        Client::instance_status(
+            self,
+            instance_id,
+            show_history,
+            show_history_output,
+            show_input,
+        )
+        .await
+    }
+
+    async fn query_instances_page(
+        &self,
+        query: &InstanceQuery,
+        continuation_token: Option<&str>,
+    ) -> Result<InstanceQueryPage> {
+        Client::query_instances_page(self, query, continuation_token).await
+    }
+
+    async fn purge_history(&self, instance_id: &str) -> Result<()> {
+        Client::purge_history(self, instance_id).await
+    }
+
+    async fn purge_history_by_query(
+        &self,
+        created_time_from: Option<DateTime<Utc>>,
+        created_time_to: Option<DateTime<Utc>>,
+        runtime_statuses: &[OrchestrationRuntimeStatus],
+    ) -> Result<u32> {
+        Client::purge_history_by_query(
+            self,
+            created_time_from,
+            created_time_to,
+            if runtime_statuses.is_empty() {
+                None
+            } else {
+                Some(runtime_statuses.iter().cloned())
+            },
+        )
+        .await
+    }
+
+    async fn raise_event(
+        &self,
+        instance_id: &str,
+        event_name: &str,
+        event_data: Value,
+    ) -> Result<()> {
+        Client::raise_event(self, instance_id, event_name, event_data).await
+    }
+
+    async fn rewind(&self, instance_id: &str, reason: &str) -> Result<()> {
+        Client::rewind(self, instance_id, reason).await
+    }
+
+    async fn start_new(
+        &self,
+        function_name: &str,
+        instance_id: Option<&str>,
+        input: Value,
+    ) -> Result<OrchestrationData> {
+        Client::start_new(self, function_name, instance_id, input).await
+    }
+
+    async fn terminate(&self, instance_id: &str, reason: &str) -> Result<()> {
+        Client::terminate(self, instance_id, reason).await
+    }
+}
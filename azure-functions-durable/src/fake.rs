@@ -0,0 +1,563 @@
+use crate::client::{
+    InstanceQuery, InstanceQueryPage, OrchestrationData, OrchestrationRuntimeStatus,
+    OrchestrationStatus,
+};
+use crate::durable_client::DurableClient;
+use crate::error::ClientError;
+use crate::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+
+/// Represents a call made to a `FakeClient`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClientCall {
+    /// The status of an instance was requested.
+    InstanceStatus {
+        /// The instance id.
+        instance_id: String,
+    },
+    /// A page of instances was queried.
+    QueryInstances {
+        /// The query.
+        query: InstanceQuery,
+        /// The continuation token for the page.
+        continuation_token: Option<String>,
+    },
+    /// The history of an instance was purged.
+    PurgeHistory {
+        /// The instance id.
+        instance_id: String,
+    },
+    /// The history of instances was purged by query.
+    PurgeHistoryByQuery {
+        /// The created time lower bound.
+        created_time_from: Option<DateTime<Utc>>,
+        /// The created time upper bound.
+        created_time_to: Option<DateTime<Utc>>,
+        /// The runtime statuses.
+        runtime_statuses: Vec<OrchestrationRuntimeStatus>,
+    },
+    /// An event was raised.
+    RaiseEvent {
+        /// The instance id.
+        instance_id: String,
+        /// The event name.
+        event_name: String,
+        /// The event data.
+        event_data: Value,
+    },
+    /// An instance was rewound.
+    Rewind {
+        /// The instance id.
+        instance_id: String,
+        /// The reason for the rewind.
+        reason: String,
+    },
+    /// A new orchestration was started.
+    StartNew {
+        /// The orchestration function name.
+        function_name: String,
+        /// The instance id of the new orchestration.
+        instance_id: String,
+        /// The orchestration input.
+        input: Value,
+    },
+    /// An instance was terminated.
+    Terminate {
+        /// The instance id.
+        instance_id: String,
+        /// The reason for the termination.
+        reason: String,
+    },
+}
+
+#[derive(Default)]
+struct State {
+    instances: BTreeMap<String, VecDeque<OrchestrationStatus>>,
+    calls: Vec<ClientCall>,
+    next_instance: u32,
+}
+
+impl State {
+    fn current(&mut self, instance_id: &str) -> Result<&mut OrchestrationStatus> {
+        self.instances
+            .get_mut(instance_id)
+            .and_then(VecDeque::front_mut)
+            .ok_or(ClientError::InstanceNotFound)
+    }
+
+    fn running(&mut self, instance_id: &str) -> Result<&mut OrchestrationStatus> {
+        let status = self.current(instance_id)?;
+        if is_finished(&status.runtime_status) {
+            return Err(ClientError::InstanceCompletedOrFailed);
+        }
+        Ok(status)
+    }
+}
+
+/// Represents an in-memory Durable Functions client for unit testing.
+///
+/// Every call is recorded and can be inspected with `calls`.
+///
+/// Statuses added with `add_status` are returned in order by `instance_status`; the last
+/// status added for an instance is returned for all subsequent calls.
+///
+/// Started orchestrations begin with a `Pending` status.
+/// Terminating an instance sets it to `Terminated` and rewinding a failed instance sets it to `Running`.
+///
+/// # Examples
+///
+/// ```rust
+/// use azure_functions_durable::{ClientCall, DurableClient, FakeClient, OrchestrationRuntimeStatus};
+/// use futures::executor::block_on;
+/// use serde_json::json;
+///
+/// let client = FakeClient::new("hub");
+///
+/// let data = block_on(client.start_new("orchestration", Some("1234"), json!("input"))).unwrap();
+/// assert_eq!(data.instance_id, "1234");
+///
+/// let status = block_on(client.instance_status("1234", false, false, false)).unwrap();
+/// assert_eq!(status.runtime_status, OrchestrationRuntimeStatus::Pending);
+///
+/// assert_eq!(
+///     client.calls()[0],
+///     ClientCall::StartNew {
+///         function_name: "orchestration".to_string(),
+///         instance_id: "1234".to_string(),
+///         input: json!("input"),
+///     }
+/// );
+/// ```
+pub struct FakeClient {
+    task_hub: String,
+    state: Mutex<State>,
+}
+
+impl FakeClient {
+    /// Creates a new fake client for the given task hub.
+    pub fn new(task_hub: &str) -> Self {
+        Self {
+            task_hub: task_hub.to_owned(),
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// Adds a status for the given instance.
+    ///
+    /// The instance id of the status is set to the given instance id if not present.
+    pub fn add_status(&self, instance_id: &str, mut status: OrchestrationStatus) {
+        if status.instance_id.is_none() {
+            status.instance_id = Some(instance_id.to_owned());
+        }
+
+        self.state
+            .lock()
+            .unwrap()
+            .instances
+            .entry(instance_id.to_owned())
+            .or_default()
+            .push_back(status);
+    }
+
+    /// Gets the calls made to the client.
+    pub fn calls(&self) -> Vec<ClientCall> {
+        self.state.lock().unwrap().calls.clone()
+    }
+
+    /// Gets the management URLs for the fake task hub.
+    ///
+    /// The URLs contain an `INSTANCEID` placeholder, like the URLs passed to the orchestration client binding.
+    pub fn management_urls(&self) -> OrchestrationData {
+        self.management_data("INSTANCEID")
+    }
+
+    fn management_data(&self, instance_id: &str) -> OrchestrationData {
+        let base = format!(
+            "http://localhost/runtime/webhooks/durabletask/instances/{}",
+            instance_id
+        );
+        let query = format!("taskHub={}&connection=Storage&code=fake", self.task_hub);
+
+        OrchestrationData {
+            instance_id: instance_id.to_owned(),
+            status_query_get_uri: format!("{}?{}", base, query),
+            send_event_post_uri: format!("{}/raiseEvent/{{eventName}}?{}", base, query),
+            terminate_post_uri: format!("{}/terminate?reason={{text}}&{}", base, query),
+            purge_history_delete_uri: format!("{}?{}", base, query),
+            rewind_post_uri: Some(format!("{}/rewind?reason={{text}}&{}", base, query)),
+        }
+    }
+}
+
+#[async_trait]
+impl DurableClient for FakeClient {
+    fn task_hub(&self) -> &str {
+        &self.task_hub
+    }
+
+    async fn instance_status(
+        &self,
+        instance_id: &str,
+        show_history: bool,
+        _show_history_output: bool,
+        show_input: bool,
+    ) -> Result<OrchestrationStatus> {
+        let mut state = self.state.lock().unwrap();
+        state.calls.push(ClientCall::InstanceStatus {
+            instance_id: instance_id.to_owned(),
+        });
+
+        let statuses = state
+            .instances
+            .get_mut(instance_id)
+            .ok_or(ClientError::InstanceNotFound)?;
+
+        let mut status = if statuses.len() > 1 {
+            statuses.pop_front().unwrap()
+        } else {
+            statuses
+                .front()
+                .cloned()
+                .ok_or(ClientError::InstanceNotFound)?
+        };
+
+        if !show_history {
+            status.history_events = None;
+        }
+        if !show_input {
+            status.input = None;
+        }
+
+        Ok(status)
+    }
+
+    async fn query_instances_page(
+        &self,
+        query: &InstanceQuery,
+        continuation_token: Option<&str>,
+    ) -> Result<InstanceQueryPage> {
+        let mut state = self.state.lock().unwrap();
+        state.calls.push(ClientCall::QueryInstances {
+            query: query.clone(),
+            continuation_token: continuation_token.map(ToOwned::to_owned),
+        });
+
+        // The continuation token is the id of the last instance of the previous page so that
+        // paging is unaffected by instances being purged between pages.
+        let mut matching = state
+            .instances
+            .iter()
+            .filter(|(id, _)| continuation_token.map_or(true, |t| id.as_str() > t))
+            .filter(|(id, _)| {
+                query
+                    .instance_id_prefix
+                    .as_ref()
+                    .map_or(true, |p| id.starts_with(p))
+            })
+            .filter_map(|(id, statuses)| statuses.front().map(|s| (id, s)))
+            .filter(|(_, s)| {
+                matches(
+                    s,
+                    query.created_time_from,
+                    query.created_time_to,
+                    &query.runtime_statuses,
+                )
+            });
+
+        let take = query.page_size.map_or(usize::MAX, |s| s as usize);
+        let page: Vec<_> = matching.by_ref().take(take).collect();
+
+        Ok(InstanceQueryPage {
+            continuation_token: match (page.last(), matching.next()) {
+                (Some((id, _)), Some(_)) => Some((*id).clone()),
+                _ => None,
+            },
+            instances: page.into_iter().map(|(_, s)| s.clone()).collect(),
+        })
+    }
+
+    async fn purge_history(&self, instance_id: &str) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.calls.push(ClientCall::PurgeHistory {
+            instance_id: instance_id.to_owned(),
+        });
+
+        state
+            .instances
+            .remove(instance_id)
+            .map(|_| ())
+            .ok_or(ClientError::InstanceNotFound)
+    }
+
+    async fn purge_history_by_query(
+        &self,
+        created_time_from: Option<DateTime<Utc>>,
+        created_time_to: Option<DateTime<Utc>>,
+        runtime_statuses: &[OrchestrationRuntimeStatus],
+    ) -> Result<u32> {
+        let mut state = self.state.lock().unwrap();
+        state.calls.push(ClientCall::PurgeHistoryByQuery {
+            created_time_from,
+            created_time_to,
+            runtime_statuses: runtime_statuses.to_vec(),
+        });
+
+        let before = state.instances.len();
+        state.instances.retain(|_, statuses| {
+            statuses.front().map_or(true, |s| {
+                !matches(s, created_time_from, created_time_to, runtime_statuses)
+            })
+        });
+
+        match before - state.instances.len() {
+            0 => Err(ClientError::InstanceNotFound),
+            deleted => Ok(deleted as u32),
+        }
+    }
+
+    async fn raise_event(
+        &self,
+        instance_id: &str,
+        event_name: &str,
+        event_data: Value,
+    ) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.calls.push(ClientCall::RaiseEvent {
+            instance_id: instance_id.to_owned(),
+            event_name: event_name.to_owned(),
+            event_data,
+        });
+
+        state.running(instance_id).map(|_| ())
+    }
+
+    async fn rewind(&self, instance_id: &str, reason: &str) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.calls.push(ClientCall::Rewind {
+            instance_id: instance_id.to_owned(),
+            reason: reason.to_owned(),
+        });
+
+        let status = state.current(instance_id)?;
+        if status.runtime_status == OrchestrationRuntimeStatus::Failed {
+            status.runtime_status = OrchestrationRuntimeStatus::Running;
+        }
+        Ok(())
+    }
+
+    async fn start_new(
+        &self,
+        function_name: &str,
+        instance_id: Option<&str>,
+        input: Value,
+    ) -> Result<OrchestrationData> {
+        let mut state = self.state.lock().unwrap();
+
+        let instance_id = match instance_id {
+            Some(id) => id.to_owned(),
+            None => {
+                state.next_instance += 1;
+                format!("instance-{}", state.next_instance)
+            }
+        };
+
+        state.calls.push(ClientCall::StartNew {
+            function_name: function_name.to_owned(),
+            instance_id: instance_id.clone(),
+            input: input.clone(),
+        });
+
+        let mut statuses = VecDeque::new();
+        statuses.push_back(OrchestrationStatus {
+            name: Some(function_name.to_owned()),
+            instance_id: Some(instance_id.clone()),
+            runtime_status: OrchestrationRuntimeStatus::Pending,
+            input: Some(input),
+            custom_status: None,
+            output: None,
+            created_time: Utc::now(),
+            history_events: None,
+        });
+        state.instances.insert(instance_id.clone(), statuses);
+
+        Ok(self.management_data(&instance_id))
+    }
+
+    async fn terminate(&self, instance_id: &str, reason: &str) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.calls.push(ClientCall::Terminate {
+            instance_id: instance_id.to_owned(),
+            reason: reason.to_owned(),
+        });
+
+        state.running(instance_id)?.runtime_status = OrchestrationRuntimeStatus::Terminated;
+        Ok(())
+    }
+}
+
+fn is_finished(status: &OrchestrationRuntimeStatus) -> bool {
+    match status {
+        OrchestrationRuntimeStatus::Running | OrchestrationRuntimeStatus::Pending => false,
+        _ => true,
+    }
+}
+
+fn matches(
+    status: &OrchestrationStatus,
+    created_time_from: Option<DateTime<Utc>>,
+    created_time_to: Option<DateTime<Utc>>,
+    runtime_statuses: &[OrchestrationRuntimeStatus],
+) -> bool {
+    created_time_from.map_or(true, |t| status.created_time >= t)
+        && created_time_to.map_or(true, |t| status.created_time <= t)
+        && (runtime_statuses.is_empty() || runtime_statuses.contains(&status.runtime_status))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use futures::stream::TryStreamExt;
+    use serde_json::json;
+
+    fn status(runtime_status: OrchestrationRuntimeStatus) -> OrchestrationStatus {
+        OrchestrationStatus {
+            name: None,
+            instance_id: None,
+            runtime_status,
+            input: None,
+            custom_status: None,
+            output: None,
+            created_time: Utc::now(),
+            history_events: None,
+        }
+    }
+
+    #[test]
+    fn it_returns_scripted_statuses_in_order() {
+        let client = FakeClient::new("hub");
+        client.add_status("1", status(OrchestrationRuntimeStatus::Running));
+        client.add_status("1", status(OrchestrationRuntimeStatus::Completed));
+
+        for expected in &[
+            OrchestrationRuntimeStatus::Running,
+            OrchestrationRuntimeStatus::Completed,
+            OrchestrationRuntimeStatus::Completed,
+        ] {
+            let status = block_on(client.instance_status("1", false, false, false)).unwrap();
+            assert_eq!(&status.runtime_status, expected);
+        }
+
+        assert_eq!(
+            block_on(client.instance_status("2", false, false, false)).unwrap_err(),
+            ClientError::InstanceNotFound
+        );
+        assert_eq!(client.calls().len(), 4);
+    }
+
+    #[test]
+    fn it_starts_and_terminates_instances() {
+        let client = FakeClient::new("hub");
+
+        let data = block_on(client.start_new("orchestration", None, json!(1))).unwrap();
+        assert_eq!(data.instance_id, "instance-1");
+        assert_eq!(data.status_query_get_uri, "http://localhost/runtime/webhooks/durabletask/instances/instance-1?taskHub=hub&connection=Storage&code=fake");
+
+        block_on(client.terminate("instance-1", "done")).unwrap();
+        assert_eq!(
+            block_on(client.terminate("instance-1", "again")).unwrap_err(),
+            ClientError::InstanceCompletedOrFailed
+        );
+        assert_eq!(
+            block_on(client.raise_event("instance-1", "event", Value::Null)).unwrap_err(),
+            ClientError::InstanceCompletedOrFailed
+        );
+
+        let status = block_on(client.instance_status("instance-1", false, false, true)).unwrap();
+        assert_eq!(
+            status.runtime_status,
+            OrchestrationRuntimeStatus::Terminated
+        );
+        assert_eq!(status.input, Some(json!(1)));
+
+        assert_eq!(
+            client.calls()[1],
+            ClientCall::Terminate {
+                instance_id: "instance-1".to_string(),
+                reason: "done".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn it_pages_queries() {
+        let client = FakeClient::new("hub");
+        for id in &["job-1", "job-2", "job-3", "other"] {
+            client.add_status(id, status(OrchestrationRuntimeStatus::Completed));
+        }
+
+        let query = InstanceQuery {
+            instance_id_prefix: Some("job-".to_string()),
+            page_size: Some(2),
+            ..Default::default()
+        };
+
+        let client: &dyn DurableClient = &client;
+        let statuses: Vec<_> =
+            block_on(client.query_instances_stream(query).try_collect::<Vec<_>>()).unwrap();
+        assert_eq!(statuses.len(), 3);
+
+        let progress: Vec<_> = block_on(
+            client
+                .purge_history_stream(InstanceQuery::default())
+                .try_collect::<Vec<_>>(),
+        )
+        .unwrap();
+        assert_eq!(progress.len(), 1);
+        assert_eq!(progress[0].total_instances_deleted, 4);
+    }
+
+    #[test]
+    fn it_purges_every_page() {
+        let client = FakeClient::new("hub");
+        for i in 0..5 {
+            client.add_status(
+                &format!("job-{}", i),
+                status(OrchestrationRuntimeStatus::Completed),
+            );
+        }
+
+        let query = InstanceQuery {
+            page_size: Some(2),
+            ..Default::default()
+        };
+
+        let durable: &dyn DurableClient = &client;
+        let progress: Vec<_> = block_on(
+            durable
+                .purge_history_stream(query.clone())
+                .try_collect::<Vec<_>>(),
+        )
+        .unwrap();
+        assert_eq!(
+            progress
+                .iter()
+                .map(|p| p.instances_deleted)
+                .collect::<Vec<_>>(),
+            vec![2, 2, 1]
+        );
+        assert_eq!(progress[2].total_instances_deleted, 5);
+
+        let remaining: Vec<_> = block_on(
+            durable
+                .query_instances_stream(query)
+                .try_collect::<Vec<_>>(),
+        )
+        .unwrap();
+        assert!(remaining.is_empty());
+    }
+}
@@ -3,12 +3,16 @@
 #![deny(missing_docs)]
 
 mod client;
+mod durable_client;
 mod endpoint;
 mod error;
+mod fake;
 
 pub use self::client::*;
+pub use self::durable_client::*;
 pub use self::endpoint::*;
 pub use self::error::*;
+pub use self::fake::*;
 
 /// The result type for the Durable Functions HTTP client.
 pub type Result<T> = std::result::Result<T, ClientError>;
@@ -13,6 +13,7 @@ pub struct Run<'a> {
     color: Option<&'a str>,
     port: Option<&'a str>,
     script_root: Option<&'a str>,
+    assets: Option<&'a str>,
     no_debug_info: bool,
     cargo_options: Option<Values<'a>>,
 }
@@ -51,6 +52,13 @@ impl<'a> Run<'a> {
                     .value_name("ROOT")
                     .help("The directory to use for the Azure Functions application script root. Default is a temporary directory."),
             )
+            .arg(
+                Arg::with_name("assets")
+                    .long("assets")
+                    .short("a")
+                    .value_name("ASSETS_DIR")
+                    .help("The static assets directory to copy to the script root. Default is the 'assets' directory of the Rust crate, if present."),
+            )
             .arg(
                 Arg::with_name("no_debug_info")
                     .long("--no-debug-info")
@@ -116,6 +124,17 @@ impl<'a> Run<'a> {
             "--sync-extensions",
         ]);
 
+        let assets = self.assets.map(|dir| {
+            current_dir()
+                .expect("failed to get current directory")
+                .join(dir)
+        });
+
+        if let Some(assets) = assets.as_ref() {
+            args.push("--assets");
+            args.push(assets.to_str().unwrap());
+        }
+
         if self.no_debug_info {
             args.push("--no-debug-info");
         }
@@ -199,6 +218,7 @@ impl<'a> From<&'a ArgMatches<'a>> for Run<'a> {
             color: args.value_of("color"),
             port: args.value_of("port"),
             script_root: args.value_of("script_root"),
+            assets: args.value_of("assets"),
             no_debug_info: args.is_present("no_debug_info"),
             cargo_options: args.values_of("cargo_options"),
         }
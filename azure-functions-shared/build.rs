@@ -7,6 +7,11 @@ const OUT_DIR_VAR: &str = "OUT_DIR";
 const CACHE_DIR_NAME: &str = "cache";
 const PROTOBUF_INPUT_FILES: &[&str] = &["FunctionRpc.proto"];
 const OUTPUT_FILES: &[&str] = &["azure_functions_rpc_messages.rs"];
+// Definitions the worker depends on that are missing from older versions of the protobuf submodule
+const REQUIRED_DEFINITIONS: &[(&str, &str)] = &[
+    ("azure_functions_rpc_messages.rs", "pub struct RetryContext"),
+    ("azure_functions_rpc_messages.rs", "pub retry_context:"),
+];
 
 fn format_source(path: &Path) {
     Command::new("rustfmt")
@@ -21,6 +26,19 @@ fn compile_protobufs(out_dir: &PathBuf, cache_dir: &PathBuf) {
         .compile(PROTOBUF_INPUT_FILES, &["protobuf/src/proto"])
         .unwrap_or_else(|e| panic!("protobuf compilation failed: {}", e));
 
+    // Don't overwrite the cache with definitions that would break the worker
+    for (file, definition) in REQUIRED_DEFINITIONS {
+        let output = fs::read_to_string(out_dir.join(file))
+            .unwrap_or_else(|e| panic!("can't read generated file '{}': {}", file, e));
+
+        if !output.contains(definition) {
+            panic!(
+                "generated file '{}' is missing '{}'; update the protobuf submodule to a version that defines `RetryContext` and `InvocationRequest.retry_context` (see cache/README.md)",
+                file, definition
+            );
+        }
+    }
+
     for file in OUTPUT_FILES {
         let cached_output = cache_dir.join(file);
 
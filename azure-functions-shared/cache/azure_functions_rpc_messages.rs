@@ -346,6 +346,22 @@ pub struct InvocationRequest {
     /// binding metadata from trigger
     #[prost(map = "string, message", tag = "4")]
     pub trigger_metadata: ::std::collections::HashMap<std::string::String, TypedData>,
+    /// Current retry context
+    #[prost(message, optional, tag = "6")]
+    pub retry_context: ::std::option::Option<RetryContext>,
+}
+/// Host sends retry context for a function invocation
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RetryContext {
+    /// Current retry count
+    #[prost(int32, tag = "1")]
+    pub retry_count: i32,
+    /// Max retry count
+    #[prost(int32, tag = "2")]
+    pub max_retry_count: i32,
+    /// Exception that caused the retry
+    #[prost(message, optional, tag = "3")]
+    pub exception: ::std::option::Option<RpcException>,
 }
 /// Host requests worker to cancel invocation
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -1,9 +1,13 @@
 pub mod bindings;
+mod cors;
 mod function;
 mod quotable;
+mod retry;
 mod value;
 
+pub use self::cors::*;
 pub use self::function::*;
+pub use self::retry::*;
 pub use self::value::*;
 
 use proc_macro2::Span;
@@ -0,0 +1,184 @@
+use crate::codegen::{get_string_value, macro_panic, quotable::QuotableStrArray};
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use std::borrow::Cow;
+use syn::{spanned::Spanned, Lit};
+
+/// The CORS policy of a HTTP-triggered function.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cors {
+    /// The allowed origins; an origin of `*` allows any origin.
+    pub allowed_origins: Cow<'static, [Cow<'static, str>]>,
+    /// The allowed methods; an empty list allows the methods of simple requests only.
+    pub allowed_methods: Cow<'static, [Cow<'static, str>]>,
+    /// The allowed request headers; a header of `*` allows any header.
+    pub allowed_headers: Cow<'static, [Cow<'static, str>]>,
+    /// The response headers exposed to the browser.
+    pub exposed_headers: Cow<'static, [Cow<'static, str>]>,
+    /// Whether or not credentials (e.g. cookies) are allowed.
+    pub allow_credentials: bool,
+    /// The number of seconds a preflight response may be cached.
+    pub max_age: Option<u32>,
+}
+
+impl ToTokens for Cors {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let allowed_origins = QuotableStrArray(&self.allowed_origins);
+        let allowed_methods = QuotableStrArray(&self.allowed_methods);
+        let allowed_headers = QuotableStrArray(&self.allowed_headers);
+        let exposed_headers = QuotableStrArray(&self.exposed_headers);
+        let allow_credentials = self.allow_credentials;
+        let max_age = match self.max_age {
+            Some(max_age) => quote!(Some(#max_age)),
+            None => quote!(None),
+        };
+
+        quote!(::azure_functions::codegen::Cors {
+            allowed_origins: #allowed_origins,
+            allowed_methods: #allowed_methods,
+            allowed_headers: #allowed_headers,
+            exposed_headers: #exposed_headers,
+            allow_credentials: #allow_credentials,
+            max_age: #max_age,
+        })
+        .to_tokens(tokens);
+    }
+}
+
+/// Represents the CORS-related arguments of the `func` attribute.
+#[derive(Default)]
+pub(crate) struct CorsArgs {
+    pub allowed_origins: Option<Vec<Cow<'static, str>>>,
+    pub allowed_methods: Option<Vec<Cow<'static, str>>>,
+    pub allowed_headers: Option<Vec<Cow<'static, str>>>,
+    pub exposed_headers: Option<Vec<Cow<'static, str>>>,
+    pub allow_credentials: Option<bool>,
+    pub max_age: Option<i64>,
+}
+
+impl CorsArgs {
+    pub fn into_cors(self) -> Option<Cors> {
+        let allowed_origins = match self.allowed_origins {
+            Some(origins) => origins,
+            None => {
+                if self.allowed_methods.is_some()
+                    || self.allowed_headers.is_some()
+                    || self.exposed_headers.is_some()
+                    || self.allow_credentials.is_some()
+                    || self.max_age.is_some()
+                {
+                    macro_panic(
+                        Span::call_site(),
+                        "the 'cors_origins' argument is required when specifying a CORS policy",
+                    );
+                }
+                return None;
+            }
+        };
+
+        let allow_credentials = self.allow_credentials.unwrap_or(false);
+        if allow_credentials && allowed_origins.iter().any(|o| o == "*") {
+            macro_panic(
+                Span::call_site(),
+                "the 'cors_credentials' argument cannot be used with an allowed origin of '*'",
+            );
+        }
+
+        let max_age = self.max_age.map(|max_age| {
+            if max_age > i64::from(u32::MAX) {
+                macro_panic(
+                    Span::call_site(),
+                    format!(
+                        "the 'cors_max_age' argument must be between 0 and {}",
+                        u32::MAX
+                    ),
+                );
+            }
+            max_age as u32
+        });
+
+        Some(Cors {
+            allowed_origins: Cow::Owned(allowed_origins),
+            allowed_methods: Cow::Owned(
+                self.allowed_methods
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|m| Cow::from(m.to_uppercase()))
+                    .collect(),
+            ),
+            allowed_headers: Cow::Owned(self.allowed_headers.unwrap_or_default()),
+            exposed_headers: Cow::Owned(self.exposed_headers.unwrap_or_default()),
+            allow_credentials,
+            max_age,
+        })
+    }
+}
+
+/// Gets a list argument with values separated by '|'.
+pub(crate) fn get_list_value(name: &str, value: &Lit) -> Vec<Cow<'static, str>> {
+    let value_str = get_string_value(name, value);
+
+    value_str
+        .split('|')
+        .map(str::trim)
+        .map(|v| {
+            if v.is_empty() {
+                macro_panic(
+                    value.span(),
+                    format!(
+                        "expected a list of values separated by '|' for the '{}' argument",
+                        name
+                    ),
+                );
+            }
+            Cow::from(v.to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_converts_to_tokens() {
+        let cors = Cors {
+            allowed_origins: Cow::Owned(vec![Cow::from("https://example.com")]),
+            allowed_methods: Cow::Owned(vec![Cow::from("GET")]),
+            allowed_headers: Cow::Borrowed(&[]),
+            exposed_headers: Cow::Borrowed(&[]),
+            allow_credentials: true,
+            max_age: Some(600),
+        };
+
+        let mut stream = TokenStream::new();
+        cors.to_tokens(&mut stream);
+        let mut tokens = stream.to_string();
+        tokens.retain(|c| c != ' ');
+
+        assert_eq!(
+            tokens,
+            r#"::azure_functions::codegen::Cors{allowed_origins:::std::borrow::Cow::Borrowed(&[::std::borrow::Cow::Borrowed("https://example.com"),]),allowed_methods:::std::borrow::Cow::Borrowed(&[::std::borrow::Cow::Borrowed("GET"),]),allowed_headers:::std::borrow::Cow::Borrowed(&[]),exposed_headers:::std::borrow::Cow::Borrowed(&[]),allow_credentials:true,max_age:Some(600u32),}"#
+        );
+    }
+
+    #[test]
+    fn it_uppercases_methods() {
+        let cors = CorsArgs {
+            allowed_origins: Some(vec![Cow::from("*")]),
+            allowed_methods: Some(vec![Cow::from("get"), Cow::from("Post")]),
+            ..Default::default()
+        }
+        .into_cors()
+        .unwrap();
+
+        assert_eq!(cors.allowed_methods.as_ref(), ["GET", "POST"]);
+        assert!(!cors.allow_credentials);
+        assert_eq!(cors.max_age, None);
+    }
+
+    #[test]
+    fn it_has_no_policy_without_arguments() {
+        assert_eq!(CorsArgs::default().into_cors(), None);
+    }
+}
@@ -1,12 +1,18 @@
 use crate::codegen::{
     bindings::Binding,
-    get_boolean_value, get_string_value, iter_attribute_args, macro_panic,
-    quotable::{QuotableBorrowedStr, QuotableOption},
+    cors::{get_list_value, CorsArgs},
+    get_boolean_value, get_integer_value, get_string_value, iter_attribute_args, macro_panic,
+    quotable::{QuotableBorrowedStr, QuotableOption, QuotableStrArray},
+    retry::{get_interval_value, get_retry_options_value, RetryArgs},
+    Cors, Retry, RetryOptionsFn,
 };
 use crate::rpc;
 use proc_macro2::{Span, TokenStream};
 use quote::{quote, ToTokens};
-use serde::{ser::SerializeMap, Serialize, Serializer};
+use serde::{
+    ser::{Error, SerializeMap},
+    Serialize, Serializer,
+};
 use std::borrow::Cow;
 use std::future::Future;
 use std::pin::Pin;
@@ -66,6 +72,11 @@ impl ToTokens for Invoker {
 pub struct Function {
     pub name: Cow<'static, str>,
     pub disabled: bool,
+    pub version: Option<Cow<'static, str>>,
+    pub retry: Option<Retry>,
+    pub retry_options: Option<RetryOptionsFn>,
+    pub roles: Cow<'static, [Cow<'static, str>]>,
+    pub cors: Option<Cors>,
     pub bindings: Cow<'static, [Binding]>,
     pub invoker: Option<Invoker>,
     pub manifest_dir: Option<Cow<'static, str>>,
@@ -83,6 +94,13 @@ impl Serialize for Function {
 
         map.serialize_entry("generatedBy", "azure-functions-rs")?;
         map.serialize_entry("disabled", &self.disabled)?;
+        if let Some(retry) = self.retry.as_ref() {
+            map.serialize_entry("retry", retry)?;
+        } else if let Some(retry_fn) = self.retry_options.as_ref().and_then(|r| r.retry_fn) {
+            let retry = retry_fn()
+                .map_err(|e| S::Error::custom(format!("invalid retry options: {}", e)))?;
+            map.serialize_entry("retry", &retry)?;
+        }
         map.serialize_entry("bindings", &self.bindings)?;
 
         map.end()
@@ -93,6 +111,11 @@ impl From<AttributeArgs> for Function {
     fn from(args: AttributeArgs) -> Self {
         let mut name = None;
         let mut disabled = None;
+        let mut version = None;
+        let mut retry = RetryArgs::default();
+        let mut retry_options = None;
+        let mut roles = Vec::new();
+        let mut cors = CorsArgs::default();
 
         iter_attribute_args(&args, |key, value| {
             let key_name = key.to_string();
@@ -113,6 +136,49 @@ impl From<AttributeArgs> for Function {
                     }
                 }
                 "disabled" => disabled = Some(get_boolean_value("disabled", value)),
+                "version" => version = Some(Cow::from(get_string_value("version", value))),
+                "retry" => retry.strategy = Some((get_string_value("retry", value), value.span())),
+                "retry_options" => retry_options = Some(get_retry_options_value(value)),
+                "roles" => {
+                    let value_str = get_string_value("roles", value);
+                    for role in value_str.split('|').map(str::trim) {
+                        if role.is_empty() {
+                            macro_panic(
+                                value.span(),
+                                "expected a list of roles separated by '|' for the 'roles' argument",
+                            );
+                        }
+                        roles.push(Cow::from(role.to_string()));
+                    }
+                }
+                "max_retry_count" => {
+                    retry.max_retry_count = Some(get_integer_value("max_retry_count", value))
+                }
+                "delay_interval" => {
+                    retry.delay_interval = Some(get_interval_value("delay_interval", value))
+                }
+                "minimum_interval" => {
+                    retry.minimum_interval = Some(get_interval_value("minimum_interval", value))
+                }
+                "maximum_interval" => {
+                    retry.maximum_interval = Some(get_interval_value("maximum_interval", value))
+                }
+                "cors_origins" => {
+                    cors.allowed_origins = Some(get_list_value("cors_origins", value))
+                }
+                "cors_methods" => {
+                    cors.allowed_methods = Some(get_list_value("cors_methods", value))
+                }
+                "cors_headers" => {
+                    cors.allowed_headers = Some(get_list_value("cors_headers", value))
+                }
+                "cors_expose_headers" => {
+                    cors.exposed_headers = Some(get_list_value("cors_expose_headers", value))
+                }
+                "cors_credentials" => {
+                    cors.allow_credentials = Some(get_boolean_value("cors_credentials", value))
+                }
+                "cors_max_age" => cors.max_age = Some(get_integer_value("cors_max_age", value)),
                 _ => macro_panic(
                     key.span(),
                     format!("unsupported attribue argument '{}'", key_name),
@@ -122,9 +188,21 @@ impl From<AttributeArgs> for Function {
             true
         });
 
+        if retry_options.is_some() && (retry.strategy.is_some() || retry.has_any()) {
+            macro_panic(
+                Span::call_site(),
+                "the 'retry_options' argument cannot be combined with the 'retry' argument",
+            );
+        }
+
         Function {
             name: name.unwrap_or(Cow::Borrowed("")),
             disabled: disabled.unwrap_or(false),
+            version,
+            retry: retry.into_retry(),
+            retry_options,
+            roles: Cow::Owned(roles),
+            cors: cors.into_cors(),
             bindings: Cow::Owned(Vec::new()),
             invoker: None,
             manifest_dir: None,
@@ -137,6 +215,11 @@ impl ToTokens for Function {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let name = QuotableBorrowedStr(&self.name);
         let disabled = self.disabled;
+        let version = QuotableOption(self.version.as_ref().map(|v| QuotableBorrowedStr(v)));
+        let retry = QuotableOption(self.retry.as_ref());
+        let retry_options = QuotableOption(self.retry_options.as_ref());
+        let roles = QuotableStrArray(&self.roles);
+        let cors = QuotableOption(self.cors.as_ref());
         let bindings = self.bindings.iter();
         let invoker = QuotableOption(self.invoker.as_ref());
 
@@ -144,6 +227,11 @@ impl ToTokens for Function {
             ::azure_functions::codegen::Function {
                 name: #name,
                 disabled: #disabled,
+                version: #version,
+                retry: #retry,
+                retry_options: #retry_options,
+                roles: #roles,
+                cors: #cors,
                 bindings: ::std::borrow::Cow::Borrowed(&[#(#bindings),*]),
                 invoker: #invoker,
                 manifest_dir: Some(::std::borrow::Cow::Borrowed(env!("CARGO_MANIFEST_DIR"))),
@@ -160,6 +248,7 @@ mod tests {
     use crate::codegen::{
         bindings::{Binding, Http, HttpTrigger},
         tests::should_panic,
+        RetryStrategy,
     };
     use proc_macro2::TokenStream;
     use quote::ToTokens;
@@ -171,6 +260,11 @@ mod tests {
         let func = Function {
             name: Cow::from("name"),
             disabled: false,
+            version: None,
+            retry: None,
+            retry_options: None,
+            roles: Cow::Borrowed(&[]),
+            cors: None,
             bindings: Cow::Owned(vec![
                 Binding::HttpTrigger(HttpTrigger {
                     name: Cow::from("foo"),
@@ -201,11 +295,15 @@ mod tests {
         let func: Function = vec![
             parse_str::<NestedMeta>(r#"name = "foo""#).unwrap(),
             parse_str::<NestedMeta>(r#"disabled = true"#).unwrap(),
+            parse_str::<NestedMeta>(r#"version = "2""#).unwrap(),
+            parse_str::<NestedMeta>(r#"roles = "admin|ops""#).unwrap(),
         ]
         .into();
 
         assert_eq!(func.name, "foo");
         assert_eq!(func.disabled, true);
+        assert_eq!(func.version.unwrap(), "2");
+        assert_eq!(func.roles.as_ref(), ["admin", "ops"]);
         assert_eq!(func.bindings.len(), 0);
         assert_eq!(func.invoker.is_none(), true);
         assert_eq!(func.manifest_dir.is_none(), true);
@@ -243,11 +341,323 @@ mod tests {
         );
     }
 
+    #[test]
+    fn it_requires_the_version_attribute_be_a_string() {
+        should_panic(
+            || {
+                let _: Function = vec![parse_str::<NestedMeta>(r#"version = 2"#).unwrap()].into();
+            },
+            "expected a literal string value for the 'version' argument",
+        );
+    }
+
+    #[test]
+    fn it_requires_the_roles_attribute_be_a_list_of_roles() {
+        should_panic(
+            || {
+                let _: Function =
+                    vec![parse_str::<NestedMeta>(r#"roles = "admin||ops""#).unwrap()].into();
+            },
+            "expected a list of roles separated by '|' for the 'roles' argument",
+        );
+    }
+
+    #[test]
+    fn it_parses_a_cors_policy() {
+        let func: Function = vec![
+            parse_str::<NestedMeta>(r#"cors_origins = "https://a.com | https://b.com""#).unwrap(),
+            parse_str::<NestedMeta>(r#"cors_methods = "get|put""#).unwrap(),
+            parse_str::<NestedMeta>(r#"cors_headers = "Content-Type""#).unwrap(),
+            parse_str::<NestedMeta>(r#"cors_expose_headers = "ETag""#).unwrap(),
+            parse_str::<NestedMeta>(r#"cors_credentials = true"#).unwrap(),
+            parse_str::<NestedMeta>(r#"cors_max_age = 600"#).unwrap(),
+        ]
+        .into();
+
+        let cors = func.cors.unwrap();
+        assert_eq!(
+            cors.allowed_origins.as_ref(),
+            ["https://a.com", "https://b.com"]
+        );
+        assert_eq!(cors.allowed_methods.as_ref(), ["GET", "PUT"]);
+        assert_eq!(cors.allowed_headers.as_ref(), ["Content-Type"]);
+        assert_eq!(cors.exposed_headers.as_ref(), ["ETag"]);
+        assert!(cors.allow_credentials);
+        assert_eq!(cors.max_age, Some(600));
+    }
+
+    #[test]
+    fn it_requires_origins_for_a_cors_policy() {
+        should_panic(
+            || {
+                let _: Function =
+                    vec![parse_str::<NestedMeta>(r#"cors_methods = "GET""#).unwrap()].into();
+            },
+            "the 'cors_origins' argument is required when specifying a CORS policy",
+        );
+    }
+
+    #[test]
+    fn it_rejects_credentials_with_any_origin() {
+        should_panic(
+            || {
+                let _: Function = vec![
+                    parse_str::<NestedMeta>(r#"cors_origins = "*""#).unwrap(),
+                    parse_str::<NestedMeta>(r#"cors_credentials = true"#).unwrap(),
+                ]
+                .into();
+            },
+            "the 'cors_credentials' argument cannot be used with an allowed origin of '*'",
+        );
+    }
+
+    #[test]
+    fn it_requires_the_cors_origins_attribute_be_a_list() {
+        should_panic(
+            || {
+                let _: Function =
+                    vec![parse_str::<NestedMeta>(r#"cors_origins = "a||b""#).unwrap()].into();
+            },
+            "expected a list of values separated by '|' for the 'cors_origins' argument",
+        );
+    }
+
+    #[test]
+    fn it_rejects_a_cors_max_age_out_of_range() {
+        should_panic(
+            || {
+                let _: Function = vec![
+                    parse_str::<NestedMeta>(r#"cors_origins = "*""#).unwrap(),
+                    parse_str::<NestedMeta>(r#"cors_max_age = 4294967296"#).unwrap(),
+                ]
+                .into();
+            },
+            "the 'cors_max_age' argument must be between 0 and 4294967295",
+        );
+    }
+
+    #[test]
+    fn it_serializes_a_retry_policy_to_json() {
+        let func = Function {
+            name: Cow::from("name"),
+            disabled: false,
+            version: None,
+            retry: Some(Retry {
+                strategy: RetryStrategy::FixedDelay {
+                    delay_interval: Cow::from("00:00:10"),
+                },
+                max_retry_count: 3,
+            }),
+            retry_options: None,
+            roles: Cow::Borrowed(&[]),
+            cors: None,
+            bindings: Cow::Owned(Vec::new()),
+            invoker: None,
+            manifest_dir: None,
+            file: None,
+        };
+
+        assert_eq!(
+            to_string(&func).unwrap(),
+            r#"{"generatedBy":"azure-functions-rs","disabled":false,"retry":{"strategy":"fixedDelay","maxRetryCount":3,"delayInterval":"00:00:10"},"bindings":[]}"#
+        );
+    }
+
+    #[test]
+    fn it_serializes_retry_options_to_json() {
+        fn retry() -> Result<Retry, String> {
+            Ok(Retry {
+                strategy: RetryStrategy::FixedDelay {
+                    delay_interval: Cow::from("00:00:05"),
+                },
+                max_retry_count: 2,
+            })
+        }
+
+        fn invalid() -> Result<Retry, String> {
+            Err("bad options".to_string())
+        }
+
+        let mut func = Function {
+            name: Cow::from("name"),
+            disabled: false,
+            version: None,
+            retry: None,
+            retry_options: Some(RetryOptionsFn {
+                path: Cow::from("retry"),
+                retry_fn: Some(retry),
+            }),
+            roles: Cow::Borrowed(&[]),
+            cors: None,
+            bindings: Cow::Owned(Vec::new()),
+            invoker: None,
+            manifest_dir: None,
+            file: None,
+        };
+
+        assert_eq!(
+            to_string(&func).unwrap(),
+            r#"{"generatedBy":"azure-functions-rs","disabled":false,"retry":{"strategy":"fixedDelay","maxRetryCount":2,"delayInterval":"00:00:05"},"bindings":[]}"#
+        );
+
+        func.retry_options = Some(RetryOptionsFn {
+            path: Cow::from("invalid"),
+            retry_fn: Some(invalid),
+        });
+
+        assert_eq!(
+            to_string(&func).unwrap_err().to_string(),
+            "invalid retry options: bad options"
+        );
+    }
+
+    #[test]
+    fn it_parses_the_retry_options_argument() {
+        let func: Function =
+            vec![parse_str::<NestedMeta>(r#"retry_options = "policies::retry""#).unwrap()].into();
+
+        assert!(func.retry.is_none());
+        assert_eq!(func.retry_options.unwrap().path, "policies::retry");
+
+        should_panic(
+            || {
+                let _: Function = vec![
+                    parse_str::<NestedMeta>(r#"retry_options = "policies::retry""#).unwrap(),
+                    parse_str::<NestedMeta>(r#"max_retry_count = 3"#).unwrap(),
+                ]
+                .into();
+            },
+            "the 'retry_options' argument cannot be combined with the 'retry' argument",
+        );
+    }
+
+    #[test]
+    fn it_parses_retry_arguments() {
+        let func: Function = vec![
+            parse_str::<NestedMeta>(r#"retry = "exponential_backoff""#).unwrap(),
+            parse_str::<NestedMeta>(r#"max_retry_count = 5"#).unwrap(),
+            parse_str::<NestedMeta>(r#"minimum_interval = "00:00:04""#).unwrap(),
+            parse_str::<NestedMeta>(r#"maximum_interval = "00:15:00""#).unwrap(),
+        ]
+        .into();
+
+        assert_eq!(
+            func.retry.unwrap(),
+            Retry {
+                strategy: RetryStrategy::ExponentialBackoff {
+                    minimum_interval: Cow::from("00:00:04"),
+                    maximum_interval: Cow::from("00:15:00"),
+                },
+                max_retry_count: 5,
+            }
+        );
+    }
+
+    #[test]
+    fn it_requires_a_retry_strategy() {
+        should_panic(
+            || {
+                let _: Function =
+                    vec![parse_str::<NestedMeta>(r#"max_retry_count = 3"#).unwrap()].into();
+            },
+            "the 'retry' argument is required when specifying a retry policy",
+        );
+    }
+
+    #[test]
+    fn it_requires_a_known_retry_strategy() {
+        should_panic(
+            || {
+                let _: Function = vec![
+                    parse_str::<NestedMeta>(r#"retry = "linear""#).unwrap(),
+                    parse_str::<NestedMeta>(r#"max_retry_count = 3"#).unwrap(),
+                ]
+                .into();
+            },
+            "expected 'fixed_delay' or 'exponential_backoff' for the 'retry' argument",
+        );
+    }
+
+    #[test]
+    fn it_requires_a_max_retry_count() {
+        should_panic(
+            || {
+                let _: Function = vec![
+                    parse_str::<NestedMeta>(r#"retry = "fixed_delay""#).unwrap(),
+                    parse_str::<NestedMeta>(r#"delay_interval = "00:00:10""#).unwrap(),
+                ]
+                .into();
+            },
+            "the 'max_retry_count' argument is required for a retry policy",
+        );
+    }
+
+    #[test]
+    fn it_requires_a_valid_max_retry_count() {
+        should_panic(
+            || {
+                let _: Function = vec![
+                    parse_str::<NestedMeta>(r#"retry = "fixed_delay""#).unwrap(),
+                    parse_str::<NestedMeta>(r#"max_retry_count = 0"#).unwrap(),
+                    parse_str::<NestedMeta>(r#"delay_interval = "00:00:10""#).unwrap(),
+                ]
+                .into();
+            },
+            "the 'max_retry_count' argument must be between 1 and 2147483647",
+        );
+    }
+
+    #[test]
+    fn it_requires_a_delay_interval_for_fixed_delay() {
+        should_panic(
+            || {
+                let _: Function = vec![
+                    parse_str::<NestedMeta>(r#"retry = "fixed_delay""#).unwrap(),
+                    parse_str::<NestedMeta>(r#"max_retry_count = 3"#).unwrap(),
+                ]
+                .into();
+            },
+            "the 'delay_interval' argument is required for the 'fixed_delay' retry strategy",
+        );
+    }
+
+    #[test]
+    fn it_requires_a_valid_interval() {
+        should_panic(
+            || {
+                let _: Function =
+                    vec![parse_str::<NestedMeta>(r#"delay_interval = "10s""#).unwrap()].into();
+            },
+            "expected a positive interval in the format '[d.]hh:mm:ss' for the 'delay_interval' argument",
+        );
+    }
+
+    #[test]
+    fn it_requires_the_maximum_interval_be_at_least_the_minimum() {
+        should_panic(
+            || {
+                let _: Function = vec![
+                    parse_str::<NestedMeta>(r#"retry = "exponential_backoff""#).unwrap(),
+                    parse_str::<NestedMeta>(r#"max_retry_count = 3"#).unwrap(),
+                    parse_str::<NestedMeta>(r#"minimum_interval = "00:01:00""#).unwrap(),
+                    parse_str::<NestedMeta>(r#"maximum_interval = "00:00:30""#).unwrap(),
+                ]
+                .into();
+            },
+            "the 'maximum_interval' argument must not be less than the 'minimum_interval' argument",
+        );
+    }
+
     #[test]
     fn it_converts_to_tokens() {
         let func = Function {
             name: Cow::from("name"),
             disabled: false,
+            version: None,
+            retry: None,
+            retry_options: None,
+            roles: Cow::Borrowed(&[]),
+            cors: None,
             bindings: Cow::Owned(vec![
                 Binding::HttpTrigger(HttpTrigger {
                     name: Cow::from("foo"),
@@ -274,7 +684,7 @@ mod tests {
 
         assert_eq!(
             tokens,
-            r#"::azure_functions::codegen::Function{name:::std::borrow::Cow::Borrowed("name"),disabled:false,bindings:::std::borrow::Cow::Borrowed(&[::azure_functions::codegen::bindings::Binding::HttpTrigger(::azure_functions::codegen::bindings::HttpTrigger{name:::std::borrow::Cow::Borrowed("foo"),auth_level:Some(::std::borrow::Cow::Borrowed("bar")),methods:::std::borrow::Cow::Borrowed(&[::std::borrow::Cow::Borrowed("foo"),::std::borrow::Cow::Borrowed("bar"),::std::borrow::Cow::Borrowed("baz"),]),route:Some(::std::borrow::Cow::Borrowed("baz")),}),::azure_functions::codegen::bindings::Binding::Http(::azure_functions::codegen::bindings::Http{name:::std::borrow::Cow::Borrowed("bar"),})]),invoker:Some(::azure_functions::codegen::Invoker{name:::std::borrow::Cow::Borrowed("invoker"),invoker_fn:::azure_functions::codegen::InvokerFn::Async(Some(invoker)),}),manifest_dir:Some(::std::borrow::Cow::Borrowed(env!("CARGO_MANIFEST_DIR"))),file:Some(::std::borrow::Cow::Borrowed(file!())),}"#
+            r#"::azure_functions::codegen::Function{name:::std::borrow::Cow::Borrowed("name"),disabled:false,version:None,retry:None,retry_options:None,roles:::std::borrow::Cow::Borrowed(&[]),cors:None,bindings:::std::borrow::Cow::Borrowed(&[::azure_functions::codegen::bindings::Binding::HttpTrigger(::azure_functions::codegen::bindings::HttpTrigger{name:::std::borrow::Cow::Borrowed("foo"),auth_level:Some(::std::borrow::Cow::Borrowed("bar")),methods:::std::borrow::Cow::Borrowed(&[::std::borrow::Cow::Borrowed("foo"),::std::borrow::Cow::Borrowed("bar"),::std::borrow::Cow::Borrowed("baz"),]),route:Some(::std::borrow::Cow::Borrowed("baz")),}),::azure_functions::codegen::bindings::Binding::Http(::azure_functions::codegen::bindings::Http{name:::std::borrow::Cow::Borrowed("bar"),})]),invoker:Some(::azure_functions::codegen::Invoker{name:::std::borrow::Cow::Borrowed("invoker"),invoker_fn:::azure_functions::codegen::InvokerFn::Async(Some(invoker)),}),manifest_dir:Some(::std::borrow::Cow::Borrowed(env!("CARGO_MANIFEST_DIR"))),file:Some(::std::borrow::Cow::Borrowed(file!())),}"#
         );
     }
 }
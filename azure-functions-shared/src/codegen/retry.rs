@@ -0,0 +1,377 @@
+use crate::codegen::{get_string_value, macro_panic, quotable::QuotableBorrowedStr};
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use serde::{ser::SerializeMap, Serialize, Serializer};
+use std::borrow::Cow;
+use syn::{parse_str, spanned::Spanned, Lit, Path};
+
+/// The retry strategy of a function.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RetryStrategy {
+    /// Waits a fixed interval between retries.
+    FixedDelay { delay_interval: Cow<'static, str> },
+    /// Doubles the interval between retries, starting at the minimum and capped at the maximum.
+    ExponentialBackoff {
+        minimum_interval: Cow<'static, str>,
+        maximum_interval: Cow<'static, str>,
+    },
+}
+
+/// The retry policy of a function.
+///
+/// The policy is written to `function.json` and carried out by the Azure Functions host, which
+/// re-executes the whole function when it fails.
+///
+/// The policy can also be created from the `RetryOptions` of Durable Functions with the
+/// `retry_options` argument of the `func` attribute (see `RetryOptionsFn`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Retry {
+    pub strategy: RetryStrategy,
+    /// The maximum number of retries, between 1 and `i32::MAX`.
+    pub max_retry_count: i32,
+}
+
+impl Serialize for Retry {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+
+        match &self.strategy {
+            RetryStrategy::FixedDelay { delay_interval } => {
+                map.serialize_entry("strategy", "fixedDelay")?;
+                map.serialize_entry("maxRetryCount", &self.max_retry_count)?;
+                map.serialize_entry("delayInterval", delay_interval)?;
+            }
+            RetryStrategy::ExponentialBackoff {
+                minimum_interval,
+                maximum_interval,
+            } => {
+                map.serialize_entry("strategy", "exponentialBackoff")?;
+                map.serialize_entry("maxRetryCount", &self.max_retry_count)?;
+                map.serialize_entry("minimumInterval", minimum_interval)?;
+                map.serialize_entry("maximumInterval", maximum_interval)?;
+            }
+        };
+
+        map.end()
+    }
+}
+
+impl ToTokens for Retry {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let strategy = match &self.strategy {
+            RetryStrategy::FixedDelay { delay_interval } => {
+                let delay_interval = QuotableBorrowedStr(delay_interval);
+                quote!(::azure_functions::codegen::RetryStrategy::FixedDelay { delay_interval: #delay_interval })
+            }
+            RetryStrategy::ExponentialBackoff {
+                minimum_interval,
+                maximum_interval,
+            } => {
+                let minimum_interval = QuotableBorrowedStr(minimum_interval);
+                let maximum_interval = QuotableBorrowedStr(maximum_interval);
+                quote!(::azure_functions::codegen::RetryStrategy::ExponentialBackoff { minimum_interval: #minimum_interval, maximum_interval: #maximum_interval })
+            }
+        };
+        let max_retry_count = self.max_retry_count;
+
+        quote!(::azure_functions::codegen::Retry { strategy: #strategy, max_retry_count: #max_retry_count, })
+            .to_tokens(tokens);
+    }
+}
+
+/// The function that converts a function's Durable Functions `RetryOptions` into a retry policy.
+pub type RetryFn = fn() -> Result<Retry, String>;
+
+/// Represents a retry policy created from Durable Functions `RetryOptions`.
+///
+/// The `retry_options` argument of the `func` attribute is the path of a function returning the
+/// options; they are converted into a retry policy when the function's metadata is written.
+pub struct RetryOptionsFn {
+    /// The path of the function returning the retry options.
+    pub path: Cow<'static, str>,
+    /// The function that converts the retry options into a retry policy.
+    pub retry_fn: Option<RetryFn>,
+}
+
+impl ToTokens for RetryOptionsFn {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let path = parse_str::<Path>(&self.path).expect("expected a valid path");
+        let path_str = QuotableBorrowedStr(&self.path);
+
+        quote!(::azure_functions::codegen::RetryOptionsFn {
+            path: #path_str,
+            retry_fn: Some({
+                fn __retry() -> ::std::result::Result<::azure_functions::codegen::Retry, ::std::string::String> {
+                    <::azure_functions::codegen::Retry as ::std::convert::TryFrom<&::azure_functions::durable::RetryOptions>>::try_from(&#path())
+                        .map_err(|e| e.to_string())
+                }
+                __retry
+            }),
+        })
+        .to_tokens(tokens);
+    }
+}
+
+/// Gets the `retry_options` argument of the `func` attribute.
+pub(crate) fn get_retry_options_value(value: &Lit) -> RetryOptionsFn {
+    let path = get_string_value("retry_options", value);
+
+    if parse_str::<Path>(&path).is_err() {
+        macro_panic(
+            value.span(),
+            "expected a path to a function returning `RetryOptions` for the 'retry_options' argument",
+        );
+    }
+
+    RetryOptionsFn {
+        path: Cow::from(path),
+        retry_fn: None,
+    }
+}
+
+/// Represents the retry-related arguments of the `func` attribute.
+#[derive(Default)]
+pub(crate) struct RetryArgs {
+    pub strategy: Option<(String, Span)>,
+    pub max_retry_count: Option<i64>,
+    pub delay_interval: Option<(String, u64)>,
+    pub minimum_interval: Option<(String, u64)>,
+    pub maximum_interval: Option<(String, u64)>,
+}
+
+impl RetryArgs {
+    pub fn has_any(&self) -> bool {
+        self.max_retry_count.is_some()
+            || self.delay_interval.is_some()
+            || self.minimum_interval.is_some()
+            || self.maximum_interval.is_some()
+    }
+
+    pub fn into_retry(self) -> Option<Retry> {
+        let (strategy, span) = match self.strategy {
+            Some(s) => s,
+            None => {
+                if self.has_any() {
+                    macro_panic(
+                        Span::call_site(),
+                        "the 'retry' argument is required when specifying a retry policy",
+                    );
+                }
+                return None;
+            }
+        };
+
+        let max_retry_count = match self.max_retry_count {
+            Some(count) if count >= 1 && count <= i64::from(i32::MAX) => count as i32,
+            Some(_) => macro_panic(
+                Span::call_site(),
+                format!(
+                    "the 'max_retry_count' argument must be between 1 and {}",
+                    i32::MAX
+                ),
+            ),
+            None => macro_panic(
+                Span::call_site(),
+                "the 'max_retry_count' argument is required for a retry policy",
+            ),
+        };
+
+        let strategy = match strategy.as_str() {
+            "fixed_delay" => {
+                if self.minimum_interval.is_some() || self.maximum_interval.is_some() {
+                    macro_panic(
+                        Span::call_site(),
+                        "the 'minimum_interval' and 'maximum_interval' arguments are only supported for the 'exponential_backoff' retry strategy",
+                    );
+                }
+
+                match self.delay_interval {
+                    Some((delay_interval, _)) => RetryStrategy::FixedDelay {
+                        delay_interval: Cow::from(delay_interval),
+                    },
+                    None => macro_panic(
+                        Span::call_site(),
+                        "the 'delay_interval' argument is required for the 'fixed_delay' retry strategy",
+                    ),
+                }
+            }
+            "exponential_backoff" => {
+                if self.delay_interval.is_some() {
+                    macro_panic(
+                        Span::call_site(),
+                        "the 'delay_interval' argument is only supported for the 'fixed_delay' retry strategy",
+                    );
+                }
+
+                match (self.minimum_interval, self.maximum_interval) {
+                    (Some((minimum, min_secs)), Some((maximum, max_secs))) => {
+                        if max_secs < min_secs {
+                            macro_panic(
+                                Span::call_site(),
+                                "the 'maximum_interval' argument must not be less than the 'minimum_interval' argument",
+                            );
+                        }
+
+                        RetryStrategy::ExponentialBackoff {
+                            minimum_interval: Cow::from(minimum),
+                            maximum_interval: Cow::from(maximum),
+                        }
+                    }
+                    _ => macro_panic(
+                        Span::call_site(),
+                        "the 'minimum_interval' and 'maximum_interval' arguments are required for the 'exponential_backoff' retry strategy",
+                    ),
+                }
+            }
+            _ => macro_panic(
+                span,
+                "expected 'fixed_delay' or 'exponential_backoff' for the 'retry' argument",
+            ),
+        };
+
+        Some(Retry {
+            strategy,
+            max_retry_count,
+        })
+    }
+}
+
+/// Gets a time interval argument in the `[d.]hh:mm:ss` format used by the Azure Functions host.
+///
+/// Returns the interval and its total number of seconds.
+pub(crate) fn get_interval_value(name: &str, value: &Lit) -> (String, u64) {
+    let interval = get_string_value(name, value);
+
+    match parse_interval(&interval) {
+        Some(secs) if secs > 0 => (interval, secs),
+        _ => macro_panic(
+            value.span(),
+            format!(
+                "expected a positive interval in the format '[d.]hh:mm:ss' for the '{}' argument",
+                name
+            ),
+        ),
+    }
+}
+
+fn parse_interval(interval: &str) -> Option<u64> {
+    let (days, time) = match interval.find('.') {
+        Some(i) => (interval[..i].parse::<u64>().ok()?, &interval[i + 1..]),
+        None => (0, interval),
+    };
+
+    let parts: Vec<&str> = time.split(':').collect();
+    if parts.len() != 3 || parts.iter().any(|p| p.len() != 2) {
+        return None;
+    }
+
+    let hours = parts[0].parse::<u64>().ok()?;
+    let minutes = parts[1].parse::<u64>().ok()?;
+    let seconds = parts[2].parse::<u64>().ok()?;
+
+    if hours > 23 || minutes > 59 || seconds > 59 {
+        return None;
+    }
+
+    Some(((days * 24 + hours) * 60 + minutes) * 60 + seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegen::tests::should_panic;
+    use serde_json::to_string;
+
+    #[test]
+    fn it_parses_intervals() {
+        assert_eq!(parse_interval("00:00:10"), Some(10));
+        assert_eq!(parse_interval("01:02:03"), Some(3723));
+        assert_eq!(parse_interval("1.00:00:00"), Some(86400));
+        assert_eq!(parse_interval("24:00:00"), None);
+        assert_eq!(parse_interval("00:60:00"), None);
+        assert_eq!(parse_interval("0:0:10"), None);
+        assert_eq!(parse_interval("10s"), None);
+    }
+
+    #[test]
+    fn it_serializes_a_fixed_delay_to_json() {
+        let retry = Retry {
+            strategy: RetryStrategy::FixedDelay {
+                delay_interval: Cow::from("00:00:10"),
+            },
+            max_retry_count: 5,
+        };
+
+        assert_eq!(
+            to_string(&retry).unwrap(),
+            r#"{"strategy":"fixedDelay","maxRetryCount":5,"delayInterval":"00:00:10"}"#
+        );
+    }
+
+    #[test]
+    fn it_serializes_an_exponential_backoff_to_json() {
+        let retry = Retry {
+            strategy: RetryStrategy::ExponentialBackoff {
+                minimum_interval: Cow::from("00:00:04"),
+                maximum_interval: Cow::from("00:15:00"),
+            },
+            max_retry_count: 10,
+        };
+
+        assert_eq!(
+            to_string(&retry).unwrap(),
+            r#"{"strategy":"exponentialBackoff","maxRetryCount":10,"minimumInterval":"00:00:04","maximumInterval":"00:15:00"}"#
+        );
+    }
+
+    #[test]
+    fn it_converts_retry_options_to_tokens() {
+        let retry = RetryOptionsFn {
+            path: Cow::from("policies::retry"),
+            retry_fn: None,
+        };
+
+        let mut stream = TokenStream::new();
+        retry.to_tokens(&mut stream);
+        let mut tokens = stream.to_string();
+        tokens.retain(|c| c != ' ');
+
+        assert_eq!(
+            tokens,
+            r#"::azure_functions::codegen::RetryOptionsFn{path:::std::borrow::Cow::Borrowed("policies::retry"),retry_fn:Some({fn__retry()->::std::result::Result<::azure_functions::codegen::Retry,::std::string::String>{<::azure_functions::codegen::Retryas::std::convert::TryFrom<&::azure_functions::durable::RetryOptions>>::try_from(&policies::retry()).map_err(|e|e.to_string())}__retry}),}"#
+        );
+    }
+
+    #[test]
+    fn it_requires_a_path_for_retry_options() {
+        should_panic(
+            || {
+                get_retry_options_value(&parse_str::<Lit>(r#""not a path""#).unwrap());
+            },
+            "expected a path to a function returning `RetryOptions` for the 'retry_options' argument",
+        );
+    }
+
+    #[test]
+    fn it_converts_to_tokens() {
+        let retry = Retry {
+            strategy: RetryStrategy::FixedDelay {
+                delay_interval: Cow::from("00:00:10"),
+            },
+            max_retry_count: 5,
+        };
+
+        let mut stream = TokenStream::new();
+        retry.to_tokens(&mut stream);
+        let mut tokens = stream.to_string();
+        tokens.retain(|c| c != ' ');
+
+        assert_eq!(
+            tokens,
+            r#"::azure_functions::codegen::Retry{strategy:::azure_functions::codegen::RetryStrategy::FixedDelay{delay_interval:::std::borrow::Cow::Borrowed("00:00:10")},max_retry_count:5i32,}"#
+        );
+    }
+}
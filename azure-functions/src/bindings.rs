@@ -52,3 +52,5 @@ pub use self::signalr_message::*;
 pub use self::table::*;
 pub use self::timer_info::*;
 pub use self::twilio_sms_message::*;
+#[doc(no_inline)]
+pub use azure_functions_codegen::TableEntity;
@@ -1,6 +1,6 @@
 use crate::rpc::{typed_data::Data, TypedData};
-use serde::Deserialize;
-use serde_json::{from_str, Number, Value};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{from_str, from_value, Number, Value};
 use std::collections::HashMap;
 
 const INSTANCE_ID_KEY: &str = "instanceId";
@@ -30,6 +30,26 @@ const INSTANCE_ID_KEY: &str = "instanceId";
 ///     .into()
 /// }
 /// ```
+///
+/// An activity that deserializes its input:
+///
+/// ```rust
+/// use azure_functions::{bindings::DurableActivityContext, durable::ActivityOutput, func};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Order {
+///     quantity: u32,
+///     price: f64,
+/// }
+///
+/// #[func]
+/// pub fn place_order(context: DurableActivityContext) -> ActivityOutput {
+///     let order: Order = context.input().expect("expected an order");
+///
+///     serde_json::json!({ "total": order.price * f64::from(order.quantity) }).into()
+/// }
+/// ```
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DurableActivityContext {
@@ -59,6 +79,14 @@ impl DurableActivityContext {
                 .expect("expected an instance id"),
         }
     }
+
+    /// Deserializes the input to the activity function.
+    pub fn input<T>(&self) -> serde_json::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        from_value(self.input.clone())
+    }
 }
 
 #[cfg(test)]
@@ -84,4 +112,35 @@ mod tests {
         assert_eq!(context.instance_id, "foo");
         assert_eq!(context.input, Value::String("bar".to_string()));
     }
+
+    #[test]
+    fn it_deserializes_input() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Order {
+            id: u32,
+            item: String,
+        }
+
+        let data = TypedData {
+            data: Some(Data::Json(r#"{"id":1,"item":"widget"}"#.to_string())),
+        };
+
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            INSTANCE_ID_KEY.to_string(),
+            TypedData {
+                data: Some(Data::String("foo".to_string())),
+            },
+        );
+
+        let context = DurableActivityContext::new(data, metadata);
+        assert_eq!(
+            context.input::<Order>().unwrap(),
+            Order {
+                id: 1,
+                item: "widget".to_string()
+            }
+        );
+        assert!(context.input::<String>().is_err());
+    }
 }
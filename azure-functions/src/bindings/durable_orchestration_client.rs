@@ -1,11 +1,20 @@
-use crate::http::Body;
+use crate::bindings::HttpResponse;
+use crate::http::{Body, ProblemDetails, ResponseBuilder, Status};
 use crate::rpc::{typed_data::Data, TypedData};
 use azure_functions_durable::{
-    Client, OrchestrationData, OrchestrationRuntimeStatus, OrchestrationStatus, Result,
+    Client, ClientError, DurableClient, InstanceQuery, InstanceQueryPage, OrchestrationData,
+    OrchestrationRuntimeStatus, OrchestrationStatus, PurgeProgress, Result,
 };
 use chrono::{DateTime, Utc};
-use serde::Deserialize;
+use futures::stream::{Stream, TryStreamExt};
+use serde::{Deserialize, Serialize};
 use serde_json::{from_str, to_value, Value};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::timer::delay_for;
+
+const RETRY_AFTER_SECONDS: &str = "10";
+const MIN_RETRY_INTERVAL: Duration = Duration::from_millis(100);
 
 /// Represents the Durable Functions orchestration client input binding.
 ///
@@ -43,11 +52,181 @@ use serde_json::{from_str, to_value, Value};
 ///     }
 /// }
 /// ```
+///
+/// Starting a new orchestration and waiting up to 10 seconds for it to complete:
+///
+/// ```rust
+/// use azure_functions::{
+///     bindings::{DurableOrchestrationClient, HttpRequest, HttpResponse},
+///     func,
+/// };
+/// use serde_json::Value;
+/// use std::time::Duration;
+///
+/// #[func]
+/// pub async fn start(_req: HttpRequest, client: DurableOrchestrationClient) -> HttpResponse {
+///     match client.start_new("orchestration", None, Value::Null).await {
+///         Ok(data) => {
+///             client
+///                 .wait_for_completion_or_create_check_status_response(
+///                     &data.instance_id,
+///                     Duration::from_secs(10),
+///                     Duration::from_secs(1),
+///                 )
+///                 .await
+///         }
+///         Err(e) => format!("Failed to start orchestration: {}", e).into(),
+///     }
+/// }
+/// ```
+///
+/// Unit testing the start of a new orchestration with an in-memory client:
+///
+/// ```rust
+/// use azure_functions::{
+///     bindings::{DurableOrchestrationClient, HttpResponse},
+///     http::Status,
+/// };
+/// use azure_functions_durable::{ClientCall, FakeClient};
+/// use futures::executor::block_on;
+/// use serde_json::Value;
+/// use std::sync::Arc;
+///
+/// async fn start(client: &DurableOrchestrationClient) -> HttpResponse {
+///     match client.start_new("orchestration", Some("1234"), Value::Null).await {
+///         Ok(data) => client.create_check_status_response(&data.instance_id),
+///         Err(e) => format!("Failed to start orchestration: {}", e).into(),
+///     }
+/// }
+///
+/// let fake = Arc::new(FakeClient::new("hub"));
+/// let client = DurableOrchestrationClient::new(fake.clone(), fake.management_urls());
+///
+/// let response = block_on(start(&client));
+///
+/// assert_eq!(response.status(), Status::Accepted);
+/// assert_eq!(
+///     fake.calls(),
+///     vec![ClientCall::StartNew {
+///         function_name: "orchestration".to_string(),
+///         instance_id: "1234".to_string(),
+///         input: Value::Null,
+///     }]
+/// );
+/// ```
 pub struct DurableOrchestrationClient {
-    client: Client,
+    client: Arc<dyn DurableClient>,
+    management_urls: OrchestrationData,
 }
 
 impl DurableOrchestrationClient {
+    /// Creates a new orchestration client from the given client and management URLs.
+    ///
+    /// The management URLs contain a placeholder instance id that is replaced when creating
+    /// the HTTP management payload for an instance.
+    ///
+    /// This is primarily used to unit test functions with a `FakeClient`.
+    pub fn new(client: Arc<dyn DurableClient>, management_urls: OrchestrationData) -> Self {
+        DurableOrchestrationClient {
+            client,
+            management_urls,
+        }
+    }
+
+    /// Creates the HTTP management URLs for the given orchestration instance.
+    pub fn create_http_management_payload(&self, instance_id: &str) -> OrchestrationData {
+        let placeholder = &self.management_urls.instance_id;
+        let replace = |url: &String| url.replace(placeholder.as_str(), instance_id);
+
+        OrchestrationData {
+            instance_id: instance_id.to_string(),
+            status_query_get_uri: replace(&self.management_urls.status_query_get_uri),
+            send_event_post_uri: replace(&self.management_urls.send_event_post_uri),
+            terminate_post_uri: replace(&self.management_urls.terminate_post_uri),
+            purge_history_delete_uri: replace(&self.management_urls.purge_history_delete_uri),
+            rewind_post_uri: self.management_urls.rewind_post_uri.as_ref().map(replace),
+        }
+    }
+
+    /// Creates a HTTP response for checking the status of the given orchestration instance.
+    ///
+    /// The response has a status of 202 (Accepted), a `Location` header of the status query URL,
+    /// a `Retry-After` header, and a body containing the HTTP management URLs for the instance.
+    pub fn create_check_status_response(&self, instance_id: &str) -> HttpResponse {
+        let payload = self.create_http_management_payload(instance_id);
+
+        ResponseBuilder::new()
+            .status(Status::Accepted)
+            .header("Location", payload.status_query_get_uri.as_str())
+            .header("Retry-After", RETRY_AFTER_SECONDS)
+            .body(payload)
+            .finish()
+    }
+
+    /// Waits for the given orchestration instance to complete, polling its status at the given interval.
+    ///
+    /// If the orchestration completes within the timeout, the response has a status of 200 (OK) and a body
+    /// of the orchestration's output.  If the orchestration failed, was canceled, or was terminated, the
+    /// response has a status of 200 (OK) and a body of the orchestration's status.
+    ///
+    /// Otherwise, the response is the same as returned by `create_check_status_response`.
+    ///
+    /// The retry interval is at least 100 milliseconds. Failures to query the status that may be
+    /// transient (e.g. a server error) are logged and retried; other failures end the wait with a
+    /// problem details response (e.g. 404 (Not Found) for an instance that does not exist).
+    pub async fn wait_for_completion_or_create_check_status_response(
+        &self,
+        instance_id: &str,
+        timeout: Duration,
+        retry_interval: Duration,
+    ) -> HttpResponse {
+        let start = Instant::now();
+        let retry_interval = std::cmp::max(retry_interval, MIN_RETRY_INTERVAL);
+
+        loop {
+            match self
+                .client
+                .instance_status(instance_id, false, false, false)
+                .await
+            {
+                Ok(status) => {
+                    if let Some(response) = completion_response(status) {
+                        return response;
+                    }
+                }
+                Err(e @ ClientError::InternalServerError) | Err(e @ ClientError::Message(_)) => {
+                    log::warn!(
+                        "failed to query the status of instance '{}': {}",
+                        instance_id,
+                        e
+                    );
+                }
+                Err(e) => {
+                    log::error!(
+                        "failed to query the status of instance '{}': {}",
+                        instance_id,
+                        e
+                    );
+                    return match e {
+                        ClientError::InstanceNotFound => ProblemDetails::new(Status::NotFound)
+                            .detail(e.to_string())
+                            .into(),
+                        _ => ProblemDetails::new(Status::InternalServerError).into(),
+                    };
+                }
+            }
+
+            let elapsed = start.elapsed();
+            if elapsed >= timeout {
+                break;
+            }
+
+            delay_for(std::cmp::min(retry_interval, timeout - elapsed)).await;
+        }
+
+        self.create_check_status_response(instance_id)
+    }
+
     /// Gets the status of an orchestration instance.
     pub async fn instance_status(
         &self,
@@ -62,6 +241,8 @@ impl DurableOrchestrationClient {
     }
 
     /// Queries the status for instances in a given date range or with runtime status.
+    ///
+    /// The `top` parameter controls the page size; all pages are followed and returned.
     #[allow(clippy::too_many_arguments)]
     pub async fn query_instances<I>(
         &self,
@@ -77,18 +258,41 @@ impl DurableOrchestrationClient {
         I: Iterator<Item = OrchestrationRuntimeStatus>,
     {
         self.client
-            .query_instances(
+            .query_instances_stream(InstanceQuery {
                 created_time_from,
                 created_time_to,
-                runtime_statuses,
-                top,
+                runtime_statuses: runtime_statuses.map(Iterator::collect).unwrap_or_default(),
+                instance_id_prefix: None,
+                page_size: top,
                 show_history,
                 show_history_output,
                 show_input,
-            )
+            })
+            .try_collect()
+            .await
+    }
+
+    /// Queries a single page of instances.
+    ///
+    /// Pass the continuation token of the previous page to retrieve the next page.
+    pub async fn query_instances_page(
+        &self,
+        query: &InstanceQuery,
+        continuation_token: Option<&str>,
+    ) -> Result<InstanceQueryPage> {
+        self.client
+            .query_instances_page(query, continuation_token)
             .await
     }
 
+    /// Queries instances as a stream, following continuation tokens until all pages are retrieved.
+    pub fn query_instances_stream(
+        &self,
+        query: InstanceQuery,
+    ) -> impl Stream<Item = Result<OrchestrationStatus>> + '_ {
+        self.client.query_instances_stream(query)
+    }
+
     /// Purges the history of the given orchestration instance.
     pub async fn purge_history(&self, instance_id: &str) -> Result<()> {
         self.client.purge_history(instance_id).await
@@ -105,11 +309,29 @@ impl DurableOrchestrationClient {
         I: Iterator<Item = OrchestrationRuntimeStatus>,
     {
         self.client
-            .purge_history_by_query(created_time_from, created_time_to, runtime_statuses)
+            .purge_history_by_query(
+                created_time_from,
+                created_time_to,
+                &runtime_statuses
+                    .map(Iterator::collect::<Vec<_>>)
+                    .unwrap_or_default(),
+            )
             .await
     }
 
+    /// Purges the history of instances matching the given query, one page at a time.
+    ///
+    /// The stream yields the progress after each page is purged.
+    pub fn purge_history_stream(
+        &self,
+        query: InstanceQuery,
+    ) -> impl Stream<Item = Result<PurgeProgress>> + '_ {
+        self.client.purge_history_stream(query)
+    }
+
     /// Raises an event for the given orchestration instance.
+    ///
+    /// The event data is serialized as JSON.
     pub async fn raise_event<D>(
         &self,
         instance_id: &str,
@@ -117,8 +339,11 @@ impl DurableOrchestrationClient {
         event_data: D,
     ) -> Result<()>
     where
-        D: Into<Value>,
+        D: Serialize,
     {
+        let event_data = to_value(event_data)
+            .map_err(|e| ClientError::Message(format!("failed to serialize event data: {}", e)))?;
+
         self.client
             .raise_event(instance_id, event_name, event_data)
             .await
@@ -140,7 +365,7 @@ impl DurableOrchestrationClient {
         D: Into<Value>,
     {
         self.client
-            .start_new(function_name, instance_id, input)
+            .start_new(function_name, instance_id, input.into())
             .await
     }
 
@@ -153,17 +378,10 @@ impl DurableOrchestrationClient {
 #[doc(hidden)]
 impl From<TypedData> for DurableOrchestrationClient {
     fn from(data: TypedData) -> Self {
-        #[derive(Debug, Clone, Deserialize)]
-        #[serde(rename_all = "camelCase")]
-        struct ManagementUrls {
-            #[serde(rename = "statusQueryGetUri")]
-            status_query_url: String,
-        }
-
         #[derive(Deserialize)]
         #[serde(rename_all = "camelCase")]
         struct BindingData {
-            management_urls: ManagementUrls,
+            management_urls: OrchestrationData,
         }
 
         let data: BindingData = match &data.data {
@@ -173,9 +391,10 @@ impl From<TypedData> for DurableOrchestrationClient {
             _ => panic!("expected string data for durable orchestration client"),
         };
 
-        DurableOrchestrationClient {
-            client: Client::new(&data.management_urls.status_query_url),
-        }
+        DurableOrchestrationClient::new(
+            Arc::new(Client::new(&data.management_urls.status_query_get_uri)),
+            data.management_urls,
+        )
     }
 }
 
@@ -185,9 +404,35 @@ impl<'a> Into<Body<'a>> for OrchestrationData {
     }
 }
 
+fn completion_response(status: OrchestrationStatus) -> Option<HttpResponse> {
+    match status.runtime_status {
+        OrchestrationRuntimeStatus::Completed => Some(
+            ResponseBuilder::new()
+                .status(Status::Ok)
+                .body(status.output.unwrap_or(Value::Null))
+                .finish(),
+        ),
+        OrchestrationRuntimeStatus::Failed
+        | OrchestrationRuntimeStatus::Canceled
+        | OrchestrationRuntimeStatus::Terminated => Some(
+            ResponseBuilder::new()
+                .status(Status::Ok)
+                .body(to_value(&status).unwrap())
+                .finish(),
+        ),
+        OrchestrationRuntimeStatus::Running | OrchestrationRuntimeStatus::Pending => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        bindings::DurableOrchestrationContext,
+        durable::{OrchestrationOutput, OrchestrationTester},
+    };
+    use azure_functions_durable::{ClientCall, FakeClient};
+    use serde_json::from_value;
 
     #[test]
     fn it_converts_from_typed_data() {
@@ -198,4 +443,209 @@ mod tests {
         let client: DurableOrchestrationClient = data.into();
         assert_eq!(client.client.task_hub(), "DurableFunctionsHub");
     }
+
+    fn create_client() -> DurableOrchestrationClient {
+        TypedData {
+            data: Some(Data::String(r#"{"taskHubName":"DurableFunctionsHub","creationUrls":{},"managementUrls":{"id":"INSTANCEID","statusQueryGetUri":"http://localhost:8080/runtime/webhooks/durabletask/instances/INSTANCEID?taskHub=DurableFunctionsHub&connection=Storage&code=foo","sendEventPostUri":"http://localhost:8080/runtime/webhooks/durabletask/instances/INSTANCEID/raiseEvent/{eventName}?taskHub=DurableFunctionsHub&connection=Storage&code=foo","terminatePostUri":"http://localhost:8080/runtime/webhooks/durabletask/instances/INSTANCEID/terminate?reason={text}&taskHub=DurableFunctionsHub&connection=Storage&code=foo","rewindPostUri":"http://localhost:8080/runtime/webhooks/durabletask/instances/INSTANCEID/rewind?reason={text}&taskHub=DurableFunctionsHub&connection=Storage&code=foo","purgeHistoryDeleteUri":"http://localhost:8080/runtime/webhooks/durabletask/instances/INSTANCEID?taskHub=DurableFunctionsHub&connection=Storage&code=foo"}}"#.to_owned())),
+        }
+        .into()
+    }
+
+    #[test]
+    fn it_creates_a_management_payload() {
+        let payload = create_client().create_http_management_payload("1234");
+
+        assert_eq!(payload.instance_id, "1234");
+        assert_eq!(payload.status_query_get_uri, "http://localhost:8080/runtime/webhooks/durabletask/instances/1234?taskHub=DurableFunctionsHub&connection=Storage&code=foo");
+        assert_eq!(payload.send_event_post_uri, "http://localhost:8080/runtime/webhooks/durabletask/instances/1234/raiseEvent/{eventName}?taskHub=DurableFunctionsHub&connection=Storage&code=foo");
+        assert_eq!(payload.terminate_post_uri, "http://localhost:8080/runtime/webhooks/durabletask/instances/1234/terminate?reason={text}&taskHub=DurableFunctionsHub&connection=Storage&code=foo");
+        assert_eq!(payload.rewind_post_uri.unwrap(), "http://localhost:8080/runtime/webhooks/durabletask/instances/1234/rewind?reason={text}&taskHub=DurableFunctionsHub&connection=Storage&code=foo");
+        assert_eq!(payload.purge_history_delete_uri, "http://localhost:8080/runtime/webhooks/durabletask/instances/1234?taskHub=DurableFunctionsHub&connection=Storage&code=foo");
+    }
+
+    #[test]
+    fn it_creates_a_check_status_response() {
+        let response = create_client().create_check_status_response("1234");
+
+        assert_eq!(response.status(), Status::Accepted);
+        assert_eq!(response.headers().get("Location").unwrap(), "http://localhost:8080/runtime/webhooks/durabletask/instances/1234?taskHub=DurableFunctionsHub&connection=Storage&code=foo");
+        assert_eq!(response.headers().get("Retry-After").unwrap(), "10");
+        assert_eq!(
+            response.headers().get("Content-Type").unwrap(),
+            "application/json"
+        );
+
+        let body: Value = response.body().as_json().unwrap();
+        assert_eq!(body["id"], "1234");
+        assert_eq!(body["statusQueryGetUri"], "http://localhost:8080/runtime/webhooks/durabletask/instances/1234?taskHub=DurableFunctionsHub&connection=Storage&code=foo");
+    }
+
+    #[test]
+    fn it_creates_a_completion_response_with_the_output() {
+        let status: OrchestrationStatus = from_str(
+            r#"{"runtimeStatus":"Completed","input":null,"customStatus":null,"output":["Hello!"],"createdTime":"2019-11-01T00:00:00Z"}"#,
+        )
+        .unwrap();
+
+        let response = completion_response(status).unwrap();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(
+            response.body().as_json::<Value>().unwrap(),
+            serde_json::json!(["Hello!"])
+        );
+    }
+
+    #[test]
+    fn it_creates_a_completion_response_with_the_status_for_failures() {
+        let status: OrchestrationStatus = from_str(
+            r#"{"runtimeStatus":"Failed","input":null,"customStatus":null,"output":"boom","createdTime":"2019-11-01T00:00:00Z"}"#,
+        )
+        .unwrap();
+
+        let response = completion_response(status).unwrap();
+        assert_eq!(response.status(), Status::Ok);
+
+        let body: Value = response.body().as_json().unwrap();
+        assert_eq!(body["runtimeStatus"], "Failed");
+        assert_eq!(body["output"], "boom");
+    }
+
+    #[test]
+    fn it_does_not_create_a_completion_response_while_running() {
+        let status: OrchestrationStatus = from_str(
+            r#"{"runtimeStatus":"Running","input":null,"customStatus":null,"output":null,"createdTime":"2019-11-01T00:00:00Z"}"#,
+        )
+        .unwrap();
+
+        assert!(completion_response(status).is_none());
+    }
+
+    #[test]
+    fn it_waits_for_completion_with_a_fake_client() {
+        let fake = Arc::new(FakeClient::new("hub"));
+        fake.add_status(
+            "1234",
+            from_str(
+                r#"{"runtimeStatus":"Completed","input":null,"customStatus":null,"output":"done","createdTime":"2019-11-01T00:00:00Z"}"#,
+            )
+            .unwrap(),
+        );
+
+        let client = DurableOrchestrationClient::new(fake.clone(), fake.management_urls());
+        let response = futures::executor::block_on(
+            client.wait_for_completion_or_create_check_status_response(
+                "1234",
+                Duration::from_secs(10),
+                Duration::from_secs(1),
+            ),
+        );
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body().as_json::<Value>().unwrap(), "done");
+        assert_eq!(
+            fake.calls(),
+            vec![ClientCall::InstanceStatus {
+                instance_id: "1234".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn it_does_not_wait_for_an_instance_that_does_not_exist() {
+        let fake = Arc::new(FakeClient::new("hub"));
+
+        let client = DurableOrchestrationClient::new(fake.clone(), fake.management_urls());
+        let response = futures::executor::block_on(
+            client.wait_for_completion_or_create_check_status_response(
+                "missing",
+                Duration::from_secs(3600),
+                Duration::from_secs(1),
+            ),
+        );
+
+        assert_eq!(response.status(), Status::NotFound);
+        assert_eq!(
+            response.body().as_json::<Value>().unwrap()["detail"],
+            "instance doesn't exist or has not started running"
+        );
+        assert_eq!(fake.calls().len(), 1);
+    }
+
+    #[test]
+    fn it_waits_at_least_the_minimum_retry_interval() {
+        let fake = Arc::new(FakeClient::new("hub"));
+        for status in &["Running", "Completed"] {
+            fake.add_status(
+                "1234",
+                from_str(&format!(
+                    r#"{{"runtimeStatus":"{}","input":null,"customStatus":null,"output":"done","createdTime":"2019-11-01T00:00:00Z"}}"#,
+                    status
+                ))
+                .unwrap(),
+            );
+        }
+
+        let client = DurableOrchestrationClient::new(fake.clone(), fake.management_urls());
+        let start = Instant::now();
+        let response = tokio::runtime::Runtime::new().unwrap().block_on(
+            client.wait_for_completion_or_create_check_status_response(
+                "1234",
+                Duration::from_secs(10),
+                Duration::from_secs(0),
+            ),
+        );
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body().as_json::<Value>().unwrap(), "done");
+        assert!(start.elapsed() >= MIN_RETRY_INTERVAL);
+        assert_eq!(fake.calls().len(), 2);
+    }
+
+    #[test]
+    fn it_round_trips_typed_event_data() {
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+        struct Approval {
+            approved: bool,
+            approver: String,
+        }
+
+        async fn wait_for_approval(context: DurableOrchestrationContext) -> OrchestrationOutput {
+            let approval: Approval = context.wait_for_event("approval").await.unwrap();
+            to_value(approval).unwrap().into()
+        }
+
+        let approval = Approval {
+            approved: true,
+            approver: "jane".to_string(),
+        };
+
+        let fake = Arc::new(FakeClient::new("hub"));
+        fake.add_status(
+            "1234",
+            from_str(
+                r#"{"runtimeStatus":"Running","input":null,"customStatus":null,"output":null,"createdTime":"2019-11-01T00:00:00Z"}"#,
+            )
+            .unwrap(),
+        );
+
+        let client = DurableOrchestrationClient::new(fake.clone(), fake.management_urls());
+        futures::executor::block_on(client.raise_event("1234", "approval", approval.clone()))
+            .unwrap();
+
+        let event_data = match fake.calls().pop() {
+            Some(ClientCall::RaiseEvent { event_data, .. }) => event_data,
+            _ => panic!("expected an event to be raised"),
+        };
+
+        let mut tester = OrchestrationTester::new("approval", Value::Null);
+        tester.run(wait_for_approval);
+        tester.raise_event("approval", event_data);
+
+        let result = tester.run(wait_for_approval);
+        assert!(result.is_done);
+        assert_eq!(
+            from_value::<Approval>(result.output.unwrap()).unwrap(),
+            approval
+        );
+    }
 }
@@ -1,14 +1,15 @@
 use crate::{
     durable::{
-        Action, ActionFuture, EventType, HistoryEvent, JoinAll, OrchestrationFuture,
-        OrchestrationState, RetryOptions, SelectAll,
+        Action, ActionFuture, DurableHttpRequest, DurableHttpResponse, EventTimeoutFuture,
+        EventType, FanOutMode, HistoryEvent, JoinAll, OrchestrationFuture, OrchestrationState,
+        RetryOptions, SelectAll, TaskError, TimerFuture, HTTP_ACTIVITY_NAME,
     },
     rpc::{typed_data::Data, TypedData},
 };
-use chrono::{DateTime, Utc};
-use serde::Deserialize;
-use serde_json::{from_str, Value};
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use chrono::{DateTime, Duration, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{from_str, from_value, to_value, Value};
+use std::{cell::RefCell, collections::HashMap, future::Future, rc::Rc};
 
 /// Represents the Durable Functions orchestration context binding.
 ///
@@ -19,6 +20,11 @@ use std::{cell::RefCell, collections::HashMap, rc::Rc};
 /// | `name`          | The name of the parameter being bound.                                |
 /// | `orchestration` | The name of the orchestration.  Defaults to the name of the function. |
 ///
+/// Orchestration functions are replayed and must be deterministic. The `#[func]` attribute
+/// rejects known non-deterministic calls (e.g. `Utc::now`, `Uuid::new_v4` or `std::thread::sleep`)
+/// in favor of `current_time`, `new_guid` and `create_timer`. The check can be disabled with
+/// `#[func(allow_nondeterminism)]` for orchestrations that use such calls in a deterministic way.
+///
 /// # Examples
 ///
 /// Calling multiple activities and waiting for them all to complete:
@@ -56,6 +62,37 @@ use std::{cell::RefCell, collections::HashMap, rc::Rc};
 ///     result.into()
 /// }
 /// ```
+///
+/// Calling an activity with typed input and output:
+///
+/// ```rust
+/// use azure_functions::{bindings::DurableOrchestrationContext, durable::OrchestrationOutput, func};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize)]
+/// struct Order {
+///     id: u32,
+///     quantity: u32,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Receipt {
+///     total: f64,
+/// }
+///
+/// #[func]
+/// pub async fn run(context: DurableOrchestrationContext) -> OrchestrationOutput {
+///     let order = Order { id: 1, quantity: 3 };
+///
+///     match context
+///         .call_typed_activity::<_, Receipt>("place_order", order)
+///         .await
+///     {
+///         Ok(receipt) => receipt.total.into(),
+///         Err(e) => format!("Failed to place order: {}", e).into(),
+///     }
+/// }
+/// ```
 pub struct DurableOrchestrationContext {
     /// The orchestration instance identifier.
     pub instance_id: String,
@@ -63,6 +100,7 @@ pub struct DurableOrchestrationContext {
     pub parent_instance_id: Option<String>,
     /// The input value to the orchestration.
     pub input: Value,
+    version: Option<String>,
     state: Rc<RefCell<OrchestrationState>>,
 }
 
@@ -83,18 +121,51 @@ impl DurableOrchestrationContext {
                 let data: BindingData =
                     from_str(s).expect("failed to parse orchestration context data");
 
-                DurableOrchestrationContext {
-                    instance_id: data.instance_id,
-                    parent_instance_id: data.parent_instance_id,
-                    input: data.input,
-                    state: Rc::new(RefCell::new(OrchestrationState::new(data.history))),
-                }
+                DurableOrchestrationContext::with_history(
+                    data.instance_id,
+                    data.parent_instance_id,
+                    data.input,
+                    data.history,
+                )
             }
             _ => panic!("expected JSON data for orchestration context data"),
         }
     }
 
+    pub(crate) fn with_history(
+        instance_id: String,
+        parent_instance_id: Option<String>,
+        input: Value,
+        history: Vec<HistoryEvent>,
+    ) -> Self {
+        let mut state = OrchestrationState::new(history);
+        state.instance_id = instance_id.clone();
+
+        DurableOrchestrationContext {
+            instance_id,
+            parent_instance_id,
+            input,
+            version: None,
+            state: Rc::new(RefCell::new(state)),
+        }
+    }
+
+    /// Gets the version of the orchestration function.
+    ///
+    /// The version is specified with the `version` argument of the `#[func]` attribute.
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_ref().map(String::as_str)
+    }
+
+    #[doc(hidden)]
+    pub fn set_version(&mut self, version: &str) {
+        self.version = Some(version.to_owned());
+        self.state.borrow_mut().version = Some(version.to_owned());
+    }
+
     /// Gets a value indicating whether the orchestrator function is currently replaying itself.
+    ///
+    /// Records logged while the orchestrator function is replaying are not sent to the host.
     pub fn is_replaying(&self) -> bool {
         self.state.borrow().is_replaying()
     }
@@ -143,6 +214,9 @@ impl DurableOrchestrationContext {
     /// completion the item resolved will be returned, along with the index of the
     /// future that was ready and the list of all the remaining futures.
     ///
+    /// The remaining futures are canceled (e.g. durable timers that lost the select) unless
+    /// `SelectAll::keep_remaining` is used to await them later.
+    ///
     /// # Panics
     ///
     /// This function will panic if the iterator specified contains no items.
@@ -154,6 +228,106 @@ impl DurableOrchestrationContext {
         SelectAll::new(self.state.clone(), iter)
     }
 
+    /// Runs the given calls with at most `max_parallelism` of them outstanding at a time.
+    ///
+    /// Each call is a function that starts a task (e.g. an activity call); a new task is started as each
+    /// outstanding task completes.  On success, the results are returned in the order of the calls.
+    ///
+    /// On failure, the failures are returned with the positions of the failed calls.  With `FanOutMode::FailFast`,
+    /// only the first failure is returned and no new tasks are started.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_parallelism` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::{
+    ///     bindings::DurableOrchestrationContext,
+    ///     durable::{FanOutMode, OrchestrationOutput},
+    ///     func,
+    /// };
+    ///
+    /// #[func]
+    /// pub async fn process(context: DurableOrchestrationContext) -> OrchestrationOutput {
+    ///     let items = vec!["a", "b", "c", "d"];
+    ///     let ctx = &context;
+    ///
+    ///     let calls = items
+    ///         .into_iter()
+    ///         .map(|item| move || ctx.call_activity("process_item", item));
+    ///
+    ///     match context.fan_out(calls, 2, FanOutMode::FailFast).await {
+    ///         Ok(results) => results.into_iter().collect(),
+    ///         Err(failures) => format!("item {} failed: {}", failures[0].0, failures[0].1).into(),
+    ///     }
+    /// }
+    /// ```
+    pub async fn fan_out<I, C, F, T, E>(
+        &self,
+        calls: I,
+        max_parallelism: usize,
+        mode: FanOutMode,
+    ) -> Result<Vec<T>, Vec<(usize, E)>>
+    where
+        I: IntoIterator<Item = C>,
+        C: FnOnce() -> F,
+        F: OrchestrationFuture<Output = Result<T, E>> + Unpin,
+    {
+        assert!(
+            max_parallelism > 0,
+            "max_parallelism must be greater than zero"
+        );
+
+        let mut calls = calls.into_iter().enumerate();
+        let mut results = Vec::new();
+        let mut failures = Vec::new();
+        let mut outstanding = Vec::new();
+        let mut positions = Vec::new();
+
+        loop {
+            while outstanding.len() < max_parallelism {
+                match calls.next() {
+                    Some((position, call)) => {
+                        outstanding.push(call());
+                        positions.push(position);
+                        results.push(None);
+                    }
+                    None => break,
+                }
+            }
+
+            if outstanding.is_empty() {
+                break;
+            }
+
+            // The outstanding calls are still awaited, so they must not be canceled by the select
+            let (output, idx, rest) = self.select_all(outstanding).keep_remaining().await;
+            outstanding = rest;
+
+            let position = positions.remove(idx);
+            match output {
+                Ok(result) => results[position] = Some(result),
+                Err(e) => {
+                    failures.push((position, e));
+                    if mode == FanOutMode::FailFast {
+                        for f in outstanding.iter_mut() {
+                            f.cancel();
+                        }
+                        return Err(failures);
+                    }
+                }
+            }
+        }
+
+        if !failures.is_empty() {
+            return Err(failures);
+        }
+
+        Ok(results.into_iter().map(Option::unwrap).collect())
+    }
+
     /// Schedules an activity function for execution.
     #[must_use = "futures do nothing unless you `.await` or poll them"]
     pub fn call_activity<D>(
@@ -173,6 +347,7 @@ impl DurableOrchestrationContext {
             EventType::TaskScheduled,
             EventType::TaskCompleted,
             Some(EventType::TaskFailed),
+            untyped_result,
         )
     }
 
@@ -197,6 +372,7 @@ impl DurableOrchestrationContext {
             EventType::TaskScheduled,
             EventType::TaskCompleted,
             Some(EventType::TaskFailed),
+            untyped_result,
         )
     }
 
@@ -221,6 +397,7 @@ impl DurableOrchestrationContext {
             EventType::SubOrchestrationInstanceCreated,
             EventType::SubOrchestrationInstanceCompleted,
             Some(EventType::SubOrchestrationInstanceFailed),
+            untyped_result,
         )
     }
 
@@ -247,9 +424,227 @@ impl DurableOrchestrationContext {
             EventType::SubOrchestrationInstanceCreated,
             EventType::SubOrchestrationInstanceCompleted,
             Some(EventType::SubOrchestrationInstanceFailed),
+            untyped_result,
+        )
+    }
+
+    /// Schedules an activity function for execution with a serialized input and a deserialized output.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the input cannot be serialized to JSON.
+    #[must_use = "futures do nothing unless you `.await` or poll them"]
+    pub fn call_typed_activity<I, O>(
+        &self,
+        activity_name: &str,
+        input: I,
+    ) -> ActionFuture<Result<O, TaskError>>
+    where
+        I: Serialize,
+        O: DeserializeOwned + Unpin,
+    {
+        self.perform_call_action(
+            Action::CallActivity {
+                function_name: activity_name.to_string(),
+                input: serialize_input(activity_name, input),
+            },
+            activity_name,
+            EventType::TaskScheduled,
+            EventType::TaskCompleted,
+            Some(EventType::TaskFailed),
+            typed_result,
+        )
+    }
+
+    /// Schedules an activity function for execution with retry options, a serialized input and a deserialized output.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the input cannot be serialized to JSON.
+    #[must_use = "futures do nothing unless you `.await` or poll them"]
+    pub fn call_typed_activity_with_retry<I, O>(
+        &self,
+        activity_name: &str,
+        input: I,
+        retry_options: RetryOptions,
+    ) -> ActionFuture<Result<O, TaskError>>
+    where
+        I: Serialize,
+        O: DeserializeOwned + Unpin,
+    {
+        self.perform_call_action(
+            Action::CallActivityWithRetry {
+                function_name: activity_name.to_string(),
+                retry_options,
+                input: serialize_input(activity_name, input),
+            },
+            activity_name,
+            EventType::TaskScheduled,
+            EventType::TaskCompleted,
+            Some(EventType::TaskFailed),
+            typed_result,
+        )
+    }
+
+    /// Schedules an orchestration function for execution with a serialized input and a deserialized output.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the input cannot be serialized to JSON.
+    #[must_use = "futures do nothing unless you `.await` or poll them"]
+    pub fn call_typed_sub_orchestrator<I, O>(
+        &self,
+        function_name: &str,
+        instance_id: Option<String>,
+        input: I,
+    ) -> ActionFuture<Result<O, TaskError>>
+    where
+        I: Serialize,
+        O: DeserializeOwned + Unpin,
+    {
+        self.perform_call_action(
+            Action::CallSubOrchestrator {
+                function_name: function_name.to_string(),
+                instance_id,
+                input: serialize_input(function_name, input),
+            },
+            function_name,
+            EventType::SubOrchestrationInstanceCreated,
+            EventType::SubOrchestrationInstanceCompleted,
+            Some(EventType::SubOrchestrationInstanceFailed),
+            typed_result,
+        )
+    }
+
+    /// Schedules an orchestration function for execution with retry, a serialized input and a deserialized output.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the input cannot be serialized to JSON.
+    #[must_use = "futures do nothing unless you `.await` or poll them"]
+    pub fn call_typed_sub_orchestrator_with_retry<I, O>(
+        &self,
+        function_name: &str,
+        instance_id: Option<String>,
+        input: I,
+        retry_options: RetryOptions,
+    ) -> ActionFuture<Result<O, TaskError>>
+    where
+        I: Serialize,
+        O: DeserializeOwned + Unpin,
+    {
+        self.perform_call_action(
+            Action::CallSubOrchestratorWithRetry {
+                function_name: function_name.to_string(),
+                retry_options,
+                instance_id,
+                input: serialize_input(function_name, input),
+            },
+            function_name,
+            EventType::SubOrchestrationInstanceCreated,
+            EventType::SubOrchestrationInstanceCompleted,
+            Some(EventType::SubOrchestrationInstanceFailed),
+            typed_result,
+        )
+    }
+
+    /// Makes a durable HTTP request.
+    ///
+    /// The request is performed by the Durable Functions extension and its response is replayed from
+    /// the orchestration history.  Failures to perform the request are returned as a `TaskError`;
+    /// HTTP error statuses are returned as a response.
+    pub fn call_http(
+        &self,
+        request: DurableHttpRequest,
+    ) -> ActionFuture<Result<DurableHttpResponse, TaskError>> {
+        self.perform_call_action(
+            Action::CallHttp {
+                http_request: request,
+            },
+            HTTP_ACTIVITY_NAME,
+            EventType::TaskScheduled,
+            EventType::TaskCompleted,
+            Some(EventType::TaskFailed),
+            typed_result,
         )
     }
 
+    /// Calls a durable task, retrying failures with durable timers according to the given retry options.
+    ///
+    /// Unlike the `*_with_retry` functions, which delegate retries to the Durable Functions extension,
+    /// the retries are performed by the orchestration: a failure is only retried if the `handle`
+    /// predicate of the retry options returns true for it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::{
+    ///     bindings::DurableOrchestrationContext,
+    ///     durable::{OrchestrationOutput, RetryOptions, TaskError},
+    ///     func,
+    /// };
+    /// use std::time::Duration;
+    ///
+    /// #[func]
+    /// pub async fn run(context: DurableOrchestrationContext) -> OrchestrationOutput {
+    ///     let options = RetryOptions::builder(Duration::from_secs(5), 3)
+    ///         .handle(|e| match e {
+    ///             TaskError::Failed { reason, .. } => reason.contains("busy"),
+    ///             _ => false,
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///
+    ///     let ctx = &context;
+    ///     match context
+    ///         .call_with_retry(&options, || ctx.call_typed_activity::<_, String>("reserve", "seat"))
+    ///         .await
+    ///     {
+    ///         Ok(reservation) => reservation.into(),
+    ///         Err(e) => format!("Failed to reserve a seat: {}", e).into(),
+    ///     }
+    /// }
+    /// ```
+    pub async fn call_with_retry<C, F, T>(
+        &self,
+        retry_options: &RetryOptions,
+        mut call: C,
+    ) -> Result<T, TaskError>
+    where
+        C: FnMut() -> F,
+        F: Future<Output = Result<T, TaskError>>,
+    {
+        let started = self.current_time();
+        let mut attempt = 1;
+
+        loop {
+            let error = match call().await {
+                Ok(output) => return Ok(output),
+                Err(e) => e,
+            };
+
+            if attempt >= retry_options.max_attempts() || !retry_options.should_retry(&error) {
+                return Err(error);
+            }
+
+            let fire_at = self.current_time()
+                + Duration::from_std(retry_options.retry_delay(attempt))
+                    .expect("retry delay must be a valid duration");
+
+            if let Some(timeout) = retry_options.retry_timeout() {
+                if fire_at
+                    > started
+                        + Duration::from_std(timeout).expect("timeout must be a valid duration")
+                {
+                    return Err(error);
+                }
+            }
+
+            self.create_timer(fire_at).await;
+            attempt += 1;
+        }
+    }
+
     /// Restarts the orchestration by clearing its history.
     pub fn continue_as_new<D>(&self, input: D, preserve_unprocessed_events: bool)
     where
@@ -264,11 +659,12 @@ impl DurableOrchestrationContext {
     }
 
     /// Creates a durable timer that expires at a specified time.
-    #[must_use = "futures do nothing unless you `.await` or poll them"]
-    pub fn create_timer(&self, fire_at: DateTime<Utc>) -> ActionFuture<()> {
+    ///
+    /// The timer can be canceled with `TimerFuture::cancel` and is canceled automatically when it loses a `select_all`.
+    pub fn create_timer(&self, fire_at: DateTime<Utc>) -> TimerFuture {
         let mut state = self.state.borrow_mut();
 
-        state.push_action(Action::CreateTimer {
+        let action = state.push_action(Action::CreateTimer {
             fire_at,
             canceled: false,
         });
@@ -286,51 +682,77 @@ impl DurableOrchestrationContext {
             }
         }
 
-        ActionFuture::new(result, self.state.clone(), event_index)
+        TimerFuture::new(
+            ActionFuture::new(result, self.state.clone(), event_index),
+            self.state.clone(),
+            action,
+        )
     }
 
-    /// Wait for an external event of the given name.
-    pub fn wait_for_event(&self, name: &str) -> ActionFuture<Result<Value, String>> {
+    /// Wait for an external event of the given name, deserializing the event data.
+    ///
+    /// Events that are raised before they are waited on are buffered; multiple waits for the
+    /// same event name receive the events in the order they were raised.
+    ///
+    /// Returns `TaskError::InvalidOutput` if the event data cannot be deserialized.
+    pub fn wait_for_event<T>(&self, name: &str) -> ActionFuture<Result<T, TaskError>>
+    where
+        T: DeserializeOwned + Unpin,
+    {
         let mut state = self.state.borrow_mut();
 
         state.push_action(Action::WaitForExternalEvent {
             external_event_name: name.to_string(),
         });
 
-        let mut input = None;
+        let mut data = None;
         let mut event_index = None;
 
         if let Some((idx, raised)) = state.find_event_raised(name) {
             raised.is_processed = true;
-            // For some reason, the data comes through as stringified JSON, so parse it
-            input = Some(Ok(raised
-                .input
-                .as_ref()
-                .map(|v| {
-                    v.as_str()
-                        .map(|s| from_str(&s).unwrap_or_default())
-                        .unwrap_or_default()
-                })
-                .unwrap_or_default()));
+            data = Some(event_data(raised));
             event_index = Some(idx);
         }
 
-        ActionFuture::new(input, self.state.clone(), event_index)
+        ActionFuture::new(data, self.state.clone(), event_index)
+    }
+
+    /// Wait for an external event of the given name or until the given timeout expires.
+    ///
+    /// The timeout is a durable timer relative to the current time of the orchestration.
+    /// The timer is canceled if the event is received first.
+    pub fn wait_for_event_with_timeout<T>(
+        &self,
+        name: &str,
+        timeout: std::time::Duration,
+    ) -> EventTimeoutFuture<T>
+    where
+        T: DeserializeOwned + Unpin,
+    {
+        let fire_at = self.current_time()
+            + Duration::from_std(timeout).expect("timeout must be a valid duration");
+
+        EventTimeoutFuture::new(
+            self.wait_for_event(name),
+            self.create_timer(fire_at),
+            self.state.clone(),
+        )
     }
 
-    fn perform_call_action(
+    fn perform_call_action<T>(
         &self,
         action: Action,
         name: &str,
         started_type: EventType,
         completed_type: EventType,
         failed_type: Option<EventType>,
-    ) -> ActionFuture<Result<Value, String>> {
+        convert: fn(&HistoryEvent, bool) -> T,
+    ) -> ActionFuture<T> {
         let mut state = self.state.borrow_mut();
 
         state.push_action(action);
 
-        let mut result: Option<Result<Value, String>> = None;
+        let mut result: Option<T> = None;
         let mut event_index = None;
 
         if let Some((idx, scheduled)) = state.find_start_event(name, started_type) {
@@ -341,14 +763,10 @@ impl DurableOrchestrationContext {
                 event_index = Some(idx);
 
                 if finished.event_type == completed_type {
-                    result = Some(Ok(finished
-                        .result
-                        .as_ref()
-                        .map(|s| from_str(&s).unwrap_or_default())
-                        .unwrap_or_default()));
+                    result = Some(convert(finished, true));
                 } else if let Some(failed_type) = failed_type {
                     if finished.event_type == failed_type {
-                        result = Some(Err(finished.reason.clone().unwrap_or_default()));
+                        result = Some(convert(finished, false));
                     }
                 } else {
                     panic!("event must be a completion or a failure");
@@ -360,12 +778,66 @@ impl DurableOrchestrationContext {
     }
 }
 
+fn untyped_result(event: &HistoryEvent, completed: bool) -> Result<Value, String> {
+    if completed {
+        Ok(event
+            .result
+            .as_ref()
+            .map(|s| from_str(&s).unwrap_or_default())
+            .unwrap_or_default())
+    } else {
+        Err(event.reason.clone().unwrap_or_default())
+    }
+}
+
+fn typed_result<T>(event: &HistoryEvent, completed: bool) -> Result<T, TaskError>
+where
+    T: DeserializeOwned,
+{
+    if !completed {
+        return Err(TaskError::Failed {
+            reason: event.reason.clone().unwrap_or_default(),
+            details: event.details.clone(),
+        });
+    }
+
+    match &event.result {
+        Some(s) => from_str(s),
+        None => serde_json::from_value(Value::Null),
+    }
+    .map_err(|e| TaskError::InvalidOutput(e.to_string()))
+}
+
+fn event_data<T>(event: &HistoryEvent) -> Result<T, TaskError>
+where
+    T: DeserializeOwned,
+{
+    // The data of raised events is recorded as a JSON string
+    match &event.input {
+        Some(Value::String(s)) => from_str(s),
+        Some(v) => from_value(v.clone()),
+        None => from_value(Value::Null),
+    }
+    .map_err(|e| TaskError::InvalidOutput(e.to_string()))
+}
+
+fn serialize_input<I>(name: &str, input: I) -> Value
+where
+    I: Serialize,
+{
+    to_value(input)
+        .unwrap_or_else(|e| panic!("failed to serialize input for function '{}': {}", name, e))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::durable::{EventType, HistoryEvent};
+    use crate::durable::{
+        EventOutcome, EventType, HistoryEvent, OrchestrationOutput, OrchestrationTester,
+    };
     use crate::rpc::typed_data::Data;
     use chrono::DateTime;
+    use serde_json::json;
 
     #[test]
     #[should_panic(expected = "expected JSON data for orchestration context data")]
@@ -488,4 +960,518 @@ mod tests {
             ]
         );
     }
+
+    #[derive(Serialize, Deserialize)]
+    struct Order {
+        id: u32,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Receipt {
+        total: f64,
+    }
+
+    async fn place_order(context: DurableOrchestrationContext) -> OrchestrationOutput {
+        match context
+            .call_typed_activity::<_, Receipt>("place_order", Order { id: 7 })
+            .await
+        {
+            Ok(receipt) => receipt.total.into(),
+            Err(TaskError::Failed { reason, details }) => {
+                json!({ "reason": reason, "details": details }).into()
+            }
+            Err(TaskError::InvalidOutput(msg)) => json!({ "invalid": msg }).into(),
+        }
+    }
+
+    #[test]
+    fn it_serializes_typed_activity_input() {
+        let mut tester = OrchestrationTester::new("place_order", Value::Null);
+
+        let result = tester.run(place_order);
+        assert_eq!(
+            result.actions,
+            vec![vec![Action::CallActivity {
+                function_name: "place_order".to_string(),
+                input: json!({ "id": 7 }),
+            }]]
+        );
+    }
+
+    #[test]
+    fn it_deserializes_typed_activity_output() {
+        let mut tester = OrchestrationTester::new("place_order", Value::Null);
+
+        tester.run(place_order);
+        tester.complete_activity("place_order", json!({ "total": 42.5 }));
+
+        let result = tester.run(place_order);
+        assert!(result.is_done);
+        assert_eq!(result.output, Some(json!(42.5)));
+    }
+
+    #[test]
+    fn it_returns_an_error_for_invalid_typed_activity_output() {
+        let mut tester = OrchestrationTester::new("place_order", Value::Null);
+
+        tester.run(place_order);
+        tester.complete_activity("place_order", "not a receipt");
+
+        let result = tester.run(place_order);
+        assert!(result.is_done);
+        assert!(result.output.unwrap()["invalid"].is_string());
+    }
+
+    #[test]
+    fn it_returns_an_error_for_failed_typed_activities() {
+        let mut tester = OrchestrationTester::new("place_order", Value::Null);
+
+        tester.run(place_order);
+        tester.fail_activity("place_order", "out of stock");
+
+        let result = tester.run(place_order);
+        assert!(result.is_done);
+        assert_eq!(
+            result.output,
+            Some(json!({ "reason": "out of stock", "details": null }))
+        );
+    }
+
+    #[test]
+    fn it_calls_typed_sub_orchestrators() {
+        async fn parent(context: DurableOrchestrationContext) -> OrchestrationOutput {
+            let receipt: Receipt = context
+                .call_typed_sub_orchestrator("child", None, Order { id: 1 })
+                .await
+                .unwrap();
+            receipt.total.into()
+        }
+
+        let mut tester = OrchestrationTester::new("parent", Value::Null);
+
+        let result = tester.run(parent);
+        assert_eq!(
+            result.actions,
+            vec![vec![Action::CallSubOrchestrator {
+                function_name: "child".to_string(),
+                instance_id: None,
+                input: json!({ "id": 1 }),
+            }]]
+        );
+
+        tester.complete_sub_orchestration("child", json!({ "total": 1.5 }));
+
+        let result = tester.run(parent);
+        assert_eq!(result.output, Some(json!(1.5)));
+    }
+
+    async fn approval_with_deadline(context: DurableOrchestrationContext) -> OrchestrationOutput {
+        match context
+            .wait_for_event_with_timeout::<bool>("approval", std::time::Duration::from_secs(3600))
+            .await
+        {
+            Ok(EventOutcome::Received(approved)) => approved.into(),
+            Ok(EventOutcome::TimedOut) => "escalated".into(),
+            Err(e) => e.to_string().into(),
+        }
+    }
+
+    fn timer_canceled(result: &crate::durable::ExecutionResult) -> bool {
+        match result.actions.iter().flatten().last() {
+            Some(Action::CreateTimer { canceled, .. }) => *canceled,
+            _ => panic!("expected the last action to be a timer"),
+        }
+    }
+
+    #[test]
+    fn it_receives_an_event_before_the_timeout() {
+        let mut tester = OrchestrationTester::new("approval", Value::Null);
+
+        let result = tester.run(approval_with_deadline);
+        assert_eq!(result.actions[0].len(), 2);
+        assert!(!timer_canceled(&result));
+
+        tester.raise_event("approval", true);
+
+        let result = tester.run(approval_with_deadline);
+        assert!(result.is_done);
+        assert_eq!(result.output, Some(json!(true)));
+        assert!(timer_canceled(&result));
+    }
+
+    #[test]
+    fn it_times_out_waiting_for_an_event() {
+        let mut tester = OrchestrationTester::new("approval", Value::Null);
+        let start = tester.current_time();
+
+        tester.run(approval_with_deadline);
+        tester.fire_timer();
+
+        assert_eq!(tester.current_time(), start + Duration::hours(1));
+
+        let result = tester.run(approval_with_deadline);
+        assert_eq!(result.output, Some(json!("escalated")));
+        assert!(!timer_canceled(&result));
+    }
+
+    #[test]
+    fn it_returns_an_error_for_an_invalid_event_payload() {
+        let mut tester = OrchestrationTester::new("approval", Value::Null);
+
+        tester.run(approval_with_deadline);
+        tester.raise_event("approval", "yes");
+
+        let result = tester.run(approval_with_deadline);
+        assert_eq!(
+            result.output,
+            Some(json!(
+                "failed to deserialize output: invalid type: string \"yes\", expected a boolean at line 1 column 5"
+            ))
+        );
+    }
+
+    #[test]
+    fn it_buffers_events_in_the_order_they_were_raised() {
+        async fn collect(context: DurableOrchestrationContext) -> OrchestrationOutput {
+            let first: Order = context.wait_for_event("order").await.unwrap();
+            let second: Order = context.wait_for_event("order").await.unwrap();
+            vec![first.id, second.id].into()
+        }
+
+        let mut tester = OrchestrationTester::new("collect", Value::Null);
+
+        tester.raise_event("order", Order { id: 1 });
+        tester.raise_event("order", Order { id: 2 });
+
+        let result = tester.run(collect);
+        assert!(result.is_done);
+        assert_eq!(result.output, Some(json!([1, 2])));
+    }
+
+    #[test]
+    fn it_returns_an_error_for_an_invalid_typed_event() {
+        async fn order(context: DurableOrchestrationContext) -> OrchestrationOutput {
+            match context.wait_for_event::<Order>("order").await {
+                Ok(order) => order.id.into(),
+                Err(TaskError::InvalidOutput(_)) => "invalid".into(),
+                Err(e) => e.to_string().into(),
+            }
+        }
+
+        let mut tester = OrchestrationTester::new("order", Value::Null);
+        tester.raise_event("order", "not an order");
+
+        let result = tester.run(order);
+        assert_eq!(result.output, Some(json!("invalid")));
+    }
+
+    #[test]
+    fn it_buffers_an_event_raised_after_a_timeout() {
+        async fn retry_approval(context: DurableOrchestrationContext) -> OrchestrationOutput {
+            if let Ok(EventOutcome::Received(approved)) = context
+                .wait_for_event_with_timeout::<bool>("approval", std::time::Duration::from_secs(60))
+                .await
+            {
+                return approved.into();
+            }
+
+            let approved: bool = context.wait_for_event("approval").await.unwrap();
+            format!("late: {}", approved).into()
+        }
+
+        let mut tester = OrchestrationTester::new("approval", Value::Null);
+
+        tester.run(retry_approval);
+        tester.fire_timer();
+        tester.run(retry_approval);
+        tester.raise_event("approval", true);
+
+        let result = tester.run(retry_approval);
+        assert!(result.is_done);
+        assert_eq!(result.output, Some(json!("late: true")));
+    }
+
+    #[test]
+    fn it_awaits_a_timer_that_lost_a_select() {
+        async fn both(context: DurableOrchestrationContext) -> OrchestrationOutput {
+            let now = context.current_time();
+            let (_, index, mut rest) = context
+                .select_all(vec![
+                    context.create_timer(now + Duration::hours(2)),
+                    context.create_timer(now + Duration::hours(1)),
+                ])
+                .keep_remaining()
+                .await;
+            rest.pop().unwrap().await;
+            (index as u32).into()
+        }
+
+        let mut tester = OrchestrationTester::new("both", Value::Null);
+
+        tester.run(both);
+        tester.fire_timer();
+
+        let result = tester.run(both);
+        assert!(!result.is_done);
+        match result.actions[0].as_slice() {
+            [Action::CreateTimer {
+                canceled: false, ..
+            }, Action::CreateTimer {
+                canceled: false, ..
+            }] => {}
+            actions => panic!("unexpected actions: {:?}", actions),
+        }
+
+        tester.fire_timer();
+
+        let result = tester.run(both);
+        assert!(result.is_done);
+        assert_eq!(result.output, Some(json!(1)));
+    }
+
+    #[test]
+    fn it_cancels_timers_that_lost_a_select() {
+        async fn first(context: DurableOrchestrationContext) -> OrchestrationOutput {
+            let now = context.current_time();
+            let (_, index, _) = context
+                .select_all(vec![
+                    context.create_timer(now + Duration::hours(2)),
+                    context.create_timer(now + Duration::hours(1)),
+                ])
+                .await;
+            (index as u32).into()
+        }
+
+        let mut tester = OrchestrationTester::new("first", Value::Null);
+
+        tester.run(first);
+        tester.fire_timer();
+
+        let result = tester.run(first);
+        assert!(result.is_done);
+        assert_eq!(result.output, Some(json!(1)));
+        match result.actions[0].as_slice() {
+            [Action::CreateTimer { canceled: true, .. }, Action::CreateTimer {
+                canceled: false, ..
+            }] => {}
+            actions => panic!("unexpected actions: {:?}", actions),
+        }
+    }
+
+    #[test]
+    fn it_calls_http() {
+        async fn deploy(context: DurableOrchestrationContext) -> OrchestrationOutput {
+            let request = DurableHttpRequest::new("PUT", "https://management.azure.com/resource")
+                .json(json!({ "location": "westus" }))
+                .token_source(crate::durable::TokenSource::ManagedIdentity {
+                    resource: "https://management.core.windows.net".to_string(),
+                });
+
+            match context.call_http(request).await {
+                Ok(response) => response.status_code.into(),
+                Err(e) => e.to_string().into(),
+            }
+        }
+
+        let mut tester = OrchestrationTester::new("deploy", Value::Null);
+
+        let result = tester.run(deploy);
+        match &result.actions[0][0] {
+            Action::CallHttp { http_request } => {
+                assert_eq!(http_request.method, "PUT");
+                assert!(http_request.asynchronous_pattern_enabled);
+            }
+            action => panic!("unexpected action: {:?}", action),
+        }
+
+        tester.complete_http(&DurableHttpResponse {
+            status_code: 201,
+            headers: HashMap::new(),
+            content: None,
+        });
+
+        let result = tester.run(deploy);
+        assert_eq!(result.output, Some(json!(201)));
+    }
+
+    fn fan_out_orchestration(
+        mode: FanOutMode,
+    ) -> impl Fn(
+        DurableOrchestrationContext,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = OrchestrationOutput>>> {
+        move |context| {
+            Box::pin(async move {
+                let ctx = &context;
+                let calls = vec!["a", "b", "c"]
+                    .into_iter()
+                    .map(|name| move || ctx.call_activity(name, Value::Null));
+
+                match context.fan_out(calls, 2, mode).await {
+                    Ok(results) => results.into_iter().collect(),
+                    Err(failures) => json!(failures).into(),
+                }
+            })
+        }
+    }
+
+    fn scheduled(result: &crate::durable::ExecutionResult) -> Vec<String> {
+        result
+            .actions
+            .iter()
+            .flatten()
+            .map(|a| match a {
+                Action::CallActivity { function_name, .. } => function_name.clone(),
+                _ => panic!("unexpected action"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn it_fans_out_with_bounded_parallelism() {
+        let orchestration = fan_out_orchestration(FanOutMode::FailFast);
+        let mut tester = OrchestrationTester::new("fan_out", Value::Null);
+
+        let result = tester.run(&orchestration);
+        assert_eq!(scheduled(&result), vec!["a", "b"]);
+
+        tester.complete_activity("b", "B");
+
+        let result = tester.run(&orchestration);
+        assert_eq!(scheduled(&result), vec!["a", "b", "c"]);
+
+        tester.complete_activity("c", "C");
+        tester.complete_activity("a", "A");
+
+        let result = tester.run(&orchestration);
+        assert_eq!(result.output, Some(json!(["A", "B", "C"])));
+    }
+
+    #[test]
+    fn it_fails_fast_when_fanning_out() {
+        let orchestration = fan_out_orchestration(FanOutMode::FailFast);
+        let mut tester = OrchestrationTester::new("fan_out", Value::Null);
+
+        tester.run(&orchestration);
+        tester.fail_activity("a", "boom");
+
+        let result = tester.run(&orchestration);
+        assert_eq!(scheduled(&result), vec!["a", "b"]);
+        assert_eq!(result.output, Some(json!([[0, "boom"]])));
+    }
+
+    #[test]
+    fn it_collects_failures_when_fanning_out() {
+        let orchestration = fan_out_orchestration(FanOutMode::CollectFailures);
+        let mut tester = OrchestrationTester::new("fan_out", Value::Null);
+
+        tester.run(&orchestration);
+        tester.fail_activity("b", "b failed");
+
+        tester.run(&orchestration);
+        tester.complete_activity("a", "A");
+        tester.fail_activity("c", "c failed");
+
+        let result = tester.run(&orchestration);
+        assert_eq!(
+            result.output,
+            Some(json!([[1, "b failed"], [2, "c failed"]]))
+        );
+    }
+
+    #[test]
+    fn it_does_not_cancel_outstanding_timers_when_fanning_out() {
+        async fn approvals(context: DurableOrchestrationContext) -> OrchestrationOutput {
+            let ctx = &context;
+            let calls = vec!["a", "b"].into_iter().map(|name| {
+                move || {
+                    ctx.wait_for_event_with_timeout::<Value>(
+                        name,
+                        std::time::Duration::from_secs(3600),
+                    )
+                }
+            });
+
+            match context.fan_out(calls, 2, FanOutMode::FailFast).await {
+                Ok(outcomes) => outcomes
+                    .into_iter()
+                    .map(|o| match o {
+                        EventOutcome::Received(v) => v,
+                        EventOutcome::TimedOut => json!("timed out"),
+                    })
+                    .collect(),
+                Err(failures) => failures[0].1.to_string().into(),
+            }
+        }
+
+        let mut tester = OrchestrationTester::new("approvals", Value::Null);
+
+        tester.run(approvals);
+        tester.raise_event("a", "approved");
+
+        let result = tester.run(approvals);
+        assert!(!result.is_done);
+
+        tester.fire_timer();
+
+        let result = tester.run(approvals);
+        assert!(result.is_done);
+        assert_eq!(result.output, Some(json!(["approved", "timed out"])));
+    }
+
+    async fn reserve(context: DurableOrchestrationContext) -> OrchestrationOutput {
+        let options = RetryOptions::builder(std::time::Duration::from_secs(30), 3)
+            .handle(|e| e.to_string() == "busy")
+            .build()
+            .unwrap();
+
+        let ctx = &context;
+        match context
+            .call_with_retry(&options, || {
+                ctx.call_typed_activity::<_, String>("reserve", "seat")
+            })
+            .await
+        {
+            Ok(reservation) => reservation.into(),
+            Err(e) => format!("failed: {}", e).into(),
+        }
+    }
+
+    #[test]
+    fn it_retries_handled_failures_with_a_timer() {
+        let mut tester = OrchestrationTester::new("reserve", Value::Null);
+        let start = tester.current_time();
+
+        tester.run(reserve);
+        tester.fail_activity("reserve", "busy");
+
+        let result = tester.run(reserve);
+        assert!(!result.is_done);
+        assert_eq!(
+            result.actions.last().unwrap(),
+            &vec![Action::CreateTimer {
+                fire_at: start + Duration::seconds(30),
+                canceled: false,
+            }]
+        );
+
+        tester.fire_timer();
+        tester.run(reserve);
+        tester.complete_activity("reserve", "A1");
+
+        let result = tester.run(reserve);
+        assert!(result.is_done);
+        assert_eq!(result.output, Some(json!("A1")));
+    }
+
+    #[test]
+    fn it_does_not_retry_unhandled_failures() {
+        let mut tester = OrchestrationTester::new("reserve", Value::Null);
+
+        tester.run(reserve);
+        tester.fail_activity("reserve", "sold out");
+
+        let result = tester.run(reserve);
+        assert!(result.is_done);
+        assert_eq!(result.output, Some(json!("failed: sold out")));
+    }
 }
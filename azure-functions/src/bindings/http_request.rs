@@ -1,8 +1,33 @@
 use crate::{
-    http::Body,
+    bindings::HttpResponse,
+    http::{
+        multipart, Body, ClaimsIdentity, ClaimsPrincipal, Cookie, ExtractError, MultipartError,
+        MultipartLimits, Part, Status,
+    },
     rpc::{typed_data::Data, RpcHttp, TypedData},
 };
-use std::collections::HashMap;
+use bytes::Bytes;
+use serde::de::DeserializeOwned;
+use std::{collections::HashMap, convert::TryFrom, env, fmt::Display, str::FromStr};
+
+const FORM_CONTENT_TYPE: &str = "application/x-www-form-urlencoded";
+const CLIENT_PRINCIPAL_HEADER: &str = "x-ms-client-principal";
+const AUTH_ENABLED_VARIABLE: &str = "WEBSITE_AUTH_ENABLED";
+
+fn is_app_service_auth_enabled() -> bool {
+    env::var(AUTH_ENABLED_VARIABLE)
+        .map(|v| v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+fn deserialize_params<T>(params: &HashMap<String, String>) -> Result<T, String>
+where
+    T: DeserializeOwned,
+{
+    serde_urlencoded::to_string(params)
+        .map_err(|e| e.to_string())
+        .and_then(|s| serde_urlencoded::from_str(&s).map_err(|e| e.to_string()))
+}
 
 /// Represents a HTTP trigger binding.
 ///
@@ -90,6 +115,10 @@ impl HttpRequest {
         &self.0.params
     }
 
+    pub(crate) fn route_params_mut(&mut self) -> &mut HashMap<String, String> {
+        &mut self.0.params
+    }
+
     /// Gets the query parameters of the request.
     ///
     /// The query parameter keys are case-sensative.
@@ -112,6 +141,302 @@ impl HttpRequest {
         &self.0.query
     }
 
+    /// Deserializes the query parameters of the request.
+    ///
+    /// Numbers and booleans are parsed from the parameter values.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::func;
+    /// use azure_functions::bindings::{HttpRequest, HttpResponse};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Greeting {
+    ///     name: String,
+    ///     times: Option<u32>,
+    /// }
+    ///
+    /// #[func]
+    /// pub fn greet(request: HttpRequest) -> HttpResponse {
+    ///     match request.query::<Greeting>() {
+    ///         Ok(g) => format!("Hello, {}!", g.name).repeat(g.times.unwrap_or(1) as usize).into(),
+    ///         Err(e) => e.into(),
+    ///     }
+    /// }
+    /// ```
+    pub fn query<T>(&self) -> Result<T, ExtractError>
+    where
+        T: DeserializeOwned,
+    {
+        deserialize_params(&self.0.query).map_err(ExtractError::Query)
+    }
+
+    /// Deserializes the route parameters of the request.
+    ///
+    /// Parameters with route constraints such as `{id:int}` or `{enabled:bool}` can be
+    /// deserialized into the corresponding Rust types.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::func;
+    /// use azure_functions::bindings::{HttpRequest, HttpResponse};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct User {
+    ///     id: i64,
+    /// }
+    ///
+    /// #[func]
+    /// #[binding(name = "request", route = "users/{id:int}")]
+    /// pub fn users(request: HttpRequest) -> HttpResponse {
+    ///     match request.route::<User>() {
+    ///         Ok(user) => format!("User ID requested: {}", user.id).into(),
+    ///         Err(e) => e.into(),
+    ///     }
+    /// }
+    /// ```
+    pub fn route<T>(&self) -> Result<T, ExtractError>
+    where
+        T: DeserializeOwned,
+    {
+        deserialize_params(&self.0.params).map_err(ExtractError::Route)
+    }
+
+    /// Deserializes a `application/x-www-form-urlencoded` body of the request.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::func;
+    /// use azure_functions::bindings::{HttpRequest, HttpResponse};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Login {
+    ///     username: String,
+    ///     remember: bool,
+    /// }
+    ///
+    /// #[func]
+    /// pub fn login(request: HttpRequest) -> HttpResponse {
+    ///     match request.form::<Login>() {
+    ///         Ok(login) => format!("Welcome back, {}!", login.username).into(),
+    ///         Err(e) => e.into(),
+    ///     }
+    /// }
+    /// ```
+    pub fn form<T>(&self) -> Result<T, ExtractError>
+    where
+        T: DeserializeOwned,
+    {
+        let is_form = self.0.headers.get("content-type").map_or(false, |t| {
+            t.split(';')
+                .next()
+                .map_or(false, |t| t.trim().eq_ignore_ascii_case(FORM_CONTENT_TYPE))
+        });
+
+        if !is_form {
+            return Err(ExtractError::NotForm);
+        }
+
+        serde_urlencoded::from_bytes(self.body().as_bytes())
+            .map_err(|e| ExtractError::Form(e.to_string()))
+    }
+
+    /// Parses the value of a header of the request.
+    ///
+    /// The header name is case-insensitive.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::func;
+    /// use azure_functions::bindings::{HttpRequest, HttpResponse};
+    ///
+    /// #[func]
+    /// pub fn items(request: HttpRequest) -> HttpResponse {
+    ///     match request.header::<u32>("X-Page-Size") {
+    ///         Ok(size) => format!("Returning {} items", size).into(),
+    ///         Err(e) => e.into(),
+    ///     }
+    /// }
+    /// ```
+    pub fn header<T>(&self, name: &str) -> Result<T, ExtractError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let value = self
+            .0
+            .headers
+            .get(&name.to_lowercase())
+            .ok_or_else(|| ExtractError::MissingHeader(name.to_owned()))?;
+
+        value
+            .trim()
+            .parse()
+            .map_err(|e: T::Err| ExtractError::Header {
+                name: name.to_owned(),
+                message: e.to_string(),
+            })
+    }
+
+    /// Gets the cookies of the request.
+    ///
+    /// The cookies are parsed from the `Cookie` header of the request.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::func;
+    /// use azure_functions::bindings::{HttpRequest, HttpResponse};
+    ///
+    /// #[func]
+    /// pub fn session(request: HttpRequest) -> HttpResponse {
+    ///     format!(
+    ///         "The session identifier is: {}",
+    ///         request
+    ///             .cookies()
+    ///             .into_iter()
+    ///             .find(|c| c.name == "session")
+    ///             .map_or("undefined".to_string(), |c| c.value)
+    ///     ).into()
+    /// }
+    /// ```
+    pub fn cookies(&self) -> Vec<Cookie> {
+        self.0
+            .headers
+            .get("cookie")
+            .map(|h| Cookie::parse_header(h))
+            .unwrap_or_default()
+    }
+
+    /// Gets the principal associated with the request.
+    ///
+    /// The principal's identities come from the Azure Functions host. If none of them are
+    /// authenticated and App Service Authentication is enabled for the function app (i.e. the
+    /// `WEBSITE_AUTH_ENABLED` application setting is `true`), the identity of the
+    /// `x-ms-client-principal` header is used instead.
+    ///
+    /// The header is ignored when App Service Authentication is not enabled, as App Service
+    /// only strips the header from client requests when authentication is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::func;
+    /// use azure_functions::bindings::{HttpRequest, HttpResponse};
+    ///
+    /// #[func]
+    /// pub fn greet(request: HttpRequest) -> HttpResponse {
+    ///     format!(
+    ///         "Hello, {}!",
+    ///         request.principal().name().unwrap_or("stranger")
+    ///     ).into()
+    /// }
+    /// ```
+    pub fn principal(&self) -> ClaimsPrincipal {
+        self.get_principal(is_app_service_auth_enabled())
+    }
+
+    #[doc(hidden)]
+    pub fn authorize(&self, roles: &[&str]) -> Option<HttpResponse> {
+        self.authorize_principal(&self.principal(), roles)
+    }
+
+    fn get_principal(&self, trust_client_principal: bool) -> ClaimsPrincipal {
+        let mut identities: Vec<ClaimsIdentity> =
+            self.0.identities.iter().map(ClaimsIdentity::from).collect();
+
+        if trust_client_principal && !identities.iter().any(ClaimsIdentity::is_authenticated) {
+            if let Some(identity) = self
+                .0
+                .headers
+                .get(CLIENT_PRINCIPAL_HEADER)
+                .and_then(|h| ClaimsIdentity::from_client_principal(h))
+            {
+                identities.push(identity);
+            }
+        }
+
+        ClaimsPrincipal::new(identities)
+    }
+
+    fn authorize_principal(
+        &self,
+        principal: &ClaimsPrincipal,
+        roles: &[&str],
+    ) -> Option<HttpResponse> {
+        if !principal.is_authenticated() {
+            return Some(HttpResponse::build().status(Status::Unauthorized).finish());
+        }
+
+        if !roles.iter().any(|r| principal.is_in_role(r)) {
+            return Some(HttpResponse::build().status(Status::Forbidden).finish());
+        }
+
+        None
+    }
+
+    /// Parses the `multipart/form-data` body of the request with the default limits.
+    ///
+    /// The boundary is read from the `Content-Type` header and the parts are parsed from the
+    /// raw bytes of the body, so uploaded binary files are not altered.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::func;
+    /// use azure_functions::bindings::{HttpRequest, HttpResponse};
+    ///
+    /// #[func]
+    /// pub fn upload(request: HttpRequest) -> HttpResponse {
+    ///     let parts = match request.multipart() {
+    ///         Ok(parts) => parts,
+    ///         Err(e) => return e.into(),
+    ///     };
+    ///
+    ///     parts
+    ///         .iter()
+    ///         .filter(|p| p.is_file())
+    ///         .map(|p| format!("{}: {} bytes\n", p.filename.as_ref().unwrap(), p.content.len()))
+    ///         .collect::<String>()
+    ///         .into()
+    /// }
+    /// ```
+    pub fn multipart(&self) -> Result<Vec<Part>, MultipartError> {
+        self.multipart_with_limits(&MultipartLimits::default())
+    }
+
+    /// Parses the `multipart/form-data` body of the request with the given limits.
+    pub fn multipart_with_limits(
+        &self,
+        limits: &MultipartLimits,
+    ) -> Result<Vec<Part>, MultipartError> {
+        let boundary = multipart::get_boundary(
+            self.0
+                .headers
+                .get("content-type")
+                .ok_or(MultipartError::NotMultipart)?,
+        )?;
+
+        self.raw_body().multipart(boundary, limits)
+    }
+
+    /// Gets the raw body of the request, falling back to the body for hosts that don't send it.
+    fn raw_body(&self) -> Body {
+        self.0
+            .raw_body
+            .as_ref()
+            .or(self.0.body.as_ref())
+            .map(|b| Body::from(&**b))
+            .unwrap_or(Body::Empty)
+    }
+
     /// Gets the body of the request.
     pub fn body(&self) -> Body {
         self.0
@@ -122,10 +447,131 @@ impl HttpRequest {
     }
 }
 
+/// Converts a HTTP request into a request of the `http` crate.
+///
+/// This allows handlers written against the `http` crate types to be used from a function.
+///
+/// # Examples
+///
+/// ```rust
+/// use azure_functions::bindings::{HttpRequest, HttpResponse};
+/// use azure_functions::func;
+/// use bytes::Bytes;
+/// use std::convert::TryFrom;
+///
+/// fn handler(request: http::Request<Bytes>) -> http::Response<String> {
+///     http::Response::new(format!("Hello from {}!", request.uri().path()))
+/// }
+///
+/// #[func]
+/// pub fn hello(request: HttpRequest) -> HttpResponse {
+///     match http::Request::try_from(request) {
+///         Ok(request) => handler(request).into(),
+///         Err(e) => HttpResponse::build()
+///             .status(azure_functions::http::Status::BadRequest)
+///             .body(e.to_string())
+///             .finish(),
+///     }
+/// }
+/// ```
+impl TryFrom<HttpRequest> for ::http::Request<Bytes> {
+    type Error = ::http::Error;
+
+    fn try_from(request: HttpRequest) -> Result<Self, Self::Error> {
+        let mut builder = ::http::Request::builder();
+
+        builder.method(request.method()).uri(request.url());
+
+        for (name, value) in request.headers() {
+            builder.header(name.as_str(), value.as_str());
+        }
+
+        builder.body(Bytes::from(request.raw_body().as_bytes()))
+    }
+}
+
+/// Converts a request of the `http` crate into a HTTP request.
+///
+/// This is useful for testing functions with requests created by standard tooling.
+///
+/// # Examples
+///
+/// ```rust
+/// use azure_functions::bindings::HttpRequest;
+///
+/// let request: HttpRequest = http::Request::post("https://example.com/api/greet?name=Peter")
+///     .header("Content-Type", "text/plain")
+///     .body("Hello!")
+///     .unwrap()
+///     .into();
+///
+/// assert_eq!(request.method(), "POST");
+/// assert_eq!(request.query_params().get("name").unwrap(), "Peter");
+/// assert_eq!(request.headers().get("content-type").unwrap(), "text/plain");
+/// assert_eq!(request.body().as_str().unwrap(), "Hello!");
+/// ```
+impl<B> From<::http::Request<B>> for HttpRequest
+where
+    B: Into<Bytes>,
+{
+    fn from(request: ::http::Request<B>) -> Self {
+        let (parts, body) = request.into_parts();
+
+        let mut headers: HashMap<String, String> = HashMap::new();
+        for (name, value) in parts.headers.iter() {
+            let value = String::from_utf8_lossy(value.as_bytes());
+            headers
+                .entry(name.as_str().to_owned())
+                .and_modify(|v| {
+                    v.push_str(", ");
+                    v.push_str(&value);
+                })
+                .or_insert_with(|| value.into_owned());
+        }
+
+        let query = parts
+            .uri
+            .query()
+            .and_then(|q| serde_urlencoded::from_str(q).ok())
+            .unwrap_or_default();
+
+        let body: Bytes = body.into();
+        let (body, raw_body) = if body.is_empty() {
+            (None, None)
+        } else {
+            let data = match std::str::from_utf8(&body) {
+                Ok(s) => Data::String(s.to_owned()),
+                Err(_) => Data::Bytes(body.to_vec()),
+            };
+            (
+                Some(Box::new(TypedData { data: Some(data) })),
+                Some(Box::new(TypedData {
+                    data: Some(Data::Bytes(body.to_vec())),
+                })),
+            )
+        };
+
+        HttpRequest(RpcHttp {
+            method: parts.method.as_str().to_owned(),
+            url: parts.uri.to_string(),
+            headers,
+            query,
+            body,
+            raw_body,
+            ..Default::default()
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        http::ROLE_CLAIM_TYPE,
+        rpc::{nullable_string, NullableString, RpcClaim, RpcClaimsIdentity},
+    };
     use matches::matches;
+    use serde::Deserialize;
     use std::borrow::Cow;
 
     #[test]
@@ -206,6 +652,314 @@ mod tests {
         assert_eq!(request.query_params().get(KEY).unwrap(), VALUE);
     }
 
+    #[test]
+    fn it_has_cookies() {
+        let mut http = RpcHttp::default();
+        http.headers
+            .insert("cookie".to_string(), "session=1234; theme=dark".to_string());
+
+        let data = TypedData {
+            data: Some(Data::Http(Box::new(http))),
+        };
+
+        let request = HttpRequest::new(data, HashMap::new());
+        assert_eq!(
+            request.cookies(),
+            vec![Cookie::new("session", "1234"), Cookie::new("theme", "dark")]
+        );
+    }
+
+    #[test]
+    fn it_has_a_principal() {
+        let mut http = RpcHttp::default();
+        http.identities.push(RpcClaimsIdentity {
+            authentication_type: Some(NullableString {
+                string: Some(nullable_string::String::Value("aad".to_string())),
+            }),
+            claims: vec![RpcClaim {
+                r#type: ROLE_CLAIM_TYPE.to_string(),
+                value: "admin".to_string(),
+            }],
+            ..Default::default()
+        });
+
+        let data = TypedData {
+            data: Some(Data::Http(Box::new(http))),
+        };
+
+        let request = HttpRequest::new(data, HashMap::new());
+        assert!(request.principal().is_authenticated());
+        assert!(request.principal().is_in_role("admin"));
+        assert!(request.authorize(&["ops", "admin"]).is_none());
+        assert_eq!(
+            request.authorize(&["ops"]).unwrap().status(),
+            Status::Forbidden
+        );
+    }
+
+    #[test]
+    fn it_falls_back_to_the_client_principal_header() {
+        let mut http = RpcHttp::default();
+        http.headers.insert(
+            "x-ms-client-principal".to_string(),
+            base64::encode(
+                r#"{"auth_typ":"aad","claims":[{"typ":"roles","val":"ops"}],"role_typ":"roles"}"#,
+            ),
+        );
+
+        let data = TypedData {
+            data: Some(Data::Http(Box::new(http))),
+        };
+
+        let request = HttpRequest::new(data, HashMap::new());
+        let principal = request.get_principal(true);
+        assert!(principal.is_in_role("ops"));
+        assert!(request.authorize_principal(&principal, &["ops"]).is_none());
+    }
+
+    #[test]
+    fn it_rejects_a_forged_client_principal_header() {
+        let mut http = RpcHttp::default();
+        http.headers.insert(
+            "x-ms-client-principal".to_string(),
+            base64::encode(
+                r#"{"auth_typ":"aad","claims":[{"typ":"roles","val":"admin"}],"role_typ":"roles"}"#,
+            ),
+        );
+
+        let data = TypedData {
+            data: Some(Data::Http(Box::new(http))),
+        };
+
+        let request = HttpRequest::new(data, HashMap::new());
+        let principal = request.get_principal(false);
+        assert!(!principal.is_authenticated());
+        assert!(!principal.is_in_role("admin"));
+        assert_eq!(
+            request
+                .authorize_principal(&principal, &["admin"])
+                .unwrap()
+                .status(),
+            Status::Unauthorized
+        );
+    }
+
+    #[test]
+    fn it_is_unauthorized_without_a_principal() {
+        let data = TypedData {
+            data: Some(Data::Http(Box::new(RpcHttp::default()))),
+        };
+
+        let request = HttpRequest::new(data, HashMap::new());
+        assert!(!request.principal().is_authenticated());
+        assert_eq!(
+            request.authorize(&["admin"]).unwrap().status(),
+            Status::Unauthorized
+        );
+    }
+
+    #[test]
+    fn it_deserializes_query_and_route_parameters() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Params {
+            id: i64,
+            name: String,
+            enabled: Option<bool>,
+        }
+
+        let mut http = RpcHttp::default();
+        http.query.insert("id".to_string(), "12".to_string());
+        http.query.insert("name".to_string(), "a b&c".to_string());
+        http.params.insert("id".to_string(), "abc".to_string());
+        http.params.insert("name".to_string(), "d".to_string());
+
+        let data = TypedData {
+            data: Some(Data::Http(Box::new(http))),
+        };
+
+        let request = HttpRequest::new(data, HashMap::new());
+        assert_eq!(
+            request.query::<Params>().unwrap(),
+            Params {
+                id: 12,
+                name: "a b&c".to_string(),
+                enabled: None
+            }
+        );
+        assert_eq!(
+            request.route::<Params>().unwrap_err().to_string(),
+            "invalid route parameters: invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn it_deserializes_a_form_body() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Login {
+            username: String,
+            remember: bool,
+        }
+
+        let mut http = RpcHttp::default();
+        http.headers.insert(
+            "content-type".to_string(),
+            "application/x-www-form-urlencoded; charset=utf-8".to_string(),
+        );
+        http.body = Some(Box::new(TypedData {
+            data: Some(Data::String("username=peter%20k&remember=true".to_string())),
+        }));
+
+        let data = TypedData {
+            data: Some(Data::Http(Box::new(http))),
+        };
+
+        let request = HttpRequest::new(data, HashMap::new());
+        assert_eq!(
+            request.form::<Login>().unwrap(),
+            Login {
+                username: "peter k".to_string(),
+                remember: true
+            }
+        );
+    }
+
+    #[test]
+    fn it_requires_a_form_content_type() {
+        let data = TypedData {
+            data: Some(Data::Http(Box::new(RpcHttp::default()))),
+        };
+
+        let request = HttpRequest::new(data, HashMap::new());
+        assert_eq!(
+            request.form::<HashMap<String, String>>().unwrap_err(),
+            ExtractError::NotForm
+        );
+    }
+
+    #[test]
+    fn it_parses_headers() {
+        let mut http = RpcHttp::default();
+        http.headers
+            .insert("x-page-size".to_string(), "25".to_string());
+        http.headers
+            .insert("x-page".to_string(), "first".to_string());
+
+        let data = TypedData {
+            data: Some(Data::Http(Box::new(http))),
+        };
+
+        let request = HttpRequest::new(data, HashMap::new());
+        assert_eq!(request.header::<u32>("X-Page-Size").unwrap(), 25);
+        assert_eq!(
+            request.header::<u32>("X-Page").unwrap_err().to_string(),
+            "invalid value for header 'X-Page': invalid digit found in string"
+        );
+        assert_eq!(
+            request.header::<u32>("X-Missing").unwrap_err(),
+            ExtractError::MissingHeader("X-Missing".to_string())
+        );
+    }
+
+    #[test]
+    fn it_parses_a_multipart_body_from_the_raw_body() {
+        let mut http = RpcHttp::default();
+        http.headers.insert(
+            "content-type".to_string(),
+            "multipart/form-data; boundary=XyZ".to_string(),
+        );
+        http.body = Some(Box::new(TypedData {
+            data: Some(Data::String("corrupted".to_string())),
+        }));
+        http.raw_body = Some(Box::new(TypedData {
+            data: Some(Data::Bytes(
+                b"--XyZ\r\nContent-Disposition: form-data; name=\"f\"; filename=\"f.bin\"\r\n\r\n\xff\x00\r\n--XyZ--"
+                    .to_vec(),
+            )),
+        }));
+
+        let data = TypedData {
+            data: Some(Data::Http(Box::new(http))),
+        };
+
+        let request = HttpRequest::new(data, HashMap::new());
+        let parts = request.multipart().unwrap();
+        assert_eq!(parts.len(), 1);
+        assert_eq!(parts[0].filename.as_ref().unwrap(), "f.bin");
+        assert_eq!(parts[0].content, b"\xff\x00");
+    }
+
+    #[test]
+    fn it_requires_a_multipart_content_type() {
+        let data = TypedData {
+            data: Some(Data::Http(Box::new(RpcHttp::default()))),
+        };
+
+        let request = HttpRequest::new(data, HashMap::new());
+        assert_eq!(
+            request.multipart().unwrap_err(),
+            MultipartError::NotMultipart
+        );
+    }
+
+    #[test]
+    fn it_converts_to_a_http_request() {
+        let mut http = RpcHttp::default();
+        http.method = "PUT".to_string();
+        http.url = "https://example.com/api/items/1".to_string();
+        http.headers
+            .insert("content-type".to_string(), "application/json".to_string());
+        http.body = Some(Box::new(TypedData {
+            data: Some(Data::Json(r#"{"id":1}"#.to_string())),
+        }));
+
+        let request = ::http::Request::try_from(HttpRequest(http)).unwrap();
+
+        assert_eq!(request.method(), ::http::Method::PUT);
+        assert_eq!(request.uri().path(), "/api/items/1");
+        assert_eq!(request.headers()["content-type"], "application/json");
+        assert_eq!(request.body(), &Bytes::from(&br#"{"id":1}"#[..]));
+    }
+
+    #[test]
+    fn it_rejects_an_invalid_method() {
+        let mut http = RpcHttp::default();
+        http.method = "NOT A METHOD".to_string();
+        http.url = "https://example.com".to_string();
+
+        assert!(::http::Request::try_from(HttpRequest(http)).is_err());
+    }
+
+    #[test]
+    fn it_converts_from_a_http_request() {
+        let request: HttpRequest = ::http::Request::get("https://example.com/api?a=1&b=two")
+            .header("Accept", "text/plain")
+            .header("Accept", "text/html")
+            .body(&[0xff, 0xfe][..])
+            .unwrap()
+            .into();
+
+        assert_eq!(request.method(), "GET");
+        assert_eq!(request.url(), "https://example.com/api?a=1&b=two");
+        assert_eq!(request.query_params().get("a").unwrap(), "1");
+        assert_eq!(request.query_params().get("b").unwrap(), "two");
+        assert_eq!(
+            request.headers().get("accept").unwrap(),
+            "text/plain, text/html"
+        );
+        assert!(matches!(
+            request.body(),
+            Body::Bytes(Cow::Borrowed(&[0xff, 0xfe]))
+        ));
+    }
+
+    #[test]
+    fn it_converts_from_a_http_request_without_a_body() {
+        let request: HttpRequest = ::http::Request::get("/").body("").unwrap().into();
+
+        assert!(request.query_params().is_empty());
+        assert!(matches!(request.body(), Body::Empty));
+    }
+
     #[test]
     fn it_has_an_empty_body() {
         let data = TypedData {
@@ -1,8 +1,11 @@
 use crate::{
-    http::{Body, ResponseBuilder, Status},
+    http::{Body, Cookie, ResponseBuilder, Status},
     rpc::{typed_data::Data, RpcHttp, TypedData},
 };
-use std::collections::HashMap;
+use bytes::Bytes;
+use std::{collections::HashMap, convert::TryFrom};
+
+const SET_COOKIE: &str = "set-cookie";
 
 /// Represents a HTTP output binding.
 ///
@@ -144,6 +147,50 @@ impl HttpResponse {
     pub fn headers(&self) -> &HashMap<String, String> {
         &self.data.headers
     }
+
+    /// Sets a header of the response, replacing any existing value.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use azure_functions::bindings::HttpResponse;
+    /// let mut response: HttpResponse = "example".into();
+    /// response.set_header("Cache-Control", "no-cache");
+    ///
+    /// assert_eq!(response.headers().get("Cache-Control").unwrap(), "no-cache");
+    /// ```
+    pub fn set_header<T: Into<String>, U: Into<String>>(&mut self, name: T, value: U) {
+        self.data.headers.insert(name.into(), value.into());
+    }
+
+    /// Gets the cookies of the response.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use azure_functions::bindings::HttpResponse;
+    /// use azure_functions::http::Cookie;
+    ///
+    /// let response = HttpResponse::build().cookie(Cookie::new("session", "1234")).finish();
+    ///
+    /// assert_eq!(response.cookies(), vec![Cookie::new("session", "1234")]);
+    /// ```
+    pub fn cookies(&self) -> Vec<Cookie> {
+        self.data.cookies.iter().map(Cookie::from).collect()
+    }
+}
+
+/// Adds a value to the `Vary` header unless it is already present.
+pub(crate) fn append_vary(headers: &mut HashMap<String, String>, value: &str) {
+    headers
+        .entry("Vary".to_string())
+        .and_modify(|v| {
+            if !v.split(',').any(|v| v.trim().eq_ignore_ascii_case(value)) {
+                v.push_str(", ");
+                v.push_str(value);
+            }
+        })
+        .or_insert_with(|| value.to_string());
 }
 
 impl<'a, T> From<T> for HttpResponse
@@ -155,6 +202,117 @@ where
     }
 }
 
+/// Converts a response of the `http` crate into a HTTP response.
+///
+/// `Set-Cookie` headers are converted into the cookies of the response.
+///
+/// # Examples
+///
+/// ```rust
+/// use azure_functions::bindings::{HttpRequest, HttpResponse};
+/// use azure_functions::func;
+///
+/// #[func]
+/// pub fn created(_req: HttpRequest) -> HttpResponse {
+///     http::Response::builder()
+///         .status(201)
+///         .header("Location", "/api/items/1")
+///         .body("Created")
+///         .unwrap()
+///         .into()
+/// }
+/// ```
+impl<B> From<::http::Response<B>> for HttpResponse
+where
+    B: Into<Bytes>,
+{
+    fn from(response: ::http::Response<B>) -> Self {
+        let (parts, body) = response.into_parts();
+
+        let mut response = HttpResponse::build().status(parts.status.as_u16()).finish();
+
+        for (name, value) in parts.headers.iter() {
+            let value = String::from_utf8_lossy(value.as_bytes());
+
+            if name.as_str() == SET_COOKIE {
+                if let Some(cookie) = Cookie::parse_set_cookie(&value) {
+                    response.data.cookies.push(cookie.into());
+                }
+                continue;
+            }
+
+            response
+                .data
+                .headers
+                .entry(name.as_str().to_owned())
+                .and_modify(|v| {
+                    v.push_str(", ");
+                    v.push_str(&value);
+                })
+                .or_insert_with(|| value.into_owned());
+        }
+
+        let body: Bytes = body.into();
+        if !body.is_empty() {
+            let body: Body = match std::str::from_utf8(&body) {
+                Ok(s) => s.into(),
+                Err(_) => body.as_ref().into(),
+            };
+
+            if !response.data.headers.contains_key("content-type") {
+                response.data.headers.insert(
+                    "Content-Type".to_string(),
+                    body.default_content_type().to_string(),
+                );
+            }
+
+            response.data.body = Some(Box::new(body.into()));
+        }
+
+        response
+    }
+}
+
+/// Converts a HTTP response into a response of the `http` crate.
+///
+/// # Examples
+///
+/// ```rust
+/// use azure_functions::bindings::HttpResponse;
+/// use azure_functions::http::Cookie;
+/// use std::convert::TryFrom;
+///
+/// let response = HttpResponse::build()
+///     .cookie(Cookie::new("session", "1234").http_only(true))
+///     .body("Hello!")
+///     .finish();
+///
+/// let response = http::Response::try_from(response).unwrap();
+///
+/// assert_eq!(response.status(), http::StatusCode::OK);
+/// assert_eq!(response.headers()["set-cookie"], "session=1234; HttpOnly");
+/// assert_eq!(response.body(), "Hello!");
+/// ```
+impl TryFrom<HttpResponse> for ::http::Response<Bytes> {
+    type Error = ::http::Error;
+
+    fn try_from(response: HttpResponse) -> Result<Self, Self::Error> {
+        let mut builder = ::http::Response::builder();
+
+        builder.status(response.status.to_string().as_str());
+
+        for (name, value) in response.headers() {
+            builder.header(name.as_str(), value.as_str());
+        }
+
+        for cookie in response.cookies() {
+            builder.header(SET_COOKIE, cookie.to_string().as_str());
+        }
+
+        builder.body(Bytes::from(response.body().as_bytes()))
+    }
+}
+
 #[doc(hidden)]
 impl Into<TypedData> for HttpResponse {
     fn into(mut self) -> TypedData {
@@ -258,6 +416,63 @@ mod tests {
         assert_eq!(response.headers().get("header3").unwrap(), "value3");
     }
 
+    #[test]
+    fn it_converts_from_a_http_response() {
+        let response: HttpResponse = ::http::Response::builder()
+            .status(404)
+            .header("Vary", "Accept")
+            .header("Vary", "Origin")
+            .header("Set-Cookie", "a=1; Path=/")
+            .header("Set-Cookie", "b=2")
+            .body(vec![0xff, 0xfe])
+            .unwrap()
+            .into();
+
+        assert_eq!(response.status(), Status::NotFound);
+        assert_eq!(response.headers().get("vary").unwrap(), "Accept, Origin");
+        assert_eq!(response.headers().get("set-cookie"), None);
+        assert_eq!(
+            response.cookies(),
+            vec![Cookie::new("a", "1").path("/"), Cookie::new("b", "2")]
+        );
+        assert_eq!(
+            response.headers().get("Content-Type").unwrap(),
+            "application/octet-stream"
+        );
+        assert_eq!(response.body().as_bytes(), &[0xff, 0xfe]);
+    }
+
+    #[test]
+    fn it_converts_from_a_http_response_without_a_body() {
+        let response: HttpResponse = ::http::Response::builder()
+            .status(204)
+            .body("")
+            .unwrap()
+            .into();
+
+        assert_eq!(response.status(), Status::NoContent);
+        assert!(response.headers().is_empty());
+        assert!(matches!(response.body(), Body::Empty));
+    }
+
+    #[test]
+    fn it_converts_to_a_http_response() {
+        let response = HttpResponse::build()
+            .status(Status::Created)
+            .header("Location", "/items/1")
+            .cookie(Cookie::new("session", "1234"))
+            .body("created")
+            .finish();
+
+        let response = ::http::Response::try_from(response).unwrap();
+
+        assert_eq!(response.status(), ::http::StatusCode::CREATED);
+        assert_eq!(response.headers()["location"], "/items/1");
+        assert_eq!(response.headers()["content-type"], "text/plain");
+        assert_eq!(response.headers()["set-cookie"], "session=1234");
+        assert_eq!(response.body(), &Bytes::from("created"));
+    }
+
     #[test]
     fn it_converts_to_typed_data() {
         let response: HttpResponse = HttpResponse::build()
@@ -2,9 +2,22 @@ use crate::{
     http::Body,
     rpc::{typed_data::Data, TypedData},
 };
-use serde_json::{from_str, json, Map, Value};
+use chrono::{DateTime, Utc};
+use serde::{
+    de::{DeserializeOwned, Error},
+    ser, Serialize,
+};
+use serde_json::{from_str, from_value, json, to_value, Map, Value};
 use std::fmt;
 
+const PARTITION_KEY: &str = "PartitionKey";
+const ROW_KEY: &str = "RowKey";
+const TIMESTAMP: &str = "Timestamp";
+const ETAG: &str = "ETag";
+const ODATA_ETAG: &str = "odata.etag";
+const ODATA_PREFIX: &str = "odata.";
+const ODATA_TYPE_SUFFIX: &str = "@odata.type";
+
 /// Represents an Azure Storage table input or output binding.
 ///
 /// The following binding attributes are supported:
@@ -55,6 +68,124 @@ pub struct Table(Value);
 /// Represents the data of an Azure Storage table row.
 pub type Row = Map<String, Value>;
 
+/// Represents the EDM type of an Azure Storage table entity property.
+///
+/// Properties of these types must be annotated with their type when written to
+/// Table storage; other types are inferred from the JSON value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdmType {
+    /// An array of bytes, written as a base64-encoded string.
+    Binary,
+    /// A date and time, written as an ISO 8601 string.
+    DateTime,
+    /// A GUID, written as a string.
+    Guid,
+    /// A 64-bit integer, written as a string to preserve precision.
+    Int64,
+}
+
+impl EdmType {
+    /// Gets the name of the EDM type (e.g. `Edm.Int64`).
+    pub fn name(self) -> &'static str {
+        match self {
+            EdmType::Binary => "Edm.Binary",
+            EdmType::DateTime => "Edm.DateTime",
+            EdmType::Guid => "Edm.Guid",
+            EdmType::Int64 => "Edm.Int64",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "Edm.Binary" => Some(EdmType::Binary),
+            "Edm.DateTime" => Some(EdmType::DateTime),
+            "Edm.Guid" => Some(EdmType::Guid),
+            "Edm.Int64" => Some(EdmType::Int64),
+            _ => None,
+        }
+    }
+}
+
+/// Represents a type that can be written as an Azure Storage table entity.
+///
+/// This trait is typically implemented with `#[derive(TableEntity)]`.
+///
+/// The derive annotates fields of type `i64`, `isize`, `DateTime`, `Uuid`, and `Vec<u8>`
+/// (or an `Option` of those) with their EDM types. Other fields can be annotated with the
+/// `#[table(edm = "...")]` attribute, where the type is one of `Binary`, `DateTime`, `Guid`,
+/// or `Int64`. Property names follow the field's `serde` renames.
+///
+/// Unsigned integers are not annotated automatically as `Edm.Int64` is a signed type; annotate
+/// them explicitly only if their values fit in an `i64`.
+///
+/// # Examples
+///
+/// ```rust
+/// use azure_functions::bindings::{EdmType, TableEntity};
+/// use chrono::{DateTime, Utc};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize, TableEntity)]
+/// #[serde(rename_all = "PascalCase")]
+/// struct Order {
+///     total_cents: i64,
+///     placed: DateTime<Utc>,
+///     #[table(edm = "Guid")]
+///     customer_id: String,
+///     notes: String,
+/// }
+///
+/// assert_eq!(
+///     Order::edm_types(),
+///     &[
+///         ("TotalCents", EdmType::Int64),
+///         ("Placed", EdmType::DateTime),
+///         ("CustomerId", EdmType::Guid)
+///     ]
+/// );
+/// ```
+pub trait TableEntity {
+    /// Gets the names and EDM types of the entity's annotated properties.
+    fn edm_types() -> &'static [(&'static str, EdmType)];
+}
+
+/// Represents an Azure Storage table entity with typed system properties.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entity<T> {
+    /// The partition key of the entity.
+    pub partition_key: String,
+    /// The row key of the entity.
+    pub row_key: String,
+    /// The time the entity was last modified.
+    pub timestamp: Option<DateTime<Utc>>,
+    /// The entity tag of the entity.
+    pub etag: Option<String>,
+    /// The properties of the entity.
+    pub properties: T,
+}
+
+impl<T: DeserializeOwned> Entity<T> {
+    fn from_row(row: &Row) -> serde_json::Result<Self> {
+        let get_str = |name: &str| row.get(name).and_then(Value::as_str);
+
+        Ok(Entity {
+            partition_key: get_str(PARTITION_KEY)
+                .ok_or_else(|| serde_json::Error::missing_field(PARTITION_KEY))?
+                .to_string(),
+            row_key: get_str(ROW_KEY)
+                .ok_or_else(|| serde_json::Error::missing_field(ROW_KEY))?
+                .to_string(),
+            timestamp: get_str(TIMESTAMP)
+                .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                .map(|t| t.with_timezone(&Utc)),
+            etag: get_str(ETAG)
+                .or_else(|| get_str(ODATA_ETAG))
+                .map(ToOwned::to_owned),
+            properties: from_value(Value::Object(decode_properties(row)))?,
+        })
+    }
+}
+
 impl Table {
     /// Creates a new table binding.
     ///
@@ -99,6 +230,99 @@ impl Table {
         array.last_mut().unwrap().as_object_mut().unwrap()
     }
 
+    /// Deserializes the rows stored in the table binding as typed entities.
+    ///
+    /// Properties annotated as `Edm.Int64` are converted to JSON numbers and properties
+    /// annotated as `Edm.Binary` are converted to arrays of bytes before deserialization.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::bindings::{HttpRequest, Table};
+    /// use azure_functions::func;
+    /// use log::info;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// #[serde(rename_all = "PascalCase")]
+    /// struct Order {
+    ///     total_cents: i64,
+    /// }
+    ///
+    /// #[func]
+    /// #[binding(name = "table", table_name = "Orders", partition_key = "{partition}")]
+    /// pub fn log_orders(req: HttpRequest, table: Table) {
+    ///     for entity in table.entities::<Order>().expect("invalid order") {
+    ///         info!("Order {} totals {} cents.", entity.row_key, entity.properties.total_cents);
+    ///     }
+    /// }
+    /// ```
+    pub fn entities<T>(&self) -> serde_json::Result<Vec<Entity<T>>>
+    where
+        T: DeserializeOwned,
+    {
+        self.rows().map(Entity::from_row).collect()
+    }
+
+    /// Adds a typed entity to the table binding with the specified partition and row keys.
+    ///
+    /// The entity's properties are annotated with the EDM types of its `TableEntity` implementation.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::bindings::{HttpRequest, HttpResponse, Table, TableEntity};
+    /// use azure_functions::func;
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize, TableEntity)]
+    /// #[serde(rename_all = "PascalCase")]
+    /// struct Order {
+    ///     total_cents: i64,
+    /// }
+    ///
+    /// #[func]
+    /// #[binding(name = "output1", table_name = "Orders")]
+    /// pub fn create_order(req: HttpRequest) -> (HttpResponse, Table) {
+    ///     let mut table = Table::new();
+    ///
+    ///     table
+    ///         .add_entity("orders", "1", &Order { total_cents: 9_007_199_254_740_993 })
+    ///         .expect("failed to serialize order");
+    ///
+    ///     ("Order created.".into(), table)
+    /// }
+    /// ```
+    pub fn add_entity<T>(
+        &mut self,
+        partition_key: &str,
+        row_key: &str,
+        entity: &T,
+    ) -> serde_json::Result<&mut Row>
+    where
+        T: Serialize + TableEntity,
+    {
+        let mut row = match to_value(entity)? {
+            Value::Object(row) => row,
+            _ => {
+                return Err(<serde_json::Error as ser::Error>::custom(
+                    "table entities must serialize to a JSON object",
+                ))
+            }
+        };
+
+        encode_properties(&mut row, T::edm_types());
+
+        row.insert(PARTITION_KEY.to_string(), Value::from(partition_key));
+        row.insert(ROW_KEY.to_string(), Value::from(row_key));
+
+        let array = self.0.as_array_mut().unwrap();
+
+        array.push(Value::Object(row));
+
+        Ok(array.last_mut().unwrap().as_object_mut().unwrap())
+    }
+
     /// Adds a row as a value to the table.
     pub fn add_row_value(&mut self, value: Value) {
         let array = self.0.as_array_mut().unwrap();
@@ -112,6 +336,61 @@ impl Table {
     }
 }
 
+fn encode_properties(row: &mut Row, edm_types: &[(&str, EdmType)]) {
+    for (name, edm_type) in edm_types {
+        let value = match row.get_mut(*name) {
+            Some(value) if !value.is_null() => value,
+            _ => continue,
+        };
+
+        match (edm_type, &value) {
+            (EdmType::Int64, Value::Number(n)) => *value = Value::String(n.to_string()),
+            (EdmType::Binary, Value::Array(items)) => {
+                let bytes: Option<Vec<u8>> = items
+                    .iter()
+                    .map(|b| b.as_u64().filter(|b| *b <= 0xFF).map(|b| b as u8))
+                    .collect();
+
+                if let Some(bytes) = bytes {
+                    *value = Value::String(base64::encode(&bytes));
+                }
+            }
+            _ => {}
+        };
+
+        row.insert(
+            format!("{}{}", name, ODATA_TYPE_SUFFIX),
+            Value::from(edm_type.name()),
+        );
+    }
+}
+
+fn decode_properties(row: &Row) -> Row {
+    row.iter()
+        .filter(|(name, _)| !name.starts_with(ODATA_PREFIX) && !name.ends_with(ODATA_TYPE_SUFFIX))
+        .map(|(name, value)| {
+            let edm_type = row
+                .get(&format!("{}{}", name, ODATA_TYPE_SUFFIX))
+                .and_then(Value::as_str)
+                .and_then(EdmType::from_name);
+
+            let value = match (edm_type, value) {
+                (Some(EdmType::Int64), Value::String(s)) => s
+                    .parse::<i64>()
+                    .map(Value::from)
+                    .or_else(|_| s.parse::<u64>().map(Value::from))
+                    .unwrap_or_else(|_| value.clone()),
+                (Some(EdmType::Binary), Value::String(s)) => base64::decode(s)
+                    .map(|bytes| Value::Array(bytes.into_iter().map(Value::from).collect()))
+                    .unwrap_or_else(|_| value.clone()),
+                _ => value.clone(),
+            };
+
+            (name.clone(), value)
+        })
+        .collect()
+}
+
 impl fmt::Display for Table {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.0)
@@ -165,8 +444,31 @@ impl Into<TypedData> for Table {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::TimeZone;
+    use serde::Deserialize;
     use std::fmt::Write;
 
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Order {
+        total: i64,
+        placed: DateTime<Utc>,
+        signature: Vec<u8>,
+        discount: Option<i64>,
+        notes: String,
+    }
+
+    impl TableEntity for Order {
+        fn edm_types() -> &'static [(&'static str, EdmType)] {
+            &[
+                ("Total", EdmType::Int64),
+                ("Placed", EdmType::DateTime),
+                ("Signature", EdmType::Binary),
+                ("Discount", EdmType::Int64),
+            ]
+        }
+    }
+
     #[test]
     fn it_constructs_an_empty_table() {
         let table = Table::new();
@@ -323,4 +625,140 @@ mod tests {
             ))
         );
     }
+
+    #[test]
+    fn it_adds_an_entity_with_edm_annotations() {
+        let mut table = Table::new();
+        table
+            .add_entity(
+                "partition1",
+                "row1",
+                &Order {
+                    total: 9_007_199_254_740_993,
+                    placed: Utc.ymd(2019, 11, 1).and_hms(12, 30, 0),
+                    signature: vec![1, 2, 3],
+                    discount: None,
+                    notes: "hello".to_string(),
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            table.as_value(),
+            &json!([{
+                "PartitionKey": "partition1",
+                "RowKey": "row1",
+                "Total": "9007199254740993",
+                "Total@odata.type": "Edm.Int64",
+                "Placed": "2019-11-01T12:30:00Z",
+                "Placed@odata.type": "Edm.DateTime",
+                "Signature": "AQID",
+                "Signature@odata.type": "Edm.Binary",
+                "Discount": null,
+                "Notes": "hello"
+            }])
+        );
+    }
+
+    #[test]
+    fn it_fails_to_add_an_entity_that_is_not_an_object() {
+        struct NotAnObject;
+
+        impl Serialize for NotAnObject {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str("nope")
+            }
+        }
+
+        impl TableEntity for NotAnObject {
+            fn edm_types() -> &'static [(&'static str, EdmType)] {
+                &[]
+            }
+        }
+
+        let mut table = Table::new();
+        assert_eq!(
+            table
+                .add_entity("partition1", "row1", &NotAnObject)
+                .unwrap_err()
+                .to_string(),
+            "table entities must serialize to a JSON object"
+        );
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn it_reads_typed_entities() {
+        let data = TypedData {
+            data: Some(Data::Json(
+                r#"[{
+                    "odata.etag": "W/\"datetime'2019-11-01T12%3A30%3A00.123Z'\"",
+                    "PartitionKey": "partition1",
+                    "RowKey": "row1",
+                    "Timestamp": "2019-11-01T12:30:00.123Z",
+                    "Total": "9007199254740993",
+                    "Total@odata.type": "Edm.Int64",
+                    "Placed": "2019-11-01T12:30:00Z",
+                    "Placed@odata.type": "Edm.DateTime",
+                    "Signature": "AQID",
+                    "Signature@odata.type": "Edm.Binary",
+                    "Discount": 5,
+                    "Notes": "hello"
+                }]"#
+                .to_string(),
+            )),
+        };
+
+        let table: Table = data.into();
+        let entities = table.entities::<Order>().unwrap();
+
+        assert_eq!(
+            entities,
+            vec![Entity {
+                partition_key: "partition1".to_string(),
+                row_key: "row1".to_string(),
+                timestamp: Some(Utc.ymd(2019, 11, 1).and_hms_milli(12, 30, 0, 123)),
+                etag: Some(r#"W/"datetime'2019-11-01T12%3A30%3A00.123Z'""#.to_string()),
+                properties: Order {
+                    total: 9_007_199_254_740_993,
+                    placed: Utc.ymd(2019, 11, 1).and_hms(12, 30, 0),
+                    signature: vec![1, 2, 3],
+                    discount: Some(5),
+                    notes: "hello".to_string(),
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn it_round_trips_entities() {
+        let order = Order {
+            total: -42,
+            placed: Utc.ymd(2019, 1, 2).and_hms(3, 4, 5),
+            signature: vec![],
+            discount: Some(i64::MAX),
+            notes: String::new(),
+        };
+
+        let mut table = Table::new();
+        table.add_entity("partition1", "row1", &order).unwrap();
+
+        let entity = table.entities::<Order>().unwrap().pop().unwrap();
+        assert_eq!(entity.partition_key, "partition1");
+        assert_eq!(entity.row_key, "row1");
+        assert_eq!(entity.timestamp, None);
+        assert_eq!(entity.etag, None);
+        assert_eq!(entity.properties, order);
+    }
+
+    #[test]
+    fn it_requires_system_properties_for_entities() {
+        let mut table = Table::new();
+        table.add_row_value(json!({ "RowKey": "row1" }));
+
+        assert_eq!(
+            table.entities::<Row>().unwrap_err().to_string(),
+            "missing field `PartitionKey`"
+        );
+    }
 }
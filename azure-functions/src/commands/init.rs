@@ -6,10 +6,13 @@ use std::env::{self, current_dir, current_exe};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+const ASSETS_DIR: &str = "assets";
+
 pub struct Init<'a> {
     pub script_root: PathBuf,
     pub local_settings: Option<&'a str>,
     pub host_settings: Option<&'a str>,
+    pub assets: Option<&'a str>,
     pub sync_extensions: bool,
     pub no_debug_info: bool,
     pub verbose: bool,
@@ -52,6 +55,19 @@ impl<'a> Init<'a> {
                             }
                         })
                 )
+                .arg(
+                    Arg::with_name("assets")
+                        .long("assets")
+                        .value_name("ASSETS_DIR")
+                        .help("The path to the static assets directory to copy to the script root. Defaults to the `assets` directory in the directory containing `Cargo.toml`, if present.")
+                        .validator(|v| {
+                            if Path::new(&v).is_dir() {
+                                Ok(())
+                            } else {
+                                Err(format!("assets directory '{}' does not exist", v))
+                            }
+                        })
+                )
                 .arg(
                     Arg::with_name("sync_extensions")
                         .long("sync-extensions")
@@ -88,6 +104,10 @@ impl<'a> Init<'a> {
             None => self.create_local_settings_file(),
         };
 
+        if let Some(path) = self.get_local_assets_dir() {
+            self.copy_assets_directory(&path);
+        }
+
         let current_exe =
             current_exe().expect("failed to determine the path to the current executable");
 
@@ -154,6 +174,23 @@ impl<'a> Init<'a> {
             .unwrap_or(None)
     }
 
+    fn get_local_assets_dir(&self) -> Option<PathBuf> {
+        if let Some(path) = self.assets {
+            return Some(path.into());
+        }
+
+        env::var("CARGO_MANIFEST_DIR")
+            .map(|dir| {
+                let path = PathBuf::from(dir).join(ASSETS_DIR);
+                if path.is_dir() {
+                    Some(path)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(None)
+    }
+
     fn create_script_root(&self) {
         if self.script_root.exists() {
             if self.verbose {
@@ -273,6 +310,41 @@ impl<'a> Init<'a> {
         .unwrap_or_else(|e| panic!("failed to create '{}': {}", settings.display(), e));
     }
 
+    fn copy_assets_directory(&self, local_assets_dir: &Path) {
+        use fs_extra::dir;
+
+        let output_assets_dir = self.script_root.join(ASSETS_DIR);
+
+        if output_assets_dir.exists() {
+            fs::remove_dir_all(&output_assets_dir).unwrap_or_else(|e| {
+                panic!(
+                    "failed to delete assets directory '{}': {}",
+                    output_assets_dir.display(),
+                    e
+                )
+            });
+        }
+
+        if self.verbose {
+            println!(
+                "Copying assets directory '{}' to '{}'.",
+                local_assets_dir.display(),
+                output_assets_dir.display()
+            );
+        }
+
+        let mut options = dir::CopyOptions::new();
+        options.copy_inside = true;
+
+        dir::copy(local_assets_dir, &output_assets_dir, &options).unwrap_or_else(|e| {
+            panic!(
+                "failed to copy assets directory '{}': {}",
+                local_assets_dir.display(),
+                e
+            )
+        });
+    }
+
     fn create_worker_dir(&self) -> PathBuf {
         let worker_dir = self.script_root.join("workers").join("rust");
 
@@ -538,6 +610,7 @@ impl<'a> From<&'a ArgMatches<'a>> for Init<'a> {
                 ),
             local_settings: args.value_of("local_settings"),
             host_settings: args.value_of("host_settings"),
+            assets: args.value_of("assets"),
             sync_extensions: args.is_present("sync_extensions"),
             no_debug_info: args.is_present("no_debug_info"),
             verbose: args.is_present("verbose"),
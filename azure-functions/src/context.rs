@@ -1,4 +1,5 @@
 //! Module for function invocation context.
+use crate::rpc::RetryContext;
 use std::{cell::RefCell, env, path::PathBuf};
 
 pub(crate) const UNKNOWN_FUNCTION: &str = "<unknown>";
@@ -7,7 +8,9 @@ thread_local!(pub(crate) static CURRENT: RefCell<Context> = RefCell::new(
     Context{
         invocation_id: String::new(),
         function_id: String::new(),
-        function_name: UNKNOWN_FUNCTION
+        function_name: UNKNOWN_FUNCTION,
+        retry_count: 0,
+        max_retry_count: 0,
     }
 ));
 
@@ -17,6 +20,8 @@ pub struct Context {
     pub(crate) invocation_id: String,
     pub(crate) function_id: String,
     pub(crate) function_name: &'static str,
+    pub(crate) retry_count: i32,
+    pub(crate) max_retry_count: i32,
 }
 
 pub(crate) struct ContextGuard;
@@ -49,12 +54,15 @@ impl Context {
         invocation_id: &str,
         function_id: &str,
         function_name: &'static str,
+        retry_context: Option<&RetryContext>,
     ) -> ContextGuard {
         CURRENT.with(|c| {
             let mut c = c.borrow_mut();
             c.invocation_id.replace_range(.., invocation_id);
             c.function_id.replace_range(.., function_id);
             c.function_name = function_name;
+            c.retry_count = retry_context.map_or(0, |r| r.retry_count);
+            c.max_retry_count = retry_context.map_or(0, |r| r.max_retry_count);
         });
 
         ContextGuard {}
@@ -66,6 +74,8 @@ impl Context {
             c.invocation_id.clear();
             c.function_id.clear();
             c.function_name = UNKNOWN_FUNCTION;
+            c.retry_count = 0;
+            c.max_retry_count = 0;
         });
     }
 
@@ -84,6 +94,20 @@ impl Context {
         self.function_name
     }
 
+    /// Gets the number of times the current invocation has been retried.
+    ///
+    /// Returns zero for the first attempt or if the function has no retry policy.
+    pub fn retry_count(&self) -> i32 {
+        self.retry_count
+    }
+
+    /// Gets the maximum number of retries of the function's retry policy.
+    ///
+    /// Returns zero if the function has no retry policy.
+    pub fn max_retry_count(&self) -> i32 {
+        self.max_retry_count
+    }
+
     /// Gets the directory for the current Azure Function.
     pub fn function_directory(&self) -> Option<PathBuf> {
         self.app_directory().map(|p| p.join(self.function_name))
@@ -109,12 +133,33 @@ mod tests {
 
     #[test]
     fn it_returns_current_context() {
-        let _guard = Context::set("1234", "5678", "foo");
+        let _guard = Context::set("1234", "5678", "foo", None);
 
         let context = Context::current().unwrap();
 
         assert_eq!(context.invocation_id(), "1234");
         assert_eq!(context.function_id(), "5678");
         assert_eq!(context.function_name(), "foo");
+        assert_eq!(context.retry_count(), 0);
+        assert_eq!(context.max_retry_count(), 0);
+    }
+
+    #[test]
+    fn it_returns_the_retry_context() {
+        let _guard = Context::set(
+            "1234",
+            "5678",
+            "foo",
+            Some(&RetryContext {
+                retry_count: 2,
+                max_retry_count: 5,
+                exception: None,
+            }),
+        );
+
+        let context = Context::current().unwrap();
+
+        assert_eq!(context.retry_count(), 2);
+        assert_eq!(context.max_retry_count(), 5);
     }
 }
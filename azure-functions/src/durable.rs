@@ -14,20 +14,66 @@ use std::{
 mod action_future;
 mod actions;
 mod activity_output;
+mod durable_http;
+mod event_timeout_future;
+mod fan_out;
 mod history;
 mod join_all;
 mod orchestration_output;
 mod orchestration_state;
+mod orchestration_tester;
+mod retry_options;
 mod select_all;
+mod task_error;
+mod timer_future;
 
 pub use self::action_future::*;
 pub use self::actions::*;
 pub use self::activity_output::*;
+pub use self::durable_http::*;
+pub use self::event_timeout_future::*;
+pub use self::fan_out::*;
 pub(crate) use self::history::*;
 pub use self::join_all::*;
 pub use self::orchestration_output::*;
 pub use self::orchestration_state::*;
+pub use self::orchestration_tester::*;
+pub use self::retry_options::*;
 pub use self::select_all::*;
+pub use self::task_error::*;
+pub use self::timer_future::*;
+
+thread_local!(static EXECUTING: RefCell<Option<Rc<RefCell<OrchestrationState>>>> = RefCell::new(None));
+
+struct ExecutingGuard;
+
+impl Drop for ExecutingGuard {
+    fn drop(&mut self) {
+        EXECUTING.with(|e| e.borrow_mut().take());
+    }
+}
+
+// Represents the replay information of the orchestration executing on the current thread.
+pub(crate) struct ReplayInfo {
+    pub(crate) is_replaying: bool,
+    pub(crate) instance_id: String,
+    pub(crate) history_position: usize,
+}
+
+impl ReplayInfo {
+    pub(crate) fn current() -> Option<ReplayInfo> {
+        EXECUTING.with(|e| {
+            e.borrow().as_ref().and_then(|state| {
+                // The state may be mutably borrowed if logging from within the orchestration context
+                state.try_borrow().ok().map(|state| ReplayInfo {
+                    is_replaying: state.is_replaying(),
+                    instance_id: state.instance_id.clone(),
+                    history_position: state.history_position(),
+                })
+            })
+        })
+    }
+}
 
 /// Represents a Future returned by the orchestration context.
 pub trait OrchestrationFuture: Future {
@@ -36,6 +82,12 @@ pub trait OrchestrationFuture: Future {
 
     #[doc(hidden)]
     fn event_index(&self) -> Option<usize>;
+
+    /// Cancels any durable timers the future is waiting on.
+    ///
+    /// The remaining futures returned from `select_all` are canceled automatically unless
+    /// `SelectAll::keep_remaining` is used.
+    fn cancel(&mut self) {}
 }
 
 unsafe fn waker_clone(_: *const ()) -> RawWaker {
@@ -70,6 +122,27 @@ pub fn orchestrate<T>(
     func: impl Future<Output = T>,
     state: Rc<RefCell<OrchestrationState>>,
 ) -> InvocationResponse
+where
+    T: IntoValue,
+{
+    execute(func, &state);
+
+    InvocationResponse {
+        invocation_id: id,
+        return_value: Some(TypedData {
+            data: Some(Data::Json(state.borrow().result())),
+        }),
+        result: Some(StatusResult {
+            status: Status::Success as i32,
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+// Polls the orchestration function once, recording its output if it completes.
+// While polling, the orchestration's state is available to the logger through `ReplayInfo`.
+pub(crate) fn execute<T>(func: impl Future<Output = T>, state: &Rc<RefCell<OrchestrationState>>)
 where
     T: IntoValue,
 {
@@ -80,24 +153,15 @@ where
         ))
     };
 
+    EXECUTING.with(|e| e.borrow_mut().replace(state.clone()));
+    let _guard = ExecutingGuard;
+
     match Future::poll(Box::pin(func).as_mut(), &mut Context::from_waker(&waker)) {
         Poll::Ready(output) => {
             state.borrow_mut().set_output(output.into_value());
         }
         Poll::Pending => {}
     };
-
-    InvocationResponse {
-        invocation_id: id,
-        return_value: Some(TypedData {
-            data: Some(Data::Json(state.borrow().result())),
-        }),
-        result: Some(StatusResult {
-            status: Status::Success as i32,
-            ..Default::default()
-        }),
-        ..Default::default()
-    }
 }
 
 #[cfg(test)]
@@ -1,89 +1,126 @@
+use crate::durable::{DurableHttpRequest, EventType, RetryOptions, HTTP_ACTIVITY_NAME};
 use chrono::{DateTime, Utc};
 use serde::Serialize;
 use serde_json::Value;
 
-/// Defines retry policies that can be passed as parameters to various Durable Functions operations.
-#[derive(Debug, Clone, Default, Serialize, PartialEq)]
-#[serde(rename_all = "camelCase")]
-pub struct RetryOptions {
-    /// The first retry interval in milliseconds.
-    #[serde(rename = "firstRetryIntervalInMilliseconds")]
-    pub first_retry_interval_ms: i32,
-
-    /// The maximum number of retry attempts.
-    #[serde(rename = "maxNumberOfAttempts")]
-    pub max_attempts: i32,
-
-    /// The backoff coefficient used to determine rate of increase of backoff. Defaults to 1.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub backoff_coefficient: Option<f64>,
-
-    /// The max retry interval in milliseconds.
-    #[serde(
-        rename = "maxRetryIntervalInMilliseconds",
-        skip_serializing_if = "Option::is_none"
-    )]
-    pub max_retry_interval_ms: Option<i32>,
-
-    /// The timeout for retries in milliseconds.
-    #[serde(
-        rename = "retryTimeoutInMilliseconds",
-        skip_serializing_if = "Option::is_none"
-    )]
-    pub retry_timeout_ms: Option<i32>,
-}
-
-#[allow(dead_code)]
+/// Represents an action emitted by an orchestration function.
+///
+/// Actions are returned to the Durable Functions extension, which schedules them on behalf of the orchestration.
 #[derive(Debug, Clone, Serialize, PartialEq)]
 #[serde(tag = "actionType", rename_all = "camelCase")]
-pub(crate) enum Action {
+pub enum Action {
+    /// Calls an activity function.
     #[serde(rename_all = "camelCase")]
-    CallActivity { function_name: String, input: Value },
+    CallActivity {
+        /// The name of the activity function.
+        function_name: String,
+        /// The input to the activity function.
+        input: Value,
+    },
 
+    /// Calls an activity function with retry options.
     #[serde(rename_all = "camelCase")]
     CallActivityWithRetry {
+        /// The name of the activity function.
         function_name: String,
+        /// The retry options for the call.
         retry_options: RetryOptions,
+        /// The input to the activity function.
         input: Value,
     },
 
+    /// Calls a sub-orchestration function.
     #[serde(rename_all = "camelCase")]
     CallSubOrchestrator {
+        /// The name of the orchestration function.
         function_name: String,
+        /// The instance identifier of the sub-orchestration.
         instance_id: Option<String>,
+        /// The input to the orchestration function.
         input: Value,
     },
 
+    /// Calls a sub-orchestration function with retry options.
     #[serde(rename_all = "camelCase")]
     CallSubOrchestratorWithRetry {
+        /// The name of the orchestration function.
         function_name: String,
+        /// The retry options for the call.
         retry_options: RetryOptions,
+        /// The instance identifier of the sub-orchestration.
         instance_id: Option<String>,
+        /// The input to the orchestration function.
         input: Value,
     },
 
+    /// Makes a durable HTTP request.
+    #[serde(rename_all = "camelCase")]
+    CallHttp {
+        /// The HTTP request to make.
+        http_request: DurableHttpRequest,
+    },
+
+    /// Restarts the orchestration with a new input.
     #[serde(rename_all = "camelCase")]
     ContinueAsNew {
+        /// The new input to the orchestration.
         input: Value,
+        /// Whether or not to preserve unprocessed external events.
         preserve_unprocessed_events: bool,
     },
 
+    /// Creates a durable timer.
     #[serde(rename_all = "camelCase")]
     CreateTimer {
+        /// The time at which the timer fires.
         fire_at: DateTime<Utc>,
 
+        /// Whether or not the timer has been canceled.
         #[serde(rename = "isCanceled")]
         canceled: bool,
     },
 
+    /// Waits for an external event.
     #[serde(rename_all = "camelCase")]
-    WaitForExternalEvent { external_event_name: String },
+    WaitForExternalEvent {
+        /// The name of the external event.
+        external_event_name: String,
+    },
+}
+
+impl Action {
+    // Gets the type of the history event that records the action being scheduled, if any.
+    pub(crate) fn scheduled_event_type(&self) -> Option<EventType> {
+        match self {
+            Action::CallActivity { .. }
+            | Action::CallActivityWithRetry { .. }
+            | Action::CallHttp { .. } => Some(EventType::TaskScheduled),
+            Action::CallSubOrchestrator { .. } | Action::CallSubOrchestratorWithRetry { .. } => {
+                Some(EventType::SubOrchestrationInstanceCreated)
+            }
+            Action::CreateTimer { .. } => Some(EventType::TimerCreated),
+            _ => None,
+        }
+    }
+
+    // Gets the name recorded in the history event that records the action being scheduled, if any.
+    pub(crate) fn scheduled_name(&self) -> Option<&str> {
+        match self {
+            Action::CallActivity { function_name, .. }
+            | Action::CallActivityWithRetry { function_name, .. }
+            | Action::CallSubOrchestrator { function_name, .. }
+            | Action::CallSubOrchestratorWithRetry { function_name, .. } => Some(function_name),
+            Action::CallHttp { .. } => Some(HTTP_ACTIVITY_NAME),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::durable::{Action, RetryOptions};
     use chrono::{DateTime, Utc};
+    use std::time::Duration;
 
     macro_rules! it_converts_to_json {
         ($($name:ident: $value:expr,)*) => {
@@ -110,11 +147,7 @@ mod tests {
         (
             Action::CallActivityWithRetry {
                 function_name: "hello".to_owned(),
-                retry_options: RetryOptions {
-                    first_retry_interval_ms: 1000,
-                    max_attempts: 3,
-                    ..Default::default()
-                },
+                retry_options: RetryOptions::builder(Duration::from_secs(1), 3).build().unwrap(),
                 input: "World".into(),
             },
             r#"{"actionType":"callActivityWithRetry","functionName":"hello","retryOptions":{"firstRetryIntervalInMilliseconds":1000,"maxNumberOfAttempts":3},"input":"World"}"#
@@ -132,11 +165,7 @@ mod tests {
         (
             Action::CallSubOrchestratorWithRetry {
                 function_name: "hello".to_string(),
-                retry_options: RetryOptions {
-                    first_retry_interval_ms: 1000,
-                    max_attempts: 3,
-                    ..Default::default()
-                },
+                retry_options: RetryOptions::builder(Duration::from_secs(1), 3).build().unwrap(),
                 instance_id: Some("1231232144".to_string()),
                 input: "World".into()
             },
@@ -155,6 +184,13 @@ mod tests {
            },
            r#"{"actionType":"createTimer","fireAt":"2019-07-18T06:22:27.016757Z","isCanceled":true}"#
         ),
+        call_http_converts_to_json:
+        (
+            Action::CallHttp {
+                http_request: crate::durable::DurableHttpRequest::new("GET", "https://example.com"),
+            },
+            r#"{"actionType":"callHttp","httpRequest":{"method":"GET","uri":"https://example.com","asynchronousPatternEnabled":true}}"#
+        ),
         wait_for_external_event_converts_to_json:
         (
             Action::WaitForExternalEvent { external_event_name: "SmsChallengeResponse".to_string() },
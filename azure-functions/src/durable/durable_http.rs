@@ -0,0 +1,190 @@
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use serde_json::{from_str, Value};
+use std::collections::HashMap;
+
+/// The name of the built-in activity the Durable Functions extension uses for durable HTTP requests.
+pub(crate) const HTTP_ACTIVITY_NAME: &str = "BuiltIn::HttpActivity";
+
+/// Represents the source of an OAuth token for a durable HTTP request.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "kind")]
+pub enum TokenSource {
+    /// A token acquired using the managed identity of the function app.
+    #[serde(rename = "AzureManagedIdentity")]
+    ManagedIdentity {
+        /// The resource to acquire a token for (e.g. `https://management.core.windows.net`).
+        resource: String,
+    },
+}
+
+/// Represents a durable HTTP request made by an orchestration.
+///
+/// The request is performed by the Durable Functions extension and its response is
+/// recorded in the orchestration history, making it safe to replay.
+///
+/// By default, the extension follows the asynchronous 202 (Accepted) pattern: if the
+/// response has a status of 202 and a `Location` header, the extension polls the location
+/// until a different status is returned.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DurableHttpRequest {
+    /// The HTTP method of the request.
+    pub method: String,
+    /// The URI of the request.
+    pub uri: String,
+    /// The headers of the request.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+    /// The content of the request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    /// The source of the OAuth token to add to the request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_source: Option<TokenSource>,
+    /// Whether or not the extension polls 202 (Accepted) responses until completion.
+    pub asynchronous_pattern_enabled: bool,
+}
+
+impl DurableHttpRequest {
+    /// Creates a new durable HTTP request with the given method and URI.
+    pub fn new(method: &str, uri: &str) -> Self {
+        DurableHttpRequest {
+            method: method.to_owned(),
+            uri: uri.to_owned(),
+            headers: HashMap::new(),
+            content: None,
+            token_source: None,
+            asynchronous_pattern_enabled: true,
+        }
+    }
+
+    /// Adds a header to the request.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.insert(name.to_owned(), value.to_owned());
+        self
+    }
+
+    /// Sets the content of the request.
+    pub fn content<C>(mut self, content: C) -> Self
+    where
+        C: Into<String>,
+    {
+        self.content = Some(content.into());
+        self
+    }
+
+    /// Sets the content of the request to the given JSON value and sets the `Content-Type` header.
+    pub fn json<V>(self, value: V) -> Self
+    where
+        V: Into<Value>,
+    {
+        self.header("Content-Type", "application/json")
+            .content(value.into().to_string())
+    }
+
+    /// Sets the source of the OAuth token to add to the request.
+    pub fn token_source(mut self, token_source: TokenSource) -> Self {
+        self.token_source = Some(token_source);
+        self
+    }
+
+    /// Sets whether or not the extension polls 202 (Accepted) responses until completion.
+    pub fn asynchronous_pattern(mut self, enabled: bool) -> Self {
+        self.asynchronous_pattern_enabled = enabled;
+        self
+    }
+}
+
+/// Represents the response to a durable HTTP request.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DurableHttpResponse {
+    /// The HTTP status code of the response.
+    pub status_code: u16,
+    /// The headers of the response.
+    ///
+    /// Multiple values for the same header are joined with a comma.
+    #[serde(default, deserialize_with = "deserialize_headers")]
+    pub headers: HashMap<String, String>,
+    /// The content of the response.
+    #[serde(default)]
+    pub content: Option<String>,
+}
+
+impl DurableHttpResponse {
+    /// Deserializes the content of the response as JSON.
+    pub fn json<T>(&self) -> serde_json::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        from_str(self.content.as_ref().map(String::as_str).unwrap_or("null"))
+    }
+}
+
+fn deserialize_headers<'de, D>(deserializer: D) -> Result<HashMap<String, String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum HeaderValue {
+        Single(String),
+        Multiple(Vec<String>),
+    }
+
+    let headers: Option<HashMap<String, HeaderValue>> = Option::deserialize(deserializer)?;
+
+    Ok(headers
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(k, v)| match v {
+            HeaderValue::Single(v) => (k, v),
+            HeaderValue::Multiple(v) => (k, v.join(", ")),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{from_value, json, to_value};
+
+    #[test]
+    fn it_serializes_a_request() {
+        let request = DurableHttpRequest::new("POST", "https://example.com/api")
+            .json(json!({ "name": "test" }))
+            .token_source(TokenSource::ManagedIdentity {
+                resource: "https://management.core.windows.net".to_string(),
+            });
+
+        assert_eq!(
+            to_value(&request).unwrap(),
+            json!({
+                "method": "POST",
+                "uri": "https://example.com/api",
+                "headers": { "Content-Type": "application/json" },
+                "content": r#"{"name":"test"}"#,
+                "tokenSource": {
+                    "kind": "AzureManagedIdentity",
+                    "resource": "https://management.core.windows.net"
+                },
+                "asynchronousPatternEnabled": true
+            })
+        );
+    }
+
+    #[test]
+    fn it_deserializes_a_response() {
+        let response: DurableHttpResponse = from_value(json!({
+            "statusCode": 200,
+            "headers": { "Content-Type": "application/json", "Vary": ["Accept", "Origin"] },
+            "content": r#"{"id":1}"#
+        }))
+        .unwrap();
+
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.headers["Content-Type"], "application/json");
+        assert_eq!(response.headers["Vary"], "Accept, Origin");
+        assert_eq!(response.json::<Value>().unwrap(), json!({ "id": 1 }));
+    }
+}
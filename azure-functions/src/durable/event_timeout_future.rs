@@ -0,0 +1,120 @@
+use crate::durable::{
+    ActionFuture, OrchestrationFuture, OrchestrationState, TaskError, TimerFuture,
+};
+use futures::future::FutureExt;
+use std::{
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+/// Represents the outcome of waiting for an external event with a timeout.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventOutcome<T> {
+    /// The event was received with the given data.
+    Received(T),
+    /// The timeout expired before the event was received.
+    TimedOut,
+}
+
+/// Future returned by the `DurableOrchestrationContext::wait_for_event_with_timeout` function.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct EventTimeoutFuture<T> {
+    event: ActionFuture<Result<T, TaskError>>,
+    timer: TimerFuture,
+    state: Rc<RefCell<OrchestrationState>>,
+    event_index: Option<usize>,
+    is_inner: bool,
+}
+
+impl<T> EventTimeoutFuture<T>
+where
+    T: Unpin,
+{
+    pub(crate) fn new(
+        mut event: ActionFuture<Result<T, TaskError>>,
+        mut timer: TimerFuture,
+        state: Rc<RefCell<OrchestrationState>>,
+    ) -> Self {
+        event.notify_inner();
+        timer.notify_inner();
+
+        // Whichever of the event or the timer happened first wins
+        let event_index = event
+            .event_index()
+            .into_iter()
+            .chain(timer.event_index())
+            .min();
+
+        // If the timer won, the event was raised after the timeout and remains buffered for the next wait
+        if let Some(raised) = event.event_index() {
+            if event_index != Some(raised) {
+                state.borrow_mut().history[raised].is_processed = false;
+            }
+        }
+
+        EventTimeoutFuture {
+            event,
+            timer,
+            state,
+            event_index,
+            is_inner: false,
+        }
+    }
+}
+
+impl<T> Unpin for EventTimeoutFuture<T> {}
+
+impl<T> Future for EventTimeoutFuture<T>
+where
+    T: Unpin,
+{
+    type Output = Result<EventOutcome<T>, TaskError>;
+
+    fn poll(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<Self::Output> {
+        let event_index = self.event_index;
+        if event_index.is_none() {
+            return Poll::Pending;
+        }
+
+        let result = if self.event.event_index() == event_index {
+            match self.event.poll_unpin(context) {
+                Poll::Ready(data) => {
+                    self.timer.cancel();
+                    data.map(EventOutcome::Received)
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        } else {
+            match self.timer.poll_unpin(context) {
+                Poll::Ready(()) => Ok(EventOutcome::TimedOut),
+                Poll::Pending => return Poll::Pending,
+            }
+        };
+
+        if !self.is_inner {
+            self.state.borrow_mut().update(event_index.unwrap());
+        }
+
+        Poll::Ready(result)
+    }
+}
+
+impl<T> OrchestrationFuture for EventTimeoutFuture<T>
+where
+    T: Unpin,
+{
+    fn notify_inner(&mut self) {
+        self.is_inner = true;
+    }
+
+    fn event_index(&self) -> Option<usize> {
+        self.event_index
+    }
+
+    fn cancel(&mut self) {
+        self.timer.cancel();
+    }
+}
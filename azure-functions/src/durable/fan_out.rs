@@ -0,0 +1,10 @@
+/// Determines how `DurableOrchestrationContext::fan_out` handles failed tasks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FanOutMode {
+    /// Stop scheduling new tasks and return the first failure.
+    ///
+    /// Tasks that are already scheduled are not waited on.
+    FailFast,
+    /// Run every task to completion and return all failures.
+    CollectFailures,
+}
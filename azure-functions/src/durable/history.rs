@@ -68,3 +68,15 @@ pub enum EventType {
     GenericEvent = 17,
     HistoryState = 18,
 }
+
+impl EventType {
+    // Determines if the event records work scheduled by an orchestration function.
+    pub(crate) fn is_scheduled(self) -> bool {
+        match self {
+            EventType::TaskScheduled
+            | EventType::SubOrchestrationInstanceCreated
+            | EventType::TimerCreated => true,
+            _ => false,
+        }
+    }
+}
@@ -5,23 +5,32 @@ use serde_json::{to_string, Value};
 use sha1::Sha1;
 use uuid::Uuid;
 
-#[derive(Debug, Serialize, Default)]
+/// Represents the result of executing an orchestration function.
+#[derive(Debug, Clone, Serialize, Default, PartialEq)]
 #[serde(rename_all = "camelCase")]
-struct ExecutionResult {
-    is_done: bool,
-    actions: Vec<Vec<Action>>,
-    output: Option<Value>,
-    custom_status: Option<Value>,
-    error: Option<String>,
+pub struct ExecutionResult {
+    /// Whether or not the orchestration has completed.
+    pub is_done: bool,
+    /// The actions emitted by the orchestration, grouped by orchestration episode.
+    pub actions: Vec<Vec<Action>>,
+    /// The output of the orchestration, if it has completed.
+    pub output: Option<Value>,
+    /// The custom status of the orchestration.
+    pub custom_status: Option<Value>,
+    /// The error of the orchestration, if it has failed.
+    pub error: Option<String>,
 }
 
 #[doc(hidden)]
 pub struct OrchestrationState {
+    pub(crate) instance_id: String,
+    pub(crate) version: Option<String>,
     pub(crate) history: Vec<HistoryEvent>,
     result: ExecutionResult,
     started_index: usize,
     completed_index: Option<usize>,
     guid_counter: u32,
+    matched_schedules: Vec<usize>,
 }
 
 impl OrchestrationState {
@@ -37,11 +46,14 @@ impl OrchestrationState {
             .map(|pos| pos + started_index);
 
         OrchestrationState {
+            instance_id: String::new(),
+            version: None,
             history,
             result: ExecutionResult::default(),
             started_index,
             completed_index,
             guid_counter: 0,
+            matched_schedules: Vec::new(),
         }
     }
 
@@ -52,19 +64,107 @@ impl OrchestrationState {
         }
     }
 
+    pub(crate) fn history_position(&self) -> usize {
+        self.started_index
+    }
+
     pub(crate) fn current_time(&self) -> DateTime<Utc> {
         self.history[self.started_index].timestamp
     }
 
-    pub(crate) fn push_action(&mut self, action: Action) {
+    pub(crate) fn push_action(&mut self, action: Action) -> (usize, usize) {
+        self.check_action(&action);
+
         if self.result.actions.is_empty() {
             self.result.actions.push(Vec::new());
         }
 
-        self.result.actions.last_mut().unwrap().push(action);
+        let episode = self.result.actions.len() - 1;
+        let actions = self.result.actions.last_mut().unwrap();
+        actions.push(action);
+
+        (episode, actions.len() - 1)
+    }
+
+    // Checks that an action matches the work recorded in the history for the episode being replayed.
+    // Any failure to match means the orchestration function has changed in a non-deterministic way.
+    fn check_action(&mut self, action: &Action) {
+        let event_type = match action.scheduled_event_type() {
+            Some(t) => t,
+            None => return,
+        };
+
+        // Only episodes that have completed have recorded the work they scheduled
+        let completed_index = match self.completed_index {
+            Some(i) => i,
+            None => return,
+        };
+
+        let name = action.scheduled_name();
+
+        let unmatched: Vec<usize> = (self.started_index + 1..completed_index)
+            .filter(|i| {
+                self.history[*i].event_type.is_scheduled() && !self.matched_schedules.contains(i)
+            })
+            .collect();
+
+        if let Some(index) = unmatched.iter().find(|i| {
+            let event = &self.history[**i];
+            event.event_type == event_type && event.name.as_ref().map(String::as_str) == name
+        }) {
+            self.matched_schedules.push(*index);
+            return;
+        }
+
+        if self.result.error.is_some() {
+            return;
+        }
+
+        let actual = describe_schedule(event_type, name);
+
+        let message = match unmatched.first() {
+            Some(index) => {
+                let event = &self.history[*index];
+                format!(
+                    "expected {} at history position {}, but the orchestration function scheduled {}",
+                    describe_schedule(event.event_type, event.name.as_ref().map(String::as_str)),
+                    index,
+                    actual
+                )
+            }
+            None => format!(
+                "the orchestration function scheduled {}, but the history for the episode at history position {} has no further scheduled work",
+                actual, self.started_index
+            ),
+        };
+
+        self.result.error = Some(match &self.version {
+            Some(version) => format!(
+                "non-deterministic orchestration (version {}): {}",
+                version, message
+            ),
+            None => format!("non-deterministic orchestration: {}", message),
+        });
+        self.result.is_done = true;
+    }
+
+    pub(crate) fn cancel_timer(&mut self, position: (usize, usize)) {
+        if let Some(Action::CreateTimer { canceled, .. }) = self
+            .result
+            .actions
+            .get_mut(position.0)
+            .and_then(|a| a.get_mut(position.1))
+        {
+            *canceled = true;
+        }
     }
 
     pub(crate) fn set_output(&mut self, value: Value) {
+        // A non-deterministic orchestration has failed regardless of its output
+        if self.result.error.is_some() {
+            return;
+        }
+
         self.result.output = Some(value);
         self.result.is_done = true;
     }
@@ -77,6 +177,10 @@ impl OrchestrationState {
         to_string(&self.result).unwrap()
     }
 
+    pub(crate) fn execution_result(&self) -> &ExecutionResult {
+        &self.result
+    }
+
     pub(crate) fn find_start_event(
         &mut self,
         name: &str,
@@ -211,6 +315,13 @@ impl OrchestrationState {
     }
 }
 
+fn describe_schedule(event_type: EventType, name: Option<&str>) -> String {
+    match name {
+        Some(name) => format!("{:?} for '{}'", event_type, name),
+        None => format!("{:?}", event_type),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -598,4 +709,84 @@ mod tests {
         assert_ne!(state.current_time(), current_time);
         assert!(!state.is_replaying());
     }
+
+    fn create_replayed_history() -> Vec<HistoryEvent> {
+        vec![
+            create_event(EventType::OrchestratorStarted, -1, None, None, None),
+            create_event(
+                EventType::TaskScheduled,
+                0,
+                Some("foo".to_string()),
+                None,
+                None,
+            ),
+            create_event(EventType::TimerCreated, 1, None, None, None),
+            create_event(EventType::OrchestratorCompleted, -1, None, None, None),
+            create_event(EventType::OrchestratorStarted, -1, None, None, None),
+        ]
+    }
+
+    #[test]
+    fn it_matches_actions_with_the_history() {
+        let mut state = OrchestrationState::new(create_replayed_history());
+
+        state.push_action(Action::CreateTimer {
+            fire_at: Utc::now(),
+            canceled: false,
+        });
+        state.push_action(Action::CallActivity {
+            function_name: "foo".to_string(),
+            input: json!("hello"),
+        });
+        state.push_action(Action::WaitForExternalEvent {
+            external_event_name: "bar".to_string(),
+        });
+
+        assert_eq!(state.result.error, None);
+        assert!(!state.result.is_done);
+    }
+
+    #[test]
+    fn it_fails_when_an_action_does_not_match_the_history() {
+        let mut state = OrchestrationState::new(create_replayed_history());
+
+        state.push_action(Action::CallSubOrchestrator {
+            function_name: "foo".to_string(),
+            instance_id: None,
+            input: json!("hello"),
+        });
+
+        assert!(state.result.is_done);
+        assert_eq!(
+            state.result.error.as_ref().unwrap(),
+            "non-deterministic orchestration: expected TaskScheduled for 'foo' at history position 1, but the orchestration function scheduled SubOrchestrationInstanceCreated for 'foo'"
+        );
+
+        state.set_output(json!(42));
+        assert_eq!(state.result.output, None);
+    }
+
+    #[test]
+    fn it_fails_when_an_action_exceeds_the_history() {
+        let mut state = OrchestrationState::new(create_replayed_history());
+        state.version = Some("1.0".to_string());
+
+        state.push_action(Action::CallActivity {
+            function_name: "foo".to_string(),
+            input: json!("hello"),
+        });
+        state.push_action(Action::CreateTimer {
+            fire_at: Utc::now(),
+            canceled: false,
+        });
+        state.push_action(Action::CallActivity {
+            function_name: "foo".to_string(),
+            input: json!("hello"),
+        });
+
+        assert_eq!(
+            state.result.error.as_ref().unwrap(),
+            "non-deterministic orchestration (version 1.0): the orchestration function scheduled TaskScheduled for 'foo', but the history for the episode at history position 0 has no further scheduled work"
+        );
+    }
 }
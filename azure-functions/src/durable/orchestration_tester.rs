@@ -0,0 +1,889 @@
+use crate::{
+    bindings::DurableOrchestrationContext,
+    durable::{
+        execute, Action, DurableHttpResponse, EventType, ExecutionResult, HistoryEvent, IntoValue,
+        HTTP_ACTIVITY_NAME,
+    },
+};
+use azure_functions_durable::OrchestrationStatus;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use serde_json::{from_str, to_string, to_value, Value};
+use std::future::Future;
+
+const DEFAULT_INSTANCE_ID: &str = "00000000000000000000000000000000";
+
+/// Runs orchestration functions offline against a scripted or recorded history.
+///
+/// Each call to `run` executes a single orchestration episode, replaying the history
+/// accumulated so far and returning the result of the execution.
+///
+/// The actions emitted by an episode are recorded into the history so that tests can
+/// complete activities, fail sub-orchestrations, fire timers or raise external events
+/// before running the next episode.
+///
+/// A history recorded by the Durable Functions HTTP API (i.e. an instance status queried
+/// with `showHistory` and `showHistoryOutput`) can be replayed with `from_status` or `from_json`.
+///
+/// # Examples
+///
+/// ```rust
+/// use azure_functions::{
+///     bindings::DurableOrchestrationContext,
+///     durable::{Action, OrchestrationOutput, OrchestrationTester},
+/// };
+/// use serde_json::{json, Value};
+///
+/// async fn greet(context: DurableOrchestrationContext) -> OrchestrationOutput {
+///     match context.call_activity("say_hello", "Tokyo").await {
+///         Ok(greeting) => greeting.into(),
+///         Err(e) => format!("failed: {}", e).into(),
+///     }
+/// }
+///
+/// let mut tester = OrchestrationTester::new("greet", Value::Null);
+///
+/// let result = tester.run(greet);
+/// assert!(!result.is_done);
+/// assert_eq!(
+///     result.actions,
+///     vec![vec![Action::CallActivity {
+///         function_name: "say_hello".to_string(),
+///         input: json!("Tokyo"),
+///     }]]
+/// );
+///
+/// tester.complete_activity("say_hello", "Hello Tokyo!");
+///
+/// let result = tester.run(greet);
+/// assert!(result.is_done);
+/// assert_eq!(result.output, Some(json!("Hello Tokyo!")));
+/// ```
+pub struct OrchestrationTester {
+    instance_id: String,
+    parent_instance_id: Option<String>,
+    version: Option<String>,
+    input: Value,
+    history: Vec<HistoryEvent>,
+    inbound: Vec<HistoryEvent>,
+    current_time: DateTime<Utc>,
+    canceled_timers: Vec<i32>,
+}
+
+impl OrchestrationTester {
+    /// Creates a new orchestration tester for the given orchestration name and input.
+    pub fn new<D>(name: &str, input: D) -> Self
+    where
+        D: Into<Value>,
+    {
+        let mut tester = OrchestrationTester {
+            instance_id: DEFAULT_INSTANCE_ID.to_string(),
+            parent_instance_id: None,
+            version: None,
+            input: input.into(),
+            history: Vec::new(),
+            inbound: Vec::new(),
+            current_time: Utc::now(),
+            canceled_timers: Vec::new(),
+        };
+
+        tester
+            .history
+            .push(tester.event(EventType::OrchestratorStarted));
+
+        let mut started = tester.event(EventType::ExecutionStarted);
+        started.name = Some(name.to_string());
+        started.input = Some(Value::String(tester.input.to_string()));
+        tester.history.push(started);
+
+        tester
+    }
+
+    /// Creates a new orchestration tester from an instance status returned by the Durable Functions HTTP API.
+    ///
+    /// The status must have been queried with both `show_history` and `show_history_output` set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the status does not contain a history or if the history does not contain an `ExecutionStarted` event.
+    pub fn from_status(status: &OrchestrationStatus) -> Self {
+        let events = status
+            .history_events
+            .as_ref()
+            .expect("expected the orchestration status to contain history events");
+
+        let started = events
+            .iter()
+            .find(|e| e.event_type == "ExecutionStarted")
+            .expect("expected the history to contain an ExecutionStarted event");
+
+        let mut tester = OrchestrationTester::new(
+            started.function_name.as_ref().map_or("", String::as_str),
+            status
+                .input
+                .clone()
+                .or_else(|| started.input.clone())
+                .unwrap_or_default(),
+        );
+
+        tester.current_time = started.timestamp;
+        for event in tester.history.iter_mut() {
+            event.timestamp = started.timestamp;
+        }
+
+        // The HTTP API omits the events that schedule work, so they are reconstructed from each
+        // completion's scheduled time; every completion then starts a new orchestration episode.
+        let mut scheduled = Vec::new();
+        let mut completions = Vec::new();
+        let mut pending_timers = Vec::new();
+        let mut episode_time = started.timestamp;
+        let mut next_id = 0;
+
+        for recorded in events {
+            let mut event = match recorded.event_type.as_str() {
+                "TaskCompleted" | "TaskFailed" => {
+                    let mut event = tester.event(EventType::TaskScheduled);
+                    event.event_id = next_id;
+                    event.name = recorded.function_name.clone();
+                    scheduled.push((recorded.scheduled_time.unwrap_or(episode_time), event));
+
+                    let mut event = tester.event(if recorded.event_type == "TaskCompleted" {
+                        EventType::TaskCompleted
+                    } else {
+                        EventType::TaskFailed
+                    });
+                    event.task_scheduled_id = Some(next_id);
+                    next_id += 1;
+                    event
+                }
+                "SubOrchestrationInstanceCompleted" | "SubOrchestrationInstanceFailed" => {
+                    let mut event = tester.event(EventType::SubOrchestrationInstanceCreated);
+                    event.event_id = next_id;
+                    event.name = recorded.function_name.clone();
+                    scheduled.push((recorded.scheduled_time.unwrap_or(episode_time), event));
+
+                    let mut event = tester.event(
+                        if recorded.event_type == "SubOrchestrationInstanceCompleted" {
+                            EventType::SubOrchestrationInstanceCompleted
+                        } else {
+                            EventType::SubOrchestrationInstanceFailed
+                        },
+                    );
+                    event.task_scheduled_id = Some(next_id);
+                    next_id += 1;
+                    event
+                }
+                "TimerCreated" => {
+                    let mut event = tester.event(EventType::TimerCreated);
+                    event.event_id = next_id;
+                    event.fire_at = recorded.fire_at;
+                    scheduled.push((recorded.timestamp, event));
+                    pending_timers.push((next_id, recorded.fire_at));
+                    next_id += 1;
+                    continue;
+                }
+                "TimerFired" => {
+                    let timer_id = match pending_timers
+                        .iter()
+                        .position(|(_, fire_at)| *fire_at == recorded.fire_at)
+                    {
+                        Some(pos) => pending_timers.remove(pos).0,
+                        None => {
+                            let mut event = tester.event(EventType::TimerCreated);
+                            event.event_id = next_id;
+                            event.fire_at = recorded.fire_at;
+                            scheduled.push((episode_time, event));
+                            next_id += 1;
+                            next_id - 1
+                        }
+                    };
+
+                    let mut event = tester.event(EventType::TimerFired);
+                    event.timer_id = Some(timer_id);
+                    event.fire_at = recorded.fire_at;
+                    event
+                }
+                "EventRaised" => {
+                    let mut event = tester.event(EventType::EventRaised);
+                    event.name = recorded.name.clone();
+                    event.input = recorded.input.clone().map(|v| match v {
+                        Value::String(s) => Value::String(s),
+                        v => Value::String(v.to_string()),
+                    });
+                    event
+                }
+                _ => continue,
+            };
+
+            event.result = recorded.result.as_ref().map(Value::to_string);
+            event.reason = recorded.reason.clone();
+            event.details = recorded.details.clone();
+            event.timestamp = recorded.timestamp;
+            episode_time = recorded.timestamp;
+            completions.push(event);
+        }
+
+        scheduled.sort_by_key(|(time, _)| *time);
+        completions.sort_by_key(|e| e.timestamp);
+
+        let mut scheduled = scheduled.into_iter().peekable();
+        let mut next_schedules = |history: &mut Vec<HistoryEvent>, until: Option<DateTime<Utc>>| {
+            while let Some((time, _)) = scheduled.peek() {
+                if let Some(until) = until {
+                    if *time >= until {
+                        break;
+                    }
+                }
+                let (time, mut event) = scheduled.next().unwrap();
+                event.timestamp = time;
+                history.push(event);
+            }
+        };
+
+        let mut completions = completions.into_iter().peekable();
+        next_schedules(&mut tester.history, completions.peek().map(|e| e.timestamp));
+
+        while let Some(completion) = completions.next() {
+            tester.current_time = completion.timestamp;
+            tester
+                .history
+                .push(tester.event(EventType::OrchestratorCompleted));
+            tester
+                .history
+                .push(tester.event(EventType::OrchestratorStarted));
+            tester.history.push(completion);
+            next_schedules(&mut tester.history, completions.peek().map(|e| e.timestamp));
+        }
+
+        tester
+    }
+
+    /// Creates a new orchestration tester from an instance status JSON returned by the Durable Functions HTTP API.
+    ///
+    /// The status must have been queried with both `showHistory` and `showHistoryOutput` set to true.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        Ok(OrchestrationTester::from_status(&from_str(json)?))
+    }
+
+    /// Sets the orchestration instance identifier used by the tester.
+    pub fn with_instance_id(mut self, instance_id: &str) -> Self {
+        self.instance_id = instance_id.to_string();
+        self
+    }
+
+    /// Sets the parent orchestration instance identifier used by the tester.
+    pub fn with_parent_instance_id(mut self, parent_instance_id: &str) -> Self {
+        self.parent_instance_id = Some(parent_instance_id.to_string());
+        self
+    }
+
+    /// Sets the orchestration function version used by the tester.
+    pub fn with_version(mut self, version: &str) -> Self {
+        self.version = Some(version.to_string());
+        self
+    }
+
+    /// Gets the orchestration instance identifier used by the tester.
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    /// Gets the time that the next orchestration episode will start at.
+    pub fn current_time(&self) -> DateTime<Utc> {
+        self.current_time
+    }
+
+    /// Advances the time that the next orchestration episode will start at.
+    pub fn advance_time(&mut self, duration: Duration) {
+        self.current_time = self.current_time + duration;
+    }
+
+    /// Completes the first pending call to the given activity function with the given result.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no pending call to the activity function.
+    pub fn complete_activity<D>(&mut self, activity_name: &str, result: D)
+    where
+        D: Into<Value>,
+    {
+        self.complete(
+            EventType::TaskScheduled,
+            EventType::TaskCompleted,
+            activity_name,
+            result.into(),
+        );
+    }
+
+    /// Completes the first pending durable HTTP request with the given response.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no pending durable HTTP request.
+    pub fn complete_http(&mut self, response: &DurableHttpResponse) {
+        self.complete(
+            EventType::TaskScheduled,
+            EventType::TaskCompleted,
+            HTTP_ACTIVITY_NAME,
+            to_value(response).unwrap(),
+        );
+    }
+
+    /// Fails the first pending call to the given activity function with the given reason.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no pending call to the activity function.
+    pub fn fail_activity(&mut self, activity_name: &str, reason: &str) {
+        self.fail(
+            EventType::TaskScheduled,
+            EventType::TaskFailed,
+            activity_name,
+            reason,
+        );
+    }
+
+    /// Completes the first pending call to the given sub-orchestration function with the given result.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no pending call to the sub-orchestration function.
+    pub fn complete_sub_orchestration<D>(&mut self, function_name: &str, result: D)
+    where
+        D: Into<Value>,
+    {
+        self.complete(
+            EventType::SubOrchestrationInstanceCreated,
+            EventType::SubOrchestrationInstanceCompleted,
+            function_name,
+            result.into(),
+        );
+    }
+
+    /// Fails the first pending call to the given sub-orchestration function with the given reason.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no pending call to the sub-orchestration function.
+    pub fn fail_sub_orchestration(&mut self, function_name: &str, reason: &str) {
+        self.fail(
+            EventType::SubOrchestrationInstanceCreated,
+            EventType::SubOrchestrationInstanceFailed,
+            function_name,
+            reason,
+        );
+    }
+
+    /// Fires the pending durable timer with the earliest fire time.
+    ///
+    /// Timers canceled by the orchestration function are not fired.
+    ///
+    /// The tester's current time is advanced to the timer's fire time if it is in the future.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no pending durable timer.
+    pub fn fire_timer(&mut self) {
+        let (timer_id, fire_at) = self
+            .history
+            .iter()
+            .filter(|e| {
+                e.event_type == EventType::TimerCreated
+                    && !self.is_finished(e.event_id)
+                    && !self.canceled_timers.contains(&e.event_id)
+            })
+            .min_by_key(|e| e.fire_at)
+            .map(|e| (e.event_id, e.fire_at))
+            .expect("there are no pending durable timers");
+
+        if let Some(fire_at) = fire_at {
+            if fire_at > self.current_time {
+                self.current_time = fire_at;
+            }
+        }
+
+        let mut event = self.event(EventType::TimerFired);
+        event.timer_id = Some(timer_id);
+        event.fire_at = fire_at;
+        self.inbound.push(event);
+    }
+
+    /// Raises an external event with the given name and data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the data cannot be serialized to JSON.
+    pub fn raise_event<D>(&mut self, name: &str, data: D)
+    where
+        D: Serialize,
+    {
+        let mut event = self.event(EventType::EventRaised);
+        event.name = Some(name.to_string());
+        event.input = Some(Value::String(to_string(&data).unwrap_or_else(|e| {
+            panic!("failed to serialize data for event '{}': {}", name, e)
+        })));
+        self.inbound.push(event);
+    }
+
+    /// Runs an orchestration episode for the given orchestration function.
+    ///
+    /// Any completions, failures, fired timers or raised events since the last episode are
+    /// delivered to the orchestration before it runs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the orchestration function does not schedule the same work as recorded in the history
+    /// (i.e. the orchestration function is not deterministic).
+    pub fn run<F, Fut, T>(&mut self, func: F) -> ExecutionResult
+    where
+        F: FnOnce(DurableOrchestrationContext) -> Fut,
+        Fut: Future<Output = T>,
+        T: IntoValue,
+    {
+        if self.history.last().map(|e| e.event_type) == Some(EventType::OrchestratorCompleted) {
+            self.history
+                .push(self.event(EventType::OrchestratorStarted));
+        }
+
+        self.history.append(&mut self.inbound);
+
+        let mut context = DurableOrchestrationContext::with_history(
+            self.instance_id.clone(),
+            self.parent_instance_id.clone(),
+            self.input.clone(),
+            self.history.clone(),
+        );
+
+        if let Some(version) = &self.version {
+            context.set_version(version);
+        }
+
+        let state = context.state();
+        execute(func(context), &state);
+
+        let state = state.borrow();
+
+        if let Some(error) = &state.execution_result().error {
+            panic!("{}", error);
+        }
+
+        if let Some((position, event)) = state
+            .history
+            .iter()
+            .enumerate()
+            .find(|(_, e)| e.event_type.is_scheduled() && !e.is_processed)
+        {
+            panic!(
+                "non-deterministic orchestration: the {:?} event at history position {}{} was not scheduled by the orchestration function",
+                event.event_type,
+                position,
+                event.name.as_ref().map(|n| format!(" for '{}'", n)).unwrap_or_default(),
+            );
+        }
+
+        let result = state.execution_result().clone();
+
+        let next_id = self
+            .history
+            .iter()
+            .filter(|e| e.event_type.is_scheduled())
+            .map(|e| e.event_id + 1)
+            .max()
+            .unwrap_or(0);
+
+        let replayed = self
+            .history
+            .iter()
+            .filter(|e| e.event_type.is_scheduled())
+            .count();
+
+        let new_actions = result
+            .actions
+            .iter()
+            .flatten()
+            .filter(|a| a.scheduled_event_type().is_some())
+            .skip(replayed);
+
+        for (event_id, action) in (next_id..).zip(new_actions) {
+            let mut event = self.event(action.scheduled_event_type().unwrap());
+            event.event_id = event_id;
+
+            match action {
+                Action::CallActivity {
+                    function_name,
+                    input,
+                }
+                | Action::CallActivityWithRetry {
+                    function_name,
+                    input,
+                    ..
+                } => {
+                    event.name = Some(function_name.clone());
+                    event.input = Some(input.clone());
+                }
+                Action::CallSubOrchestrator {
+                    function_name,
+                    instance_id,
+                    input,
+                }
+                | Action::CallSubOrchestratorWithRetry {
+                    function_name,
+                    instance_id,
+                    input,
+                    ..
+                } => {
+                    event.name = Some(function_name.clone());
+                    event.instance_id = instance_id.clone();
+                    event.input = Some(input.clone());
+                }
+                Action::CallHttp { http_request } => {
+                    event.name = Some(HTTP_ACTIVITY_NAME.to_string());
+                    event.input = Some(to_value(http_request).unwrap());
+                }
+                Action::CreateTimer { fire_at, .. } => {
+                    event.fire_at = Some(*fire_at);
+                }
+                _ => unreachable!(),
+            }
+
+            self.history.push(event);
+        }
+
+        // Scheduled events correspond, in order, to the actions that scheduled them
+        self.canceled_timers = result
+            .actions
+            .iter()
+            .flatten()
+            .filter(|a| a.scheduled_event_type().is_some())
+            .zip(self.history.iter().filter(|e| e.event_type.is_scheduled()))
+            .filter_map(|(action, event)| match action {
+                Action::CreateTimer { canceled: true, .. } => Some(event.event_id),
+                _ => None,
+            })
+            .collect();
+
+        self.history
+            .push(self.event(EventType::OrchestratorCompleted));
+
+        result
+    }
+
+    fn complete(
+        &mut self,
+        scheduled_type: EventType,
+        completed_type: EventType,
+        name: &str,
+        result: Value,
+    ) {
+        let id = self.find_pending(scheduled_type, name);
+
+        let mut event = self.event(completed_type);
+        event.name = Some(name.to_string());
+        event.task_scheduled_id = Some(id);
+        event.result = Some(result.to_string());
+        self.inbound.push(event);
+    }
+
+    fn fail(
+        &mut self,
+        scheduled_type: EventType,
+        failed_type: EventType,
+        name: &str,
+        reason: &str,
+    ) {
+        let id = self.find_pending(scheduled_type, name);
+
+        let mut event = self.event(failed_type);
+        event.name = Some(name.to_string());
+        event.task_scheduled_id = Some(id);
+        event.reason = Some(reason.to_string());
+        self.inbound.push(event);
+    }
+
+    fn find_pending(&self, scheduled_type: EventType, name: &str) -> i32 {
+        self.history
+            .iter()
+            .find(|e| {
+                e.event_type == scheduled_type
+                    && e.name.as_ref().map(String::as_str) == Some(name)
+                    && !self.is_finished(e.event_id)
+            })
+            .map(|e| e.event_id)
+            .unwrap_or_else(|| panic!("there is no pending call to '{}'", name))
+    }
+
+    fn is_finished(&self, event_id: i32) -> bool {
+        self.history
+            .iter()
+            .chain(self.inbound.iter())
+            .any(|e| match e.event_type {
+                EventType::TimerFired => e.timer_id == Some(event_id),
+                EventType::TaskCompleted
+                | EventType::TaskFailed
+                | EventType::SubOrchestrationInstanceCompleted
+                | EventType::SubOrchestrationInstanceFailed => {
+                    e.task_scheduled_id == Some(event_id)
+                }
+                _ => false,
+            })
+    }
+
+    fn event(&self, event_type: EventType) -> HistoryEvent {
+        HistoryEvent {
+            event_type,
+            event_id: -1,
+            is_played: false,
+            timestamp: self.current_time,
+            is_processed: false,
+            name: None,
+            input: None,
+            result: None,
+            task_scheduled_id: None,
+            instance_id: None,
+            reason: None,
+            details: None,
+            fire_at: None,
+            timer_id: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::durable::OrchestrationOutput;
+    use serde_json::json;
+
+    async fn sequence(context: DurableOrchestrationContext) -> OrchestrationOutput {
+        let mut results = Vec::new();
+        for city in &["Tokyo", "Seattle", "London"] {
+            match context.call_activity("E1_SayHello", *city).await {
+                Ok(v) => results.push(v),
+                Err(e) => return format!("failed: {}", e).into(),
+            }
+        }
+        results.into_iter().collect()
+    }
+
+    async fn approval(context: DurableOrchestrationContext) -> OrchestrationOutput {
+        match context.wait_for_event::<bool>("approval").await {
+            Ok(approved) => approved.into(),
+            Err(e) => format!("failed: {}", e).into(),
+        }
+    }
+
+    async fn reminder(context: DurableOrchestrationContext) -> OrchestrationOutput {
+        context
+            .create_timer(context.current_time() + Duration::hours(1))
+            .await;
+        "reminded".into()
+    }
+
+    #[test]
+    fn it_runs_a_scripted_history() {
+        let mut tester = OrchestrationTester::new("sequence", Value::Null);
+
+        let result = tester.run(sequence);
+        assert!(!result.is_done);
+        assert_eq!(
+            result.actions,
+            vec![vec![Action::CallActivity {
+                function_name: "E1_SayHello".to_string(),
+                input: json!("Tokyo"),
+            }]]
+        );
+
+        tester.complete_activity("E1_SayHello", "Hello Tokyo!");
+        let result = tester.run(sequence);
+        assert!(!result.is_done);
+        assert_eq!(result.actions.len(), 2);
+        assert_eq!(
+            result.actions[1],
+            vec![Action::CallActivity {
+                function_name: "E1_SayHello".to_string(),
+                input: json!("Seattle"),
+            }]
+        );
+
+        tester.complete_activity("E1_SayHello", "Hello Seattle!");
+        tester.run(sequence);
+
+        tester.complete_activity("E1_SayHello", "Hello London!");
+        let result = tester.run(sequence);
+        assert!(result.is_done);
+        assert_eq!(
+            result.output,
+            Some(json!(["Hello Tokyo!", "Hello Seattle!", "Hello London!"]))
+        );
+    }
+
+    #[test]
+    fn it_delivers_activity_failures() {
+        let mut tester = OrchestrationTester::new("sequence", Value::Null);
+
+        tester.run(sequence);
+        tester.fail_activity("E1_SayHello", "boom");
+
+        let result = tester.run(sequence);
+        assert!(result.is_done);
+        assert_eq!(result.output, Some(json!("failed: boom")));
+    }
+
+    #[test]
+    fn it_raises_events() {
+        let mut tester = OrchestrationTester::new("approval", Value::Null);
+
+        let result = tester.run(approval);
+        assert!(!result.is_done);
+        assert_eq!(
+            result.actions,
+            vec![vec![Action::WaitForExternalEvent {
+                external_event_name: "approval".to_string()
+            }]]
+        );
+
+        tester.raise_event("approval", true);
+
+        let result = tester.run(approval);
+        assert!(result.is_done);
+        assert_eq!(result.output, Some(json!(true)));
+    }
+
+    #[test]
+    fn it_fires_timers() {
+        let mut tester = OrchestrationTester::new("reminder", Value::Null);
+        let start = tester.current_time();
+
+        let result = tester.run(reminder);
+        assert!(!result.is_done);
+
+        tester.fire_timer();
+        assert_eq!(tester.current_time(), start + Duration::hours(1));
+
+        let result = tester.run(reminder);
+        assert!(result.is_done);
+        assert_eq!(result.output, Some(json!("reminded")));
+    }
+
+    #[test]
+    #[should_panic(expected = "there is no pending call to 'E1_SayHello'")]
+    fn it_panics_when_completing_an_activity_that_is_not_pending() {
+        let mut tester = OrchestrationTester::new("sequence", Value::Null);
+        tester.complete_activity("E1_SayHello", "Hello Tokyo!");
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "non-deterministic orchestration: expected TaskScheduled for 'E1_SayHello' at history position 2, but the orchestration function scheduled TaskScheduled for 'E1_SayGoodbye'"
+    )]
+    fn it_panics_for_non_deterministic_orchestrations() {
+        async fn changed(context: DurableOrchestrationContext) -> OrchestrationOutput {
+            context
+                .call_activity("E1_SayGoodbye", "Tokyo")
+                .await
+                .unwrap()
+                .into()
+        }
+
+        let mut tester = OrchestrationTester::new("sequence", Value::Null);
+        tester.run(sequence);
+        tester.complete_activity("E1_SayHello", "Hello Tokyo!");
+        tester.run(changed);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "non-deterministic orchestration (version 2): expected TaskScheduled for 'E1_SayHello' at history position 2, but the orchestration function scheduled TimerCreated"
+    )]
+    fn it_panics_with_the_version_for_non_deterministic_orchestrations() {
+        async fn changed(context: DurableOrchestrationContext) -> OrchestrationOutput {
+            context
+                .create_timer(context.current_time() + Duration::minutes(1))
+                .await;
+            context.version().unwrap_or_default().into()
+        }
+
+        let mut tester = OrchestrationTester::new("sequence", Value::Null).with_version("2");
+        tester.run(sequence);
+        tester.complete_activity("E1_SayHello", "Hello Tokyo!");
+        tester.run(changed);
+    }
+
+    #[test]
+    fn it_provides_the_version_to_the_orchestration() {
+        async fn versioned(context: DurableOrchestrationContext) -> OrchestrationOutput {
+            context.version().unwrap_or_default().into()
+        }
+
+        let mut tester = OrchestrationTester::new("versioned", Value::Null).with_version("2");
+
+        let result = tester.run(versioned);
+        assert!(result.is_done);
+        assert_eq!(result.output, Some(json!("2")));
+    }
+
+    #[test]
+    fn it_replays_a_recorded_history() {
+        let mut tester = OrchestrationTester::from_json(
+            r#"{
+            "createdTime": "2018-02-28T05:18:49Z",
+            "historyEvents": [
+                {
+                    "EventType": "ExecutionStarted",
+                    "FunctionName": "E1_HelloSequence",
+                    "Timestamp": "2018-02-28T05:18:49.3452372Z"
+                },
+                {
+                    "EventType": "TaskCompleted",
+                    "FunctionName": "E1_SayHello",
+                    "Result": "Hello Tokyo!",
+                    "ScheduledTime": "2018-02-28T05:18:51.3939873Z",
+                    "Timestamp": "2018-02-28T05:18:52.2895622Z"
+                },
+                {
+                    "EventType": "TaskCompleted",
+                    "FunctionName": "E1_SayHello",
+                    "Result": "Hello Seattle!",
+                    "ScheduledTime": "2018-02-28T05:18:52.8755705Z",
+                    "Timestamp": "2018-02-28T05:18:53.1765771Z"
+                },
+                {
+                    "EventType": "TaskCompleted",
+                    "FunctionName": "E1_SayHello",
+                    "Result": "Hello London!",
+                    "ScheduledTime": "2018-02-28T05:18:53.5170791Z",
+                    "Timestamp": "2018-02-28T05:18:53.891081Z"
+                },
+                {
+                    "EventType": "ExecutionCompleted",
+                    "OrchestrationStatus": "Completed",
+                    "Result": [
+                        "Hello Tokyo!",
+                        "Hello Seattle!",
+                        "Hello London!"
+                    ],
+                    "Timestamp": "2018-02-28T05:18:54.3660895Z"
+                }
+            ],
+            "input": null,
+            "customStatus": null,
+            "lastUpdatedTime": "2018-02-28T05:18:54Z",
+            "output": [
+                "Hello Tokyo!",
+                "Hello Seattle!",
+                "Hello London!"
+            ],
+            "runtimeStatus": "Completed"
+            }"#,
+        )
+        .unwrap();
+
+        let result = tester.run(sequence);
+        assert!(result.is_done);
+        assert_eq!(result.actions.len(), 4);
+        assert_eq!(
+            result.output,
+            Some(json!(["Hello Tokyo!", "Hello Seattle!", "Hello London!"]))
+        );
+    }
+}
@@ -0,0 +1,668 @@
+use crate::codegen::{Retry, RetryStrategy};
+use crate::durable::TaskError;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+
+type RetryPredicate = Arc<dyn Fn(&TaskError) -> bool + Send + Sync>;
+
+/// Defines retry policies that can be passed as parameters to various Durable Functions operations.
+///
+/// Retry options are created with `RetryOptions::builder`, which validates the policy when it is built.
+///
+/// Retry options apply to the individual activity and sub-orchestration calls of an orchestration
+/// and are carried out by the Durable Functions extension.
+///
+/// The same options can be used as the function-level retry policy of a timer, Event Hub, or
+/// Cosmos DB triggered function with the `retry_options` argument of the `#[func]` attribute, which
+/// names a function returning the options. The options are converted into the policy applied by
+/// the Azure Functions host (see the `TryFrom<&RetryOptions>` implementation for `codegen::Retry`).
+///
+/// # Examples
+///
+/// ```rust
+/// use azure_functions::durable::{RetryOptions, TaskError};
+/// use std::time::Duration;
+///
+/// let options = RetryOptions::builder(Duration::from_secs(5), 3)
+///     .backoff_coefficient(2.0)
+///     .max_retry_interval(Duration::from_secs(60))
+///     .handle(|e| match e {
+///         TaskError::Failed { reason, .. } => reason.contains("timeout"),
+///         _ => false,
+///     })
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(options.max_attempts(), 3);
+/// assert_eq!(options.retry_delay(2), Duration::from_secs(10));
+/// ```
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryOptions {
+    #[serde(rename = "firstRetryIntervalInMilliseconds")]
+    first_retry_interval_ms: i32,
+
+    #[serde(rename = "maxNumberOfAttempts")]
+    max_attempts: i32,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    backoff_coefficient: Option<f64>,
+
+    #[serde(
+        rename = "maxRetryIntervalInMilliseconds",
+        skip_serializing_if = "Option::is_none"
+    )]
+    max_retry_interval_ms: Option<i32>,
+
+    #[serde(
+        rename = "retryTimeoutInMilliseconds",
+        skip_serializing_if = "Option::is_none"
+    )]
+    retry_timeout_ms: Option<i32>,
+
+    #[serde(skip)]
+    handle: Option<RetryPredicate>,
+}
+
+impl RetryOptions {
+    /// Creates a builder for retry options with the given first retry interval and maximum number of attempts.
+    pub fn builder(first_retry_interval: Duration, max_attempts: u32) -> RetryOptionsBuilder {
+        RetryOptionsBuilder {
+            first_retry_interval,
+            max_attempts,
+            backoff_coefficient: None,
+            max_retry_interval: None,
+            retry_timeout: None,
+            handle: None,
+        }
+    }
+
+    /// Gets the interval to wait before the first retry.
+    pub fn first_retry_interval(&self) -> Duration {
+        Duration::from_millis(self.first_retry_interval_ms as u64)
+    }
+
+    /// Gets the maximum number of attempts, including the first attempt.
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts as u32
+    }
+
+    /// Gets the backoff coefficient used to determine rate of increase of backoff.
+    pub fn backoff_coefficient(&self) -> f64 {
+        self.backoff_coefficient.unwrap_or(1.0)
+    }
+
+    /// Gets the maximum interval to wait between retries.
+    pub fn max_retry_interval(&self) -> Option<Duration> {
+        self.max_retry_interval_ms
+            .map(|ms| Duration::from_millis(ms as u64))
+    }
+
+    /// Gets the timeout for retries.
+    pub fn retry_timeout(&self) -> Option<Duration> {
+        self.retry_timeout_ms
+            .map(|ms| Duration::from_millis(ms as u64))
+    }
+
+    /// Determines if the given failure should be retried.
+    ///
+    /// Every failure is retried unless a `handle` predicate was given.
+    pub fn should_retry(&self, error: &TaskError) -> bool {
+        self.handle.as_ref().map_or(true, |handle| handle(error))
+    }
+
+    /// Gets the delay before the given retry (starting at 1 for the first retry).
+    pub fn retry_delay(&self, retry: u32) -> Duration {
+        let ms = f64::from(self.first_retry_interval_ms)
+            * self
+                .backoff_coefficient()
+                .powi(retry.saturating_sub(1) as i32);
+
+        let ms = match self.max_retry_interval_ms {
+            Some(max) => ms.min(f64::from(max)),
+            None => ms,
+        };
+
+        Duration::from_millis(ms as u64)
+    }
+}
+
+impl Debug for RetryOptions {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.debug_struct("RetryOptions")
+            .field("first_retry_interval_ms", &self.first_retry_interval_ms)
+            .field("max_attempts", &self.max_attempts)
+            .field("backoff_coefficient", &self.backoff_coefficient)
+            .field("max_retry_interval_ms", &self.max_retry_interval_ms)
+            .field("retry_timeout_ms", &self.retry_timeout_ms)
+            .field(
+                "handle",
+                &self.handle.as_ref().map(|_| "Fn(&TaskError) -> bool"),
+            )
+            .finish()
+    }
+}
+
+impl PartialEq for RetryOptions {
+    fn eq(&self, other: &Self) -> bool {
+        // Predicates cannot be compared, so only the serialized options are considered
+        self.first_retry_interval_ms == other.first_retry_interval_ms
+            && self.max_attempts == other.max_attempts
+            && self.backoff_coefficient == other.backoff_coefficient
+            && self.max_retry_interval_ms == other.max_retry_interval_ms
+            && self.retry_timeout_ms == other.retry_timeout_ms
+    }
+}
+
+/// Converts retry options into a function-level retry policy applied by the Azure Functions host.
+///
+/// A backoff coefficient of 1 becomes a fixed delay of the first retry interval. A backoff
+/// coefficient of 2 becomes an exponential backoff from the first retry interval up to the maximum
+/// retry interval, which is then required. The maximum retry count is the maximum number of
+/// attempts less the first attempt.
+///
+/// The host retries every failure without a timeout and with whole-second intervals, so options
+/// with a `handle` predicate, a retry timeout, or sub-second intervals cannot be converted.
+///
+/// # Examples
+///
+/// ```rust
+/// use azure_functions::codegen::{Retry, RetryStrategy};
+/// use azure_functions::durable::RetryOptions;
+/// use std::convert::TryFrom;
+/// use std::time::Duration;
+///
+/// let options = RetryOptions::builder(Duration::from_secs(4), 6)
+///     .backoff_coefficient(2.0)
+///     .max_retry_interval(Duration::from_secs(900))
+///     .build()
+///     .unwrap();
+///
+/// let retry = Retry::try_from(&options).unwrap();
+///
+/// assert_eq!(retry.max_retry_count, 5);
+/// assert_eq!(
+///     retry.strategy,
+///     RetryStrategy::ExponentialBackoff {
+///         minimum_interval: "00:00:04".into(),
+///         maximum_interval: "00:15:00".into(),
+///     }
+/// );
+/// ```
+impl TryFrom<&RetryOptions> for Retry {
+    type Error = FunctionRetryError;
+
+    fn try_from(options: &RetryOptions) -> Result<Self, Self::Error> {
+        if options.handle.is_some() {
+            return Err(FunctionRetryError::UnsupportedHandle);
+        }
+
+        if options.retry_timeout_ms.is_some() {
+            return Err(FunctionRetryError::UnsupportedRetryTimeout);
+        }
+
+        if options.max_attempts < 2 {
+            return Err(FunctionRetryError::NoRetries);
+        }
+
+        let first_retry_interval = to_interval(options.first_retry_interval_ms)?;
+
+        let strategy = match options.backoff_coefficient() {
+            c if c == 1.0 => RetryStrategy::FixedDelay {
+                delay_interval: first_retry_interval,
+            },
+            c if c == 2.0 => RetryStrategy::ExponentialBackoff {
+                minimum_interval: first_retry_interval,
+                maximum_interval: to_interval(
+                    options
+                        .max_retry_interval_ms
+                        .ok_or(FunctionRetryError::MissingMaxRetryInterval)?,
+                )?,
+            },
+            _ => return Err(FunctionRetryError::UnsupportedBackoffCoefficient),
+        };
+
+        Ok(Retry {
+            strategy,
+            max_retry_count: options.max_attempts - 1,
+        })
+    }
+}
+
+/// Represents an error converting `RetryOptions` into a function-level retry policy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FunctionRetryError {
+    /// The options have a `handle` predicate; the host retries every failure.
+    UnsupportedHandle,
+    /// The options have a retry timeout, which the host does not support.
+    UnsupportedRetryTimeout,
+    /// The options allow a single attempt, so there is nothing to retry.
+    NoRetries,
+    /// The backoff coefficient is neither 1 (a fixed delay) nor 2 (an exponential backoff).
+    UnsupportedBackoffCoefficient,
+    /// The backoff is exponential but there is no maximum retry interval.
+    MissingMaxRetryInterval,
+    /// An interval is not a whole number of seconds.
+    UnsupportedInterval,
+}
+
+impl Display for FunctionRetryError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedHandle => write!(
+                f,
+                "a retry predicate is not supported for a function retry policy"
+            ),
+            Self::UnsupportedRetryTimeout => write!(
+                f,
+                "a retry timeout is not supported for a function retry policy"
+            ),
+            Self::NoRetries => write!(
+                f,
+                "the maximum number of attempts must be at least 2 for a function retry policy"
+            ),
+            Self::UnsupportedBackoffCoefficient => write!(
+                f,
+                "the backoff coefficient must be 1 or 2 for a function retry policy"
+            ),
+            Self::MissingMaxRetryInterval => write!(
+                f,
+                "a maximum retry interval is required for an exponential backoff"
+            ),
+            Self::UnsupportedInterval => write!(
+                f,
+                "the retry intervals must be whole seconds for a function retry policy"
+            ),
+        }
+    }
+}
+
+impl Error for FunctionRetryError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+// Formats an interval in the `[d.]hh:mm:ss` format used by the Azure Functions host
+fn to_interval(ms: i32) -> Result<Cow<'static, str>, FunctionRetryError> {
+    if ms % 1000 != 0 {
+        return Err(FunctionRetryError::UnsupportedInterval);
+    }
+
+    let secs = ms / 1000;
+    let (days, hours, minutes, seconds) =
+        (secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+
+    Ok(Cow::from(if days > 0 {
+        format!("{}.{:02}:{:02}:{:02}", days, hours, minutes, seconds)
+    } else {
+        format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+    }))
+}
+
+/// Builds validated `RetryOptions`.
+pub struct RetryOptionsBuilder {
+    first_retry_interval: Duration,
+    max_attempts: u32,
+    backoff_coefficient: Option<f64>,
+    max_retry_interval: Option<Duration>,
+    retry_timeout: Option<Duration>,
+    handle: Option<RetryPredicate>,
+}
+
+impl RetryOptionsBuilder {
+    /// Sets the backoff coefficient used to determine rate of increase of backoff. Defaults to 1.
+    pub fn backoff_coefficient(mut self, coefficient: f64) -> Self {
+        self.backoff_coefficient = Some(coefficient);
+        self
+    }
+
+    /// Sets the maximum interval to wait between retries.
+    pub fn max_retry_interval(mut self, interval: Duration) -> Self {
+        self.max_retry_interval = Some(interval);
+        self
+    }
+
+    /// Sets the timeout for retries.
+    pub fn retry_timeout(mut self, timeout: Duration) -> Self {
+        self.retry_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets a predicate that determines which failures are retried.
+    ///
+    /// The predicate is evaluated by the orchestration, so it is only honored by
+    /// `DurableOrchestrationContext::call_with_retry`.  The Durable Functions extension
+    /// retries every failure of calls made with the `*_with_retry` functions.
+    pub fn handle<F>(mut self, handle: F) -> Self
+    where
+        F: Fn(&TaskError) -> bool + Send + Sync + 'static,
+    {
+        self.handle = Some(Arc::new(handle));
+        self
+    }
+
+    /// Builds the retry options.
+    ///
+    /// Returns an error if the first retry interval is not positive, if the maximum number of attempts is zero,
+    /// if the backoff coefficient is less than 1, if the maximum retry interval is less than the first
+    /// retry interval, or if the retry timeout is not positive.
+    pub fn build(self) -> Result<RetryOptions, RetryOptionsError> {
+        let first_retry_interval_ms = to_millis(self.first_retry_interval)
+            .filter(|ms| *ms > 0)
+            .ok_or(RetryOptionsError::InvalidFirstRetryInterval)?;
+
+        if self.max_attempts == 0 || self.max_attempts > i32::MAX as u32 {
+            return Err(RetryOptionsError::InvalidMaxAttempts);
+        }
+
+        if let Some(coefficient) = self.backoff_coefficient {
+            if !coefficient.is_finite() || coefficient < 1.0 {
+                return Err(RetryOptionsError::InvalidBackoffCoefficient);
+            }
+        }
+
+        let max_retry_interval_ms = match self.max_retry_interval {
+            Some(interval) => Some(
+                to_millis(interval)
+                    .filter(|ms| *ms >= first_retry_interval_ms)
+                    .ok_or(RetryOptionsError::InvalidMaxRetryInterval)?,
+            ),
+            None => None,
+        };
+
+        let retry_timeout_ms = match self.retry_timeout {
+            Some(timeout) => Some(
+                to_millis(timeout)
+                    .filter(|ms| *ms > 0)
+                    .ok_or(RetryOptionsError::InvalidRetryTimeout)?,
+            ),
+            None => None,
+        };
+
+        Ok(RetryOptions {
+            first_retry_interval_ms,
+            max_attempts: self.max_attempts as i32,
+            backoff_coefficient: self.backoff_coefficient,
+            max_retry_interval_ms,
+            retry_timeout_ms,
+            handle: self.handle,
+        })
+    }
+}
+
+/// Represents an error building `RetryOptions`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RetryOptionsError {
+    /// The first retry interval is zero or too large.
+    InvalidFirstRetryInterval,
+    /// The maximum number of attempts is zero or too large.
+    InvalidMaxAttempts,
+    /// The backoff coefficient is less than 1.
+    InvalidBackoffCoefficient,
+    /// The maximum retry interval is less than the first retry interval or too large.
+    InvalidMaxRetryInterval,
+    /// The retry timeout is zero or too large.
+    InvalidRetryTimeout,
+}
+
+impl Display for RetryOptionsError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Self::InvalidFirstRetryInterval => write!(
+                f,
+                "the first retry interval must be greater than zero and less than {} milliseconds",
+                i32::MAX
+            ),
+            Self::InvalidMaxAttempts => write!(
+                f,
+                "the maximum number of attempts must be greater than zero and less than {}",
+                i32::MAX
+            ),
+            Self::InvalidBackoffCoefficient => {
+                write!(f, "the backoff coefficient must be at least 1")
+            }
+            Self::InvalidMaxRetryInterval => write!(
+                f,
+                "the maximum retry interval must be at least the first retry interval and less than {} milliseconds",
+                i32::MAX
+            ),
+            Self::InvalidRetryTimeout => write!(
+                f,
+                "the retry timeout must be greater than zero and less than {} milliseconds",
+                i32::MAX
+            ),
+        }
+    }
+}
+
+impl Error for RetryOptionsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+fn to_millis(duration: Duration) -> Option<i32> {
+    let ms = duration.as_millis();
+    if ms > i32::MAX as u128 {
+        return None;
+    }
+    Some(ms as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::to_string;
+
+    #[test]
+    fn it_builds_retry_options() {
+        let options = RetryOptions::builder(Duration::from_secs(1), 3)
+            .backoff_coefficient(2.0)
+            .max_retry_interval(Duration::from_secs(3))
+            .retry_timeout(Duration::from_secs(60))
+            .build()
+            .unwrap();
+
+        assert_eq!(options.first_retry_interval(), Duration::from_secs(1));
+        assert_eq!(options.max_attempts(), 3);
+        assert_eq!(options.backoff_coefficient(), 2.0);
+        assert_eq!(options.max_retry_interval(), Some(Duration::from_secs(3)));
+        assert_eq!(options.retry_timeout(), Some(Duration::from_secs(60)));
+        assert_eq!(
+            to_string(&options).unwrap(),
+            r#"{"firstRetryIntervalInMilliseconds":1000,"maxNumberOfAttempts":3,"backoffCoefficient":2.0,"maxRetryIntervalInMilliseconds":3000,"retryTimeoutInMilliseconds":60000}"#
+        );
+    }
+
+    #[test]
+    fn it_validates_retry_options() {
+        let first = Duration::from_secs(1);
+
+        assert_eq!(
+            RetryOptions::builder(Duration::from_secs(0), 3)
+                .build()
+                .unwrap_err(),
+            RetryOptionsError::InvalidFirstRetryInterval
+        );
+        assert_eq!(
+            RetryOptions::builder(Duration::from_secs(30 * 24 * 60 * 60), 3)
+                .build()
+                .unwrap_err(),
+            RetryOptionsError::InvalidFirstRetryInterval
+        );
+        assert_eq!(
+            RetryOptions::builder(first, 0).build().unwrap_err(),
+            RetryOptionsError::InvalidMaxAttempts
+        );
+        assert_eq!(
+            RetryOptions::builder(first, 3)
+                .backoff_coefficient(0.5)
+                .build()
+                .unwrap_err(),
+            RetryOptionsError::InvalidBackoffCoefficient
+        );
+        assert_eq!(
+            RetryOptions::builder(first, 3)
+                .backoff_coefficient(f64::NAN)
+                .build()
+                .unwrap_err(),
+            RetryOptionsError::InvalidBackoffCoefficient
+        );
+        assert_eq!(
+            RetryOptions::builder(first, 3)
+                .max_retry_interval(Duration::from_millis(500))
+                .build()
+                .unwrap_err(),
+            RetryOptionsError::InvalidMaxRetryInterval
+        );
+        assert_eq!(
+            RetryOptions::builder(first, 3)
+                .retry_timeout(Duration::from_secs(0))
+                .build()
+                .unwrap_err(),
+            RetryOptionsError::InvalidRetryTimeout
+        );
+    }
+
+    #[test]
+    fn it_calculates_retry_delays() {
+        let options = RetryOptions::builder(Duration::from_secs(1), 5)
+            .backoff_coefficient(2.0)
+            .max_retry_interval(Duration::from_secs(5))
+            .build()
+            .unwrap();
+
+        assert_eq!(options.retry_delay(1), Duration::from_secs(1));
+        assert_eq!(options.retry_delay(2), Duration::from_secs(2));
+        assert_eq!(options.retry_delay(3), Duration::from_secs(4));
+        assert_eq!(options.retry_delay(4), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn it_uses_the_handle_predicate() {
+        let options = RetryOptions::builder(Duration::from_secs(1), 3)
+            .handle(|e| match e {
+                TaskError::Failed { reason, .. } => reason == "transient",
+                _ => false,
+            })
+            .build()
+            .unwrap();
+
+        assert!(options.should_retry(&TaskError::Failed {
+            reason: "transient".to_string(),
+            details: None,
+        }));
+        assert!(!options.should_retry(&TaskError::Failed {
+            reason: "fatal".to_string(),
+            details: None,
+        }));
+        assert!(!options.should_retry(&TaskError::InvalidOutput("bad".to_string())));
+
+        let options = RetryOptions::builder(Duration::from_secs(1), 3)
+            .build()
+            .unwrap();
+        assert!(options.should_retry(&TaskError::InvalidOutput("bad".to_string())));
+    }
+
+    #[test]
+    fn it_uses_a_capturing_handle_predicate() {
+        let transient = ["timeout".to_string(), "throttled".to_string()];
+
+        let options = RetryOptions::builder(Duration::from_secs(1), 3)
+            .handle(move |e| match e {
+                TaskError::Failed { reason, .. } => transient.contains(reason),
+                _ => false,
+            })
+            .build()
+            .unwrap();
+
+        let cloned = options.clone();
+        assert!(cloned.should_retry(&TaskError::Failed {
+            reason: "throttled".to_string(),
+            details: None,
+        }));
+        assert!(!options.should_retry(&TaskError::Failed {
+            reason: "fatal".to_string(),
+            details: None,
+        }));
+    }
+
+    #[test]
+    fn it_converts_to_a_fixed_delay_retry_policy() {
+        let options = RetryOptions::builder(Duration::from_secs(90), 4)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            Retry::try_from(&options).unwrap(),
+            Retry {
+                strategy: RetryStrategy::FixedDelay {
+                    delay_interval: Cow::from("00:01:30"),
+                },
+                max_retry_count: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn it_converts_to_an_exponential_backoff_retry_policy() {
+        let options = RetryOptions::builder(Duration::from_secs(1), 2)
+            .backoff_coefficient(2.0)
+            .max_retry_interval(Duration::from_secs(90_061))
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            Retry::try_from(&options).unwrap(),
+            Retry {
+                strategy: RetryStrategy::ExponentialBackoff {
+                    minimum_interval: Cow::from("00:00:01"),
+                    maximum_interval: Cow::from("1.01:01:01"),
+                },
+                max_retry_count: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn it_rejects_options_unsupported_by_a_retry_policy() {
+        let builder = || RetryOptions::builder(Duration::from_secs(1), 3);
+
+        let cases = [
+            (
+                builder().handle(|_| true).build(),
+                FunctionRetryError::UnsupportedHandle,
+            ),
+            (
+                builder().retry_timeout(Duration::from_secs(60)).build(),
+                FunctionRetryError::UnsupportedRetryTimeout,
+            ),
+            (
+                RetryOptions::builder(Duration::from_secs(1), 1).build(),
+                FunctionRetryError::NoRetries,
+            ),
+            (
+                builder().backoff_coefficient(1.5).build(),
+                FunctionRetryError::UnsupportedBackoffCoefficient,
+            ),
+            (
+                builder().backoff_coefficient(2.0).build(),
+                FunctionRetryError::MissingMaxRetryInterval,
+            ),
+            (
+                RetryOptions::builder(Duration::from_millis(1500), 3).build(),
+                FunctionRetryError::UnsupportedInterval,
+            ),
+        ];
+
+        for (options, error) in cases.iter() {
+            assert_eq!(Retry::try_from(options.as_ref().unwrap()), Err(*error));
+        }
+    }
+}
@@ -9,12 +9,16 @@ use std::{
 };
 
 /// Future for the `DurableOrchestrationContext::select_all` function.
+///
+/// The remaining futures are canceled when the select completes so that durable timers that lost
+/// the select do not keep the orchestration from completing; use `keep_remaining` to await them.
 #[must_use = "futures do nothing unless you `.await` or poll them"]
 pub struct SelectAll<F> {
     inner: Vec<F>,
     state: Rc<RefCell<OrchestrationState>>,
     event_index: Option<usize>,
     is_inner: bool,
+    cancel_remaining: bool,
 }
 
 impl<F> SelectAll<F>
@@ -44,8 +48,18 @@ where
             state,
             event_index,
             is_inner: false,
+            cancel_remaining: true,
         }
     }
+
+    /// Keeps the remaining futures running when the select completes.
+    ///
+    /// Remaining durable timers must then be awaited or canceled with `OrchestrationFuture::cancel`
+    /// for the orchestration to complete without waiting on them.
+    pub fn keep_remaining(mut self) -> Self {
+        self.cancel_remaining = false;
+        self
+    }
 }
 
 impl<F> Unpin for SelectAll<F> where F: Unpin {}
@@ -75,7 +89,14 @@ where
         match item {
             Some((idx, res)) => {
                 self.inner.remove(idx);
-                let rest = std::mem::replace(&mut self.inner, Vec::new());
+                let mut rest = std::mem::replace(&mut self.inner, Vec::new());
+
+                // Cancel the futures that lost the select (e.g. durable timers)
+                if self.cancel_remaining {
+                    for f in rest.iter_mut() {
+                        f.cancel();
+                    }
+                }
 
                 if !self.is_inner {
                     self.state.borrow_mut().update(event_index.unwrap());
@@ -99,6 +120,12 @@ where
     fn event_index(&self) -> Option<usize> {
         self.event_index
     }
+
+    fn cancel(&mut self) {
+        for f in self.inner.iter_mut() {
+            f.cancel();
+        }
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,56 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// Represents an error from a durable task, such as a call to an activity or sub-orchestration function.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaskError {
+    /// The called function failed.
+    Failed {
+        /// The reason the function failed.
+        reason: String,
+        /// The details of the failure.
+        details: Option<String>,
+    },
+    /// The output of the task could not be deserialized.
+    InvalidOutput(String),
+}
+
+impl Display for TaskError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Self::Failed { reason, .. } => write!(f, "{}", reason),
+            Self::InvalidOutput(msg) => write!(f, "failed to deserialize output: {}", msg),
+        }
+    }
+}
+
+impl Error for TaskError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_displays_the_failure_reason() {
+        let error = TaskError::Failed {
+            reason: "boom".to_string(),
+            details: Some("stack".to_string()),
+        };
+
+        assert_eq!(error.to_string(), "boom");
+    }
+
+    #[test]
+    fn it_displays_invalid_output() {
+        let error = TaskError::InvalidOutput("expected a string".to_string());
+
+        assert_eq!(
+            error.to_string(),
+            "failed to deserialize output: expected a string"
+        );
+    }
+}
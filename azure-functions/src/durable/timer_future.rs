@@ -0,0 +1,102 @@
+use crate::durable::{ActionFuture, OrchestrationFuture, OrchestrationState};
+use futures::future::FutureExt;
+use std::{
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+/// Future returned by the `DurableOrchestrationContext::create_timer` function.
+///
+/// A timer that loses a `select_all` is canceled automatically unless the select is created with
+/// `SelectAll::keep_remaining`.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct TimerFuture {
+    inner: ActionFuture<()>,
+    state: Rc<RefCell<OrchestrationState>>,
+    action: (usize, usize),
+}
+
+impl TimerFuture {
+    pub(crate) fn new(
+        inner: ActionFuture<()>,
+        state: Rc<RefCell<OrchestrationState>>,
+        action: (usize, usize),
+    ) -> Self {
+        TimerFuture {
+            inner,
+            state,
+            action,
+        }
+    }
+
+    /// Cancels the durable timer.
+    ///
+    /// A canceled timer does not keep the orchestration from completing.
+    pub fn cancel(&self) {
+        self.state.borrow_mut().cancel_timer(self.action);
+    }
+}
+
+impl Future for TimerFuture {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<()> {
+        self.inner.poll_unpin(context)
+    }
+}
+
+impl OrchestrationFuture for TimerFuture {
+    fn notify_inner(&mut self) {
+        self.inner.notify_inner();
+    }
+
+    fn event_index(&self) -> Option<usize> {
+        self.inner.event_index()
+    }
+
+    fn cancel(&mut self) {
+        TimerFuture::cancel(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::durable::{tests::create_event, Action, EventType};
+    use chrono::Utc;
+
+    #[test]
+    fn it_cancels_the_timer_action() {
+        let state = Rc::new(RefCell::new(OrchestrationState::new(vec![create_event(
+            EventType::OrchestratorStarted,
+            -1,
+            None,
+            None,
+            None,
+        )])));
+
+        let fire_at = Utc::now();
+        let action = state.borrow_mut().push_action(Action::CreateTimer {
+            fire_at,
+            canceled: false,
+        });
+
+        let timer = TimerFuture::new(
+            ActionFuture::new(None, state.clone(), None),
+            state.clone(),
+            action,
+        );
+        timer.cancel();
+
+        assert_eq!(
+            state.borrow().execution_result().actions,
+            vec![vec![Action::CreateTimer {
+                fire_at,
+                canceled: true
+            }]]
+        );
+    }
+}
@@ -1,8 +1,27 @@
 //! Module for HTTP types.
 mod body;
+mod claims;
+mod compression;
+mod cookie;
+pub(crate) mod cors;
+mod extract_error;
+pub(crate) mod multipart;
+pub(crate) mod negotiation;
+mod problem;
 mod response_builder;
+mod router;
+mod static_files;
 mod status;
 
 pub use self::body::*;
+pub use self::claims::*;
+pub use self::compression::*;
+pub use self::cookie::*;
+pub use self::cors::Cors;
+pub use self::extract_error::*;
+pub use self::multipart::*;
+pub use self::problem::*;
 pub use self::response_builder::*;
+pub use self::router::*;
+pub use self::static_files::*;
 pub use self::status::*;
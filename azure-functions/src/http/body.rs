@@ -1,4 +1,7 @@
-use crate::rpc::{typed_data::Data, TypedData};
+use crate::{
+    http::{multipart, MultipartError, MultipartLimits, Part},
+    rpc::{typed_data::Data, TypedData},
+};
 use serde::{de::Error, Deserialize};
 use serde_json::{from_str, Result, Value};
 use std::borrow::Cow;
@@ -117,6 +120,30 @@ impl Body<'_> {
             })?),
         }
     }
+
+    /// Parses the body as `multipart/form-data` with the given boundary.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::http::{Body, MultipartLimits};
+    /// use std::borrow::Cow;
+    ///
+    /// let body = Body::Bytes(Cow::Borrowed(
+    ///     b"--boundary\r\nContent-Disposition: form-data; name=\"greeting\"\r\n\r\nhello\r\n--boundary--",
+    /// ));
+    ///
+    /// let parts = body.multipart("boundary", &MultipartLimits::default()).unwrap();
+    /// assert_eq!(parts[0].name, "greeting");
+    /// assert_eq!(parts[0].as_str().unwrap(), "hello");
+    /// ```
+    pub fn multipart(
+        &self,
+        boundary: &str,
+        limits: &MultipartLimits,
+    ) -> std::result::Result<Vec<Part>, MultipartError> {
+        multipart::parse(self.as_bytes(), boundary, limits)
+    }
 }
 
 impl fmt::Display for Body<'_> {
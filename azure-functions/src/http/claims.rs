@@ -0,0 +1,288 @@
+use crate::rpc::{nullable_string, NullableString, RpcClaimsIdentity};
+use serde::Deserialize;
+use serde_json::from_slice;
+
+/// The default claim type of an identity's name.
+pub const NAME_CLAIM_TYPE: &str = "http://schemas.xmlsoap.org/ws/2005/05/identity/claims/name";
+
+/// The default claim type of an identity's roles.
+pub const ROLE_CLAIM_TYPE: &str = "http://schemas.microsoft.com/ws/2008/06/identity/claims/role";
+
+/// Represents a claim about an authenticated identity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Claim {
+    /// The type of the claim (e.g. `http://schemas.xmlsoap.org/ws/2005/05/identity/claims/name`).
+    pub claim_type: String,
+    /// The value of the claim.
+    pub value: String,
+}
+
+/// Represents an identity of a HTTP request's principal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClaimsIdentity {
+    /// The type of authentication used to authenticate the identity (e.g. `aad`).
+    pub authentication_type: Option<String>,
+    /// The claim type used for the name of the identity.
+    pub name_claim_type: String,
+    /// The claim type used for the roles of the identity.
+    pub role_claim_type: String,
+    /// The claims of the identity.
+    pub claims: Vec<Claim>,
+}
+
+impl ClaimsIdentity {
+    /// Determines if the identity has been authenticated.
+    pub fn is_authenticated(&self) -> bool {
+        self.authentication_type
+            .as_ref()
+            .map_or(false, |t| !t.is_empty())
+    }
+
+    /// Gets the name of the identity.
+    pub fn name(&self) -> Option<&str> {
+        self.claims
+            .iter()
+            .find(|c| c.claim_type == self.name_claim_type)
+            .map(|c| c.value.as_str())
+    }
+
+    /// Determines if the identity has the given role.
+    pub fn is_in_role(&self, role: &str) -> bool {
+        self.claims
+            .iter()
+            .any(|c| c.claim_type == self.role_claim_type && c.value == role)
+    }
+
+    /// Parses an identity from the base64-encoded JSON of an App Service Authentication
+    /// `x-ms-client-principal` header.
+    pub(crate) fn from_client_principal(header: &str) -> Option<Self> {
+        #[derive(Deserialize)]
+        struct ClientClaim {
+            typ: String,
+            val: String,
+        }
+
+        #[derive(Deserialize)]
+        struct ClientPrincipal {
+            auth_typ: Option<String>,
+            name_typ: Option<String>,
+            role_typ: Option<String>,
+            #[serde(default)]
+            claims: Vec<ClientClaim>,
+        }
+
+        let principal: ClientPrincipal = from_slice(&base64::decode(header.trim()).ok()?).ok()?;
+
+        Some(ClaimsIdentity {
+            authentication_type: principal.auth_typ,
+            name_claim_type: principal
+                .name_typ
+                .unwrap_or_else(|| NAME_CLAIM_TYPE.to_string()),
+            role_claim_type: principal
+                .role_typ
+                .unwrap_or_else(|| ROLE_CLAIM_TYPE.to_string()),
+            claims: principal
+                .claims
+                .into_iter()
+                .map(|c| Claim {
+                    claim_type: c.typ,
+                    value: c.val,
+                })
+                .collect(),
+        })
+    }
+}
+
+fn nullable_string_value(s: &Option<NullableString>) -> Option<String> {
+    s.as_ref().and_then(|s| {
+        s.string
+            .as_ref()
+            .map(|nullable_string::String::Value(s)| s.clone())
+    })
+}
+
+impl From<&RpcClaimsIdentity> for ClaimsIdentity {
+    fn from(identity: &RpcClaimsIdentity) -> Self {
+        ClaimsIdentity {
+            authentication_type: nullable_string_value(&identity.authentication_type),
+            name_claim_type: nullable_string_value(&identity.name_claim_type)
+                .unwrap_or_else(|| NAME_CLAIM_TYPE.to_string()),
+            role_claim_type: nullable_string_value(&identity.role_claim_type)
+                .unwrap_or_else(|| ROLE_CLAIM_TYPE.to_string()),
+            claims: identity
+                .claims
+                .iter()
+                .map(|c| Claim {
+                    claim_type: c.r#type.clone(),
+                    value: c.value.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Represents the principal associated with a HTTP request.
+///
+/// A principal has an identity for each way the request was authenticated, such as
+/// App Service Authentication or a function key.
+///
+/// # Examples
+///
+/// ```rust
+/// use azure_functions::bindings::{HttpRequest, HttpResponse};
+/// use azure_functions::func;
+/// use azure_functions::http::Status;
+///
+/// #[func]
+/// pub fn whoami(request: HttpRequest) -> HttpResponse {
+///     let principal = request.principal();
+///
+///     match principal.name() {
+///         Some(name) => format!("Hello, {}!", name).into(),
+///         None => HttpResponse::build().status(Status::Unauthorized).finish(),
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClaimsPrincipal {
+    identities: Vec<ClaimsIdentity>,
+}
+
+impl ClaimsPrincipal {
+    /// Creates a new principal with the given identities.
+    pub fn new(identities: Vec<ClaimsIdentity>) -> Self {
+        ClaimsPrincipal { identities }
+    }
+
+    /// Gets the identities of the principal.
+    pub fn identities(&self) -> &[ClaimsIdentity] {
+        &self.identities
+    }
+
+    /// Gets the primary identity of the principal.
+    ///
+    /// The primary identity is the first authenticated identity.
+    pub fn identity(&self) -> Option<&ClaimsIdentity> {
+        self.identities.iter().find(|i| i.is_authenticated())
+    }
+
+    /// Determines if any identity of the principal has been authenticated.
+    pub fn is_authenticated(&self) -> bool {
+        self.identity().is_some()
+    }
+
+    /// Gets the name of the primary identity.
+    pub fn name(&self) -> Option<&str> {
+        self.identity().and_then(ClaimsIdentity::name)
+    }
+
+    /// Iterates over the claims of every identity of the principal.
+    pub fn claims(&self) -> impl Iterator<Item = &Claim> {
+        self.identities.iter().flat_map(|i| i.claims.iter())
+    }
+
+    /// Iterates over the claims of the given type.
+    pub fn claims_of_type<'a>(&'a self, claim_type: &'a str) -> impl Iterator<Item = &'a Claim> {
+        self.claims().filter(move |c| c.claim_type == claim_type)
+    }
+
+    /// Finds the first claim of the given type.
+    pub fn find_first(&self, claim_type: &str) -> Option<&Claim> {
+        self.claims().find(|c| c.claim_type == claim_type)
+    }
+
+    /// Determines if any authenticated identity of the principal has the given role.
+    pub fn is_in_role(&self, role: &str) -> bool {
+        self.identities
+            .iter()
+            .any(|i| i.is_authenticated() && i.is_in_role(role))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::RpcClaim;
+
+    fn nullable(s: &str) -> Option<NullableString> {
+        Some(NullableString {
+            string: Some(nullable_string::String::Value(s.to_string())),
+        })
+    }
+
+    #[test]
+    fn it_converts_from_a_rpc_identity() {
+        let identity: ClaimsIdentity = (&RpcClaimsIdentity {
+            authentication_type: nullable("aad"),
+            name_claim_type: nullable("name"),
+            role_claim_type: None,
+            claims: vec![
+                RpcClaim {
+                    r#type: "name".to_string(),
+                    value: "Peter".to_string(),
+                },
+                RpcClaim {
+                    r#type: ROLE_CLAIM_TYPE.to_string(),
+                    value: "admin".to_string(),
+                },
+            ],
+        })
+            .into();
+
+        assert!(identity.is_authenticated());
+        assert_eq!(identity.name(), Some("Peter"));
+        assert_eq!(identity.role_claim_type, ROLE_CLAIM_TYPE);
+        assert!(identity.is_in_role("admin"));
+        assert!(!identity.is_in_role("ops"));
+    }
+
+    #[test]
+    fn it_parses_a_client_principal() {
+        let header = base64::encode(
+            r#"{"auth_typ":"aad","claims":[{"typ":"name","val":"Peter"},{"typ":"roles","val":"ops"}],"name_typ":"name","role_typ":"roles"}"#,
+        );
+
+        let principal =
+            ClaimsPrincipal::new(vec![ClaimsIdentity::from_client_principal(&header).unwrap()]);
+
+        assert!(principal.is_authenticated());
+        assert_eq!(principal.name(), Some("Peter"));
+        assert!(principal.is_in_role("ops"));
+        assert_eq!(principal.find_first("roles").unwrap().value, "ops");
+        assert_eq!(principal.claims_of_type("name").count(), 1);
+    }
+
+    #[test]
+    fn it_rejects_an_invalid_client_principal() {
+        assert_eq!(ClaimsIdentity::from_client_principal("not base64!"), None);
+        assert_eq!(
+            ClaimsIdentity::from_client_principal(&base64::encode("not json")),
+            None
+        );
+    }
+
+    #[test]
+    fn it_is_unauthenticated_without_identities() {
+        let principal = ClaimsPrincipal::default();
+
+        assert!(!principal.is_authenticated());
+        assert_eq!(principal.name(), None);
+        assert!(!principal.is_in_role("admin"));
+    }
+
+    #[test]
+    fn it_ignores_roles_of_unauthenticated_identities() {
+        let principal = ClaimsPrincipal::new(vec![ClaimsIdentity {
+            authentication_type: None,
+            name_claim_type: NAME_CLAIM_TYPE.to_string(),
+            role_claim_type: ROLE_CLAIM_TYPE.to_string(),
+            claims: vec![Claim {
+                claim_type: ROLE_CLAIM_TYPE.to_string(),
+                value: "admin".to_string(),
+            }],
+        }]);
+
+        assert!(!principal.is_authenticated());
+        assert!(!principal.is_in_role("admin"));
+    }
+}
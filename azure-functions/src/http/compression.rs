@@ -0,0 +1,419 @@
+use crate::{
+    bindings::{append_vary, HttpRequest, HttpResponse},
+    http::{negotiation::parse_quality_values, Body, Middleware, Next, Status},
+};
+use flate2::{
+    write::{GzEncoder, ZlibEncoder},
+    Compression as Level,
+};
+use std::io::{self, Write};
+
+const DEFAULT_MIN_SIZE: usize = 1024;
+const BROTLI_BUFFER_SIZE: usize = 4096;
+const BROTLI_QUALITY: u32 = 5;
+const BROTLI_WINDOW_SIZE: u32 = 22;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Encoding {
+    Brotli,
+    Gzip,
+    Deflate,
+}
+
+impl Encoding {
+    fn name(self) -> &'static str {
+        match self {
+            Encoding::Brotli => "br",
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+        }
+    }
+
+    fn encode(self, data: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Encoding::Brotli => {
+                let mut writer = brotli::CompressorWriter::new(
+                    Vec::new(),
+                    BROTLI_BUFFER_SIZE,
+                    BROTLI_QUALITY,
+                    BROTLI_WINDOW_SIZE,
+                );
+                writer.write_all(data)?;
+                Ok(writer.into_inner())
+            }
+            Encoding::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Level::default());
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+            Encoding::Deflate => {
+                let mut encoder = ZlibEncoder::new(Vec::new(), Level::default());
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+        }
+    }
+}
+
+/// Represents opt-in compression of HTTP response bodies.
+///
+/// The encoding is selected from the request's `Accept-Encoding` header, preferring
+/// `br`, then `gzip`, then `deflate`. Responses are only compressed if the body is at
+/// least the minimum size (1 KiB by default) and has a textual content type such as
+/// `application/json` or `text/plain`.
+///
+/// Compression can also be used as [Router](struct.Router.html) middleware.
+///
+/// # Examples
+///
+/// ```rust
+/// use azure_functions::bindings::{HttpRequest, HttpResponse};
+/// use azure_functions::func;
+/// use azure_functions::http::Compression;
+/// use serde_json::json;
+///
+/// #[func]
+/// pub fn items(request: HttpRequest) -> HttpResponse {
+///     let response = json!({ "items": vec!["item"; 1000] }).into();
+///
+///     Compression::new().compress(&request, response)
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Compression {
+    min_size: usize,
+    encodings: Vec<Encoding>,
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Compression {
+            min_size: DEFAULT_MIN_SIZE,
+            encodings: vec![Encoding::Brotli, Encoding::Gzip, Encoding::Deflate],
+        }
+    }
+}
+
+impl Compression {
+    /// Creates a new compression configuration with all encodings enabled.
+    pub fn new() -> Self {
+        Compression::default()
+    }
+
+    /// Sets the minimum size, in bytes, of a response body to compress.
+    pub fn min_size(mut self, min_size: usize) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    /// Sets whether or not the `br` (Brotli) encoding is enabled.
+    pub fn brotli(self, enabled: bool) -> Self {
+        self.set_encoding(Encoding::Brotli, enabled)
+    }
+
+    /// Sets whether or not the `gzip` encoding is enabled.
+    pub fn gzip(self, enabled: bool) -> Self {
+        self.set_encoding(Encoding::Gzip, enabled)
+    }
+
+    /// Sets whether or not the `deflate` encoding is enabled.
+    pub fn deflate(self, enabled: bool) -> Self {
+        self.set_encoding(Encoding::Deflate, enabled)
+    }
+
+    /// Compresses the body of a response to the given request.
+    ///
+    /// The response is returned unchanged if it should not be compressed.
+    pub fn compress(&self, request: &HttpRequest, response: HttpResponse) -> HttpResponse {
+        self.compress_with(
+            request.headers().get("accept-encoding").map(String::as_str),
+            response,
+        )
+    }
+
+    fn set_encoding(mut self, encoding: Encoding, enabled: bool) -> Self {
+        self.encodings.retain(|e| *e != encoding);
+        if enabled {
+            self.encodings.push(encoding);
+            self.encodings.sort_by_key(|e| *e as u8);
+        }
+        self
+    }
+
+    fn select_encoding(&self, accept_encoding: &str) -> Option<Encoding> {
+        let codings = parse_quality_values(accept_encoding);
+
+        let mut selected = None;
+        let mut best = 0.0;
+
+        for encoding in &self.encodings {
+            let quality = [encoding.name(), "*"]
+                .iter()
+                .find_map(|name| codings.iter().find(|(c, _)| c == name).map(|(_, q)| *q))
+                .unwrap_or(0.0);
+
+            if quality > best {
+                selected = Some(*encoding);
+                best = quality;
+            }
+        }
+
+        selected
+    }
+
+    fn compress_with(
+        &self,
+        accept_encoding: Option<&str>,
+        mut response: HttpResponse,
+    ) -> HttpResponse {
+        if response.status == Status::NoContent || response.status == Status::NotModified {
+            return response;
+        }
+
+        let headers = response.headers();
+        if headers
+            .keys()
+            .any(|k| k.eq_ignore_ascii_case("content-encoding"))
+        {
+            return response;
+        }
+
+        let is_compressible = headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case("content-type"))
+            .map_or(false, |(_, v)| is_compressible(v));
+
+        if !is_compressible {
+            return response;
+        }
+
+        // The representation depends on Accept-Encoding whether or not this particular
+        // response ends up compressed, so caches must always key on it.
+        append_vary(&mut response.data.headers, "Accept-Encoding");
+
+        let encoding = match accept_encoding.and_then(|a| self.select_encoding(a)) {
+            Some(encoding) => encoding,
+            None => return response,
+        };
+
+        let compressed = {
+            let body = response.body();
+            let data = body.as_bytes();
+            if data.len() >= self.min_size {
+                encoding
+                    .encode(data)
+                    .ok()
+                    .filter(|compressed| compressed.len() < data.len())
+            } else {
+                None
+            }
+        };
+
+        let compressed = match compressed {
+            Some(compressed) => compressed,
+            None => return response,
+        };
+
+        response.data.body = Some(Box::new(Body::from(compressed).into()));
+        response
+            .data
+            .headers
+            .insert("Content-Encoding".to_string(), encoding.name().to_string());
+
+        response
+    }
+}
+
+impl Middleware for Compression {
+    fn handle(&self, request: HttpRequest, next: Next) -> HttpResponse {
+        let accept_encoding = request.headers().get("accept-encoding").cloned();
+
+        self.compress_with(
+            accept_encoding.as_ref().map(String::as_str),
+            next.run(request),
+        )
+    }
+}
+
+/// Determines if a content type is textual and worth compressing.
+fn is_compressible(content_type: &str) -> bool {
+    let media_type = content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_lowercase();
+
+    media_type.starts_with("text/")
+        || media_type.ends_with("+json")
+        || media_type.ends_with("+xml")
+        || match media_type.as_str() {
+            "application/json"
+            | "application/xml"
+            | "application/javascript"
+            | "application/x-www-form-urlencoded" => true,
+            _ => false,
+        }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::Router;
+    use flate2::read::{GzDecoder, ZlibDecoder};
+    use std::io::Read;
+
+    fn request(accept_encoding: &str) -> HttpRequest {
+        ::http::Request::builder()
+            .uri("https://example.com/api/items")
+            .header("Accept-Encoding", accept_encoding)
+            .body("")
+            .unwrap()
+            .into()
+    }
+
+    fn response() -> HttpResponse {
+        "hello world! ".repeat(100).into()
+    }
+
+    fn decode<R: Read>(mut reader: R) -> String {
+        let mut decoded = String::new();
+        reader.read_to_string(&mut decoded).unwrap();
+        decoded
+    }
+
+    #[test]
+    fn it_compresses_with_gzip() {
+        let response = Compression::new().compress(&request("gzip"), response());
+
+        assert_eq!(response.headers().get("Content-Encoding").unwrap(), "gzip");
+        assert_eq!(response.headers().get("Vary").unwrap(), "Accept-Encoding");
+        assert_eq!(
+            decode(GzDecoder::new(response.body().as_bytes())),
+            "hello world! ".repeat(100)
+        );
+    }
+
+    #[test]
+    fn it_compresses_with_deflate() {
+        let response = Compression::new().compress(&request("deflate"), response());
+
+        assert_eq!(
+            response.headers().get("Content-Encoding").unwrap(),
+            "deflate"
+        );
+        assert_eq!(
+            decode(ZlibDecoder::new(response.body().as_bytes())),
+            "hello world! ".repeat(100)
+        );
+    }
+
+    #[test]
+    fn it_compresses_with_brotli() {
+        let response = Compression::new().compress(&request("gzip, deflate, br"), response());
+
+        assert_eq!(response.headers().get("Content-Encoding").unwrap(), "br");
+        assert_eq!(
+            decode(brotli::Decompressor::new(
+                response.body().as_bytes(),
+                BROTLI_BUFFER_SIZE
+            )),
+            "hello world! ".repeat(100)
+        );
+    }
+
+    #[test]
+    fn it_selects_the_encoding_by_quality() {
+        let compression = Compression::new();
+
+        assert_eq!(
+            compression.select_encoding("br;q=0.5, gzip"),
+            Some(Encoding::Gzip)
+        );
+        assert_eq!(compression.select_encoding("*"), Some(Encoding::Brotli));
+        assert_eq!(
+            compression.select_encoding("*, br;q=0"),
+            Some(Encoding::Gzip)
+        );
+        assert_eq!(compression.select_encoding("identity"), None);
+        assert_eq!(
+            compression.brotli(false).select_encoding("br, deflate"),
+            Some(Encoding::Deflate)
+        );
+    }
+
+    #[test]
+    fn it_does_not_compress_small_bodies() {
+        let response = Compression::new().compress(&request("gzip"), "hello".into());
+
+        assert!(response.headers().get("Content-Encoding").is_none());
+        assert_eq!(response.body().as_str().unwrap(), "hello");
+
+        let response = Compression::new()
+            .min_size(100)
+            .compress(&request("gzip"), "hello world! ".repeat(10).into());
+        assert_eq!(response.headers().get("Content-Encoding").unwrap(), "gzip");
+    }
+
+    #[test]
+    fn it_does_not_compress_binary_bodies() {
+        let response = Compression::new().compress(&request("gzip"), vec![0u8; 2048].into());
+
+        assert!(response.headers().get("Content-Encoding").is_none());
+        assert_eq!(response.body().as_bytes(), &[0u8; 2048][..]);
+    }
+
+    #[test]
+    fn it_does_not_compress_without_accept_encoding() {
+        let request: HttpRequest = ::http::Request::builder()
+            .uri("https://example.com/api/items")
+            .body("")
+            .unwrap()
+            .into();
+
+        let response = Compression::new().compress(&request, response());
+        assert!(response.headers().get("Content-Encoding").is_none());
+        assert_eq!(response.headers().get("Vary").unwrap(), "Accept-Encoding");
+    }
+
+    #[test]
+    fn it_varies_on_accept_encoding_when_not_compressed() {
+        let response = Compression::new().compress(&request("identity"), response());
+        assert!(response.headers().get("Content-Encoding").is_none());
+        assert_eq!(response.headers().get("Vary").unwrap(), "Accept-Encoding");
+
+        let response = Compression::new().compress(&request("gzip"), "hello".into());
+        assert!(response.headers().get("Content-Encoding").is_none());
+        assert_eq!(response.headers().get("Vary").unwrap(), "Accept-Encoding");
+    }
+
+    #[test]
+    fn it_does_not_vary_for_binary_bodies() {
+        let response = Compression::new().compress(&request("gzip"), vec![0u8; 2048].into());
+        assert!(response.headers().get("Vary").is_none());
+    }
+
+    #[test]
+    fn it_compresses_as_middleware() {
+        let router = Router::new()
+            .middleware(Compression::new())
+            .get("/api/items", |_| response());
+
+        let response = router.handle(request("gzip"));
+        assert_eq!(response.headers().get("Content-Encoding").unwrap(), "gzip");
+        assert_eq!(
+            decode(GzDecoder::new(response.body().as_bytes())),
+            "hello world! ".repeat(100)
+        );
+    }
+
+    #[test]
+    fn it_detects_compressible_content_types() {
+        assert!(is_compressible("text/plain"));
+        assert!(is_compressible("application/json; charset=utf-8"));
+        assert!(is_compressible("application/problem+json"));
+        assert!(is_compressible("image/svg+xml"));
+        assert!(!is_compressible("application/octet-stream"));
+        assert!(!is_compressible("image/png"));
+    }
+}
@@ -0,0 +1,402 @@
+use crate::rpc::{
+    nullable_bool, nullable_double, nullable_string, nullable_timestamp, rpc_http_cookie,
+    NullableBool, NullableDouble, NullableString, NullableTimestamp, RpcHttpCookie,
+};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use prost_types::Timestamp;
+use std::fmt;
+
+const EXPIRES_FORMAT: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+/// Represents the `SameSite` attribute of a HTTP cookie.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SameSite {
+    /// The cookie is sent with both same-site and cross-site requests.
+    None,
+    /// The cookie is sent with same-site requests and top-level cross-site navigations.
+    Lax,
+    /// The cookie is only sent with same-site requests.
+    Strict,
+}
+
+impl Default for SameSite {
+    fn default() -> Self {
+        SameSite::None
+    }
+}
+
+/// Represents a HTTP cookie.
+///
+/// Cookies of a request only have a name and a value; the remaining attributes are only
+/// used when setting a cookie on a response.
+///
+/// # Examples
+///
+/// ```rust
+/// use azure_functions::http::{Cookie, SameSite};
+/// use chrono::Duration;
+///
+/// let cookie = Cookie::new("session", "1234")
+///     .path("/")
+///     .max_age(Duration::hours(1))
+///     .secure(true)
+///     .http_only(true)
+///     .same_site(SameSite::Strict);
+///
+/// assert_eq!(cookie.name, "session");
+/// assert_eq!(cookie.value, "1234");
+/// assert_eq!(cookie.max_age, Some(Duration::hours(1)));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cookie {
+    /// The name of the cookie.
+    pub name: String,
+    /// The value of the cookie.
+    pub value: String,
+    /// The hosts allowed to receive the cookie.
+    pub domain: Option<String>,
+    /// The URL path that must exist in the requested URL for the cookie to be sent.
+    pub path: Option<String>,
+    /// The date and time the cookie expires.
+    pub expires: Option<DateTime<Utc>>,
+    /// The amount of time until the cookie expires.
+    pub max_age: Option<Duration>,
+    /// Whether or not the cookie is only sent with encrypted requests.
+    pub secure: Option<bool>,
+    /// Whether or not the cookie is inaccessible to JavaScript's `Document.cookie` API.
+    pub http_only: Option<bool>,
+    /// Whether or not the cookie is sent with cross-site requests.
+    pub same_site: SameSite,
+}
+
+impl Cookie {
+    /// Creates a new cookie with the given name and value.
+    pub fn new<N, V>(name: N, value: V) -> Self
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        Cookie {
+            name: name.into(),
+            value: value.into(),
+            domain: None,
+            path: None,
+            expires: None,
+            max_age: None,
+            secure: None,
+            http_only: None,
+            same_site: SameSite::default(),
+        }
+    }
+
+    /// Sets the hosts allowed to receive the cookie.
+    pub fn domain<T: Into<String>>(mut self, domain: T) -> Self {
+        self.domain = Some(domain.into());
+        self
+    }
+
+    /// Sets the URL path that must exist in the requested URL for the cookie to be sent.
+    pub fn path<T: Into<String>>(mut self, path: T) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Sets the date and time the cookie expires.
+    ///
+    /// Prefer `max_age` when possible, as clients use it over `Expires` when both are set.
+    pub fn expires(mut self, expires: DateTime<Utc>) -> Self {
+        self.expires = Some(expires);
+        self
+    }
+
+    /// Sets the amount of time until the cookie expires.
+    ///
+    /// A zero or negative duration expires the cookie immediately.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Sets whether or not the cookie is only sent with encrypted requests.
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.secure = Some(secure);
+        self
+    }
+
+    /// Sets whether or not the cookie is inaccessible to JavaScript's `Document.cookie` API.
+    pub fn http_only(mut self, http_only: bool) -> Self {
+        self.http_only = Some(http_only);
+        self
+    }
+
+    /// Sets whether or not the cookie is sent with cross-site requests.
+    pub fn same_site(mut self, same_site: SameSite) -> Self {
+        self.same_site = same_site;
+        self
+    }
+
+    pub(crate) fn parse_header(header: &str) -> Vec<Cookie> {
+        header
+            .split(';')
+            .filter_map(|pair| {
+                let mut parts = pair.splitn(2, '=');
+                let name = parts.next()?.trim();
+                let value = parts.next()?.trim();
+
+                if name.is_empty() {
+                    return None;
+                }
+
+                let value = if value.len() > 1 && value.starts_with('"') && value.ends_with('"') {
+                    &value[1..value.len() - 1]
+                } else {
+                    value
+                };
+
+                Some(Cookie::new(name, value))
+            })
+            .collect()
+    }
+
+    /// Parses the value of a `Set-Cookie` header.
+    ///
+    /// Unknown or invalid attributes are ignored.
+    pub(crate) fn parse_set_cookie(header: &str) -> Option<Cookie> {
+        let mut attributes = header.split(';');
+
+        let mut cookie = Cookie::parse_header(attributes.next()?).pop()?;
+
+        for attribute in attributes {
+            let mut parts = attribute.splitn(2, '=');
+            let name = parts.next().unwrap_or("").trim().to_lowercase();
+            let value = parts.next().map(str::trim);
+
+            match (name.as_str(), value) {
+                ("domain", Some(v)) => cookie.domain = Some(v.to_owned()),
+                ("path", Some(v)) => cookie.path = Some(v.to_owned()),
+                ("expires", Some(v)) => {
+                    if let Ok(expires) = DateTime::parse_from_rfc2822(v) {
+                        cookie.expires = Some(expires.with_timezone(&Utc));
+                    }
+                }
+                ("max-age", Some(v)) => {
+                    if let Ok(seconds) = v.parse() {
+                        cookie.max_age = Some(Duration::seconds(seconds));
+                    }
+                }
+                ("secure", _) => cookie.secure = Some(true),
+                ("httponly", _) => cookie.http_only = Some(true),
+                ("samesite", Some(v)) => {
+                    if v.eq_ignore_ascii_case("lax") {
+                        cookie.same_site = SameSite::Lax;
+                    } else if v.eq_ignore_ascii_case("strict") {
+                        cookie.same_site = SameSite::Strict;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Some(cookie)
+    }
+}
+
+/// Formats the cookie as the value of a `Set-Cookie` header.
+impl fmt::Display for Cookie {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}={}", self.name, self.value)?;
+
+        if let Some(domain) = &self.domain {
+            write!(f, "; Domain={}", domain)?;
+        }
+        if let Some(path) = &self.path {
+            write!(f, "; Path={}", path)?;
+        }
+        if let Some(expires) = &self.expires {
+            write!(f, "; Expires={}", expires.format(EXPIRES_FORMAT))?;
+        }
+        if let Some(max_age) = &self.max_age {
+            write!(f, "; Max-Age={}", max_age.num_seconds())?;
+        }
+        if self.secure == Some(true) {
+            write!(f, "; Secure")?;
+        }
+        if self.http_only == Some(true) {
+            write!(f, "; HttpOnly")?;
+        }
+        match self.same_site {
+            SameSite::None => {}
+            SameSite::Lax => write!(f, "; SameSite=Lax")?,
+            SameSite::Strict => write!(f, "; SameSite=Strict")?,
+        };
+
+        Ok(())
+    }
+}
+
+impl From<Cookie> for RpcHttpCookie {
+    fn from(cookie: Cookie) -> Self {
+        RpcHttpCookie {
+            name: cookie.name,
+            value: cookie.value,
+            domain: cookie.domain.map(|d| NullableString {
+                string: Some(nullable_string::String::Value(d)),
+            }),
+            path: cookie.path.map(|p| NullableString {
+                string: Some(nullable_string::String::Value(p)),
+            }),
+            expires: cookie.expires.map(|e| NullableTimestamp {
+                timestamp: Some(nullable_timestamp::Timestamp::Value(Timestamp {
+                    seconds: e.timestamp(),
+                    nanos: e.timestamp_subsec_nanos() as i32,
+                })),
+            }),
+            secure: cookie.secure.map(|s| NullableBool {
+                bool: Some(nullable_bool::Bool::Value(s)),
+            }),
+            http_only: cookie.http_only.map(|h| NullableBool {
+                bool: Some(nullable_bool::Bool::Value(h)),
+            }),
+            same_site: match cookie.same_site {
+                SameSite::None => rpc_http_cookie::SameSite::None,
+                SameSite::Lax => rpc_http_cookie::SameSite::Lax,
+                SameSite::Strict => rpc_http_cookie::SameSite::Strict,
+            } as i32,
+            max_age: cookie.max_age.map(|m| NullableDouble {
+                double: Some(nullable_double::Double::Value(
+                    m.num_milliseconds() as f64 / 1000.0,
+                )),
+            }),
+        }
+    }
+}
+
+impl From<&RpcHttpCookie> for Cookie {
+    fn from(cookie: &RpcHttpCookie) -> Self {
+        Cookie {
+            name: cookie.name.clone(),
+            value: cookie.value.clone(),
+            domain: cookie.domain.as_ref().and_then(|d| {
+                d.string
+                    .as_ref()
+                    .map(|nullable_string::String::Value(d)| d.clone())
+            }),
+            path: cookie.path.as_ref().and_then(|p| {
+                p.string
+                    .as_ref()
+                    .map(|nullable_string::String::Value(p)| p.clone())
+            }),
+            expires: cookie.expires.as_ref().and_then(|e| {
+                e.timestamp
+                    .as_ref()
+                    .map(|nullable_timestamp::Timestamp::Value(t)| {
+                        Utc.timestamp(t.seconds, t.nanos as u32)
+                    })
+            }),
+            max_age: cookie.max_age.as_ref().and_then(|m| {
+                m.double.as_ref().map(|nullable_double::Double::Value(m)| {
+                    Duration::milliseconds((m * 1000.0) as i64)
+                })
+            }),
+            secure: cookie
+                .secure
+                .as_ref()
+                .and_then(|s| s.bool.as_ref().map(|nullable_bool::Bool::Value(s)| *s)),
+            http_only: cookie
+                .http_only
+                .as_ref()
+                .and_then(|h| h.bool.as_ref().map(|nullable_bool::Bool::Value(h)| *h)),
+            same_site: match rpc_http_cookie::SameSite::from_i32(cookie.same_site) {
+                Some(rpc_http_cookie::SameSite::Lax) => SameSite::Lax,
+                Some(rpc_http_cookie::SameSite::Strict) => SameSite::Strict,
+                _ => SameSite::None,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_a_cookie_header() {
+        let cookies = Cookie::parse_header(r#"session=1234; theme="dark";invalid; =empty"#);
+
+        assert_eq!(
+            cookies,
+            vec![Cookie::new("session", "1234"), Cookie::new("theme", "dark")]
+        );
+    }
+
+    #[test]
+    fn it_converts_to_and_from_a_rpc_cookie() {
+        let cookie = Cookie::new("session", "1234")
+            .domain("example.com")
+            .path("/")
+            .expires(Utc.ymd(2019, 11, 1).and_hms(12, 30, 0))
+            .max_age(Duration::seconds(3600))
+            .secure(true)
+            .http_only(true)
+            .same_site(SameSite::Lax);
+
+        let rpc: RpcHttpCookie = cookie.clone().into();
+
+        assert_eq!(rpc.name, "session");
+        assert_eq!(rpc.value, "1234");
+        assert_eq!(rpc.same_site, rpc_http_cookie::SameSite::Lax as i32);
+        assert_eq!(
+            rpc.max_age,
+            Some(NullableDouble {
+                double: Some(nullable_double::Double::Value(3600.0))
+            })
+        );
+        assert_eq!(
+            rpc.expires,
+            Some(NullableTimestamp {
+                timestamp: Some(nullable_timestamp::Timestamp::Value(Timestamp {
+                    seconds: 1_572_611_400,
+                    nanos: 0
+                }))
+            })
+        );
+        assert_eq!(Cookie::from(&rpc), cookie);
+    }
+
+    #[test]
+    fn it_formats_and_parses_set_cookie_headers() {
+        let cookie = Cookie::new("session", "1234")
+            .domain("example.com")
+            .path("/")
+            .expires(Utc.ymd(2019, 11, 1).and_hms(12, 30, 0))
+            .max_age(Duration::seconds(3600))
+            .secure(true)
+            .http_only(true)
+            .same_site(SameSite::Strict);
+
+        let header = cookie.to_string();
+        assert_eq!(
+            header,
+            "session=1234; Domain=example.com; Path=/; Expires=Fri, 01 Nov 2019 12:30:00 GMT; Max-Age=3600; Secure; HttpOnly; SameSite=Strict"
+        );
+        assert_eq!(Cookie::parse_set_cookie(&header).unwrap(), cookie);
+        assert_eq!(
+            Cookie::parse_set_cookie("a=b; Unknown; Max-Age=x").unwrap(),
+            Cookie::new("a", "b")
+        );
+        assert_eq!(Cookie::parse_set_cookie("invalid"), None);
+    }
+
+    #[test]
+    fn it_leaves_unset_attributes_empty() {
+        let rpc: RpcHttpCookie = Cookie::new("name", "value").into();
+
+        assert_eq!(rpc.domain, None);
+        assert_eq!(rpc.path, None);
+        assert_eq!(rpc.expires, None);
+        assert_eq!(rpc.max_age, None);
+        assert_eq!(rpc.secure, None);
+        assert_eq!(rpc.http_only, None);
+        assert_eq!(rpc.same_site, rpc_http_cookie::SameSite::None as i32);
+    }
+}
@@ -0,0 +1,538 @@
+use crate::{
+    bindings::{append_vary, HttpResponse},
+    codegen::{bindings::Binding, Function},
+    http::Status,
+    rpc::{
+        status_result::Status as ResultStatus, typed_data::Data, InvocationRequest,
+        InvocationResponse, ParameterBinding, StatusResult, TypedData,
+    },
+};
+use std::{collections::HashMap, sync::Arc};
+
+const RETURN_BINDING_NAME: &str = "$return";
+const SIMPLE_METHODS: &[&str] = &["GET", "HEAD", "POST"];
+
+/// Represents a Cross-Origin Resource Sharing (CORS) policy for HTTP-triggered functions.
+///
+/// A policy can be specified for a single function with the `cors_*` arguments of the `func` attribute
+/// (e.g. `#[func(cors_origins = "https://example.com", cors_methods = "GET|PUT")]`), or for every
+/// HTTP-triggered function that doesn't specify its own with
+/// [worker_main_with_cors](../fn.worker_main_with_cors.html).
+///
+/// Preflight requests are answered without invoking the function; the `Access-Control-*` headers
+/// are added to the responses of requests from allowed origins.
+///
+/// The Azure Functions host only forwards preflight requests to a function whose HTTP trigger
+/// accepts the `OPTIONS` method, so a function that restricts its methods must include `options`
+/// (e.g. `#[binding(name = "req", methods = "get|post|options")]`). This is checked at compile
+/// time for functions that specify their own policy.
+///
+/// # Examples
+///
+/// ```rust
+/// use azure_functions::http::Cors;
+///
+/// let cors = Cors::new()
+///     .allow_origin("https://example.com")
+///     .allow_method("PUT")
+///     .allow_header("Content-Type")
+///     .expose_header("ETag")
+///     .allow_credentials(true)
+///     .max_age(3600);
+/// ```
+///
+/// Specifying a policy for a single function:
+///
+/// ```rust
+/// use azure_functions::bindings::{HttpRequest, HttpResponse};
+/// use azure_functions::func;
+///
+/// #[func(cors_origins = "https://example.com", cors_methods = "PUT|DELETE", cors_max_age = 600)]
+/// pub fn items(_req: HttpRequest) -> HttpResponse {
+///     "Hello from another origin!".into()
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Cors {
+    allowed_origins: Vec<String>,
+    allowed_methods: Vec<String>,
+    allowed_headers: Vec<String>,
+    exposed_headers: Vec<String>,
+    allow_credentials: bool,
+    max_age: Option<u32>,
+}
+
+impl Cors {
+    /// Creates a new CORS policy that allows no origins.
+    pub fn new() -> Self {
+        Cors::default()
+    }
+
+    /// Allows requests from the given origin (e.g. `https://example.com`).
+    ///
+    /// An origin of `*` allows any origin.
+    pub fn allow_origin<T: Into<String>>(mut self, origin: T) -> Self {
+        self.allowed_origins.push(origin.into());
+        self
+    }
+
+    /// Allows requests from any origin.
+    ///
+    /// If credentials are allowed, the request's origin is sent back instead of `*`.
+    pub fn allow_any_origin(self) -> Self {
+        self.allow_origin("*")
+    }
+
+    /// Allows the given method in addition to `GET`, `HEAD`, and `POST`.
+    ///
+    /// A method of `*` allows any method.
+    pub fn allow_method<T: Into<String>>(mut self, method: T) -> Self {
+        self.allowed_methods.push(method.into().to_uppercase());
+        self
+    }
+
+    /// Allows the given request header.
+    ///
+    /// A header of `*` allows any header.
+    pub fn allow_header<T: Into<String>>(mut self, header: T) -> Self {
+        self.allowed_headers.push(header.into());
+        self
+    }
+
+    /// Exposes the given response header to the browser.
+    pub fn expose_header<T: Into<String>>(mut self, header: T) -> Self {
+        self.exposed_headers.push(header.into());
+        self
+    }
+
+    /// Sets whether or not credentials (e.g. cookies) are allowed.
+    pub fn allow_credentials(mut self, allow: bool) -> Self {
+        self.allow_credentials = allow;
+        self
+    }
+
+    /// Sets the number of seconds the browser may cache a preflight response.
+    pub fn max_age(mut self, seconds: u32) -> Self {
+        self.max_age = Some(seconds);
+        self
+    }
+
+    fn allows_origin(&self, origin: &str) -> bool {
+        self.allowed_origins
+            .iter()
+            .any(|o| o == "*" || o.eq_ignore_ascii_case(origin))
+    }
+
+    fn allows_method(&self, method: &str) -> bool {
+        SIMPLE_METHODS
+            .iter()
+            .any(|m| m.eq_ignore_ascii_case(method))
+            || self
+                .allowed_methods
+                .iter()
+                .any(|m| m == "*" || m.eq_ignore_ascii_case(method))
+    }
+
+    fn allows_headers(&self, headers: &str) -> bool {
+        headers
+            .split(',')
+            .map(str::trim)
+            .filter(|h| !h.is_empty())
+            .all(|h| {
+                self.allowed_headers
+                    .iter()
+                    .any(|a| a == "*" || a.eq_ignore_ascii_case(h))
+            })
+    }
+
+    /// Builds the response to a preflight request.
+    ///
+    /// The response has no CORS headers if the request is not allowed by the policy.
+    fn preflight(&self, headers: &HashMap<String, String>) -> HttpResponse {
+        let mut response = HttpResponse::build().status(Status::NoContent).finish();
+
+        let origin = headers.get("origin").map_or("", String::as_str);
+        let method = headers
+            .get("access-control-request-method")
+            .map_or("", String::as_str);
+        let request_headers = headers.get("access-control-request-headers");
+
+        if !self.allows_origin(origin)
+            || !self.allows_method(method)
+            || !request_headers.map_or(true, |h| self.allows_headers(h))
+        {
+            return response;
+        }
+
+        self.add_headers(origin, &mut response.data.headers);
+
+        response.data.headers.insert(
+            "Access-Control-Allow-Methods".to_string(),
+            method.to_string(),
+        );
+
+        if let Some(request_headers) = request_headers {
+            response.data.headers.insert(
+                "Access-Control-Allow-Headers".to_string(),
+                request_headers.clone(),
+            );
+        }
+
+        if let Some(max_age) = self.max_age {
+            response
+                .data
+                .headers
+                .insert("Access-Control-Max-Age".to_string(), max_age.to_string());
+        }
+
+        response
+    }
+
+    /// Adds the CORS headers for a request from the given origin.
+    fn add_headers(&self, origin: &str, headers: &mut HashMap<String, String>) {
+        if !self.allows_origin(origin) {
+            return;
+        }
+
+        let any = self.allowed_origins.iter().any(|o| o == "*");
+        if any && !self.allow_credentials {
+            headers.insert("Access-Control-Allow-Origin".to_string(), "*".to_string());
+        } else {
+            headers.insert(
+                "Access-Control-Allow-Origin".to_string(),
+                origin.to_string(),
+            );
+            append_vary(headers, "Origin");
+        }
+
+        if self.allow_credentials {
+            headers.insert(
+                "Access-Control-Allow-Credentials".to_string(),
+                "true".to_string(),
+            );
+        }
+
+        if !self.exposed_headers.is_empty() {
+            headers.insert(
+                "Access-Control-Expose-Headers".to_string(),
+                self.exposed_headers.join(", "),
+            );
+        }
+    }
+}
+
+impl From<&crate::codegen::Cors> for Cors {
+    fn from(cors: &crate::codegen::Cors) -> Self {
+        fn to_vec(values: &[std::borrow::Cow<'static, str>]) -> Vec<String> {
+            values.iter().map(|v| v.to_string()).collect()
+        }
+
+        Cors {
+            allowed_origins: to_vec(&cors.allowed_origins),
+            allowed_methods: to_vec(&cors.allowed_methods),
+            allowed_headers: to_vec(&cors.allowed_headers),
+            exposed_headers: to_vec(&cors.exposed_headers),
+            allow_credentials: cors.allow_credentials,
+            max_age: cors.max_age,
+        }
+    }
+}
+
+/// Applies a CORS policy to an invocation of a HTTP-triggered function.
+pub(crate) struct CorsInvocation {
+    policy: Arc<Cors>,
+    origin: String,
+    output: String,
+    preflight: Option<TypedData>,
+}
+
+impl CorsInvocation {
+    /// Creates a CORS invocation if the request is a cross-origin request to a HTTP-triggered function.
+    pub fn new(policy: Arc<Cors>, func: &Function, req: &InvocationRequest) -> Option<Self> {
+        let trigger = func.bindings.iter().find_map(|b| match b {
+            Binding::HttpTrigger(b) => Some(&b.name),
+            _ => None,
+        })?;
+
+        let output = func.bindings.iter().find_map(|b| match b {
+            Binding::Http(b) => Some(b.name.to_string()),
+            _ => None,
+        })?;
+
+        let http = req
+            .input_data
+            .iter()
+            .find(|b| b.name == *trigger)
+            .and_then(|b| match b.data.as_ref()?.data.as_ref()? {
+                Data::Http(http) => Some(http),
+                _ => None,
+            })?;
+
+        let origin = http.headers.get("origin")?.clone();
+
+        let preflight = if http.method.eq_ignore_ascii_case("OPTIONS")
+            && http.headers.contains_key("access-control-request-method")
+        {
+            Some(policy.preflight(&http.headers).into())
+        } else {
+            None
+        };
+
+        Some(CorsInvocation {
+            policy,
+            origin,
+            output,
+            preflight,
+        })
+    }
+
+    /// Gets the response to a preflight request; the function is not invoked for preflight requests.
+    pub fn preflight_response(&mut self, invocation_id: &str) -> Option<InvocationResponse> {
+        let data = self.preflight.take()?;
+
+        let mut res = InvocationResponse {
+            invocation_id: invocation_id.to_owned(),
+            result: Some(StatusResult {
+                status: ResultStatus::Success as i32,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        if self.output == RETURN_BINDING_NAME {
+            res.return_value = Some(data);
+        } else {
+            res.output_data.push(ParameterBinding {
+                name: self.output.clone(),
+                data: Some(data),
+            });
+        }
+
+        Some(res)
+    }
+
+    /// Adds the CORS headers to the HTTP output of the function.
+    pub fn apply(&self, res: &mut InvocationResponse) {
+        let data = if self.output == RETURN_BINDING_NAME {
+            res.return_value.as_mut()
+        } else {
+            res.output_data
+                .iter_mut()
+                .find(|b| b.name == self.output)
+                .and_then(|b| b.data.as_mut())
+        };
+
+        if let Some(TypedData {
+            data: Some(Data::Http(http)),
+        }) = data
+        {
+            self.policy.add_headers(&self.origin, &mut http.headers);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        codegen::bindings::{Http, HttpTrigger},
+        rpc::RpcHttp,
+    };
+    use std::borrow::Cow;
+
+    fn headers(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn function(output: &'static str) -> Function {
+        Function {
+            name: Cow::Borrowed("function"),
+            disabled: false,
+            version: None,
+            retry: None,
+            retry_options: None,
+            roles: Cow::Borrowed(&[]),
+            cors: None,
+            bindings: Cow::Owned(vec![
+                Binding::HttpTrigger(HttpTrigger {
+                    name: Cow::Borrowed("req"),
+                    auth_level: None,
+                    methods: Cow::Borrowed(&[]),
+                    route: None,
+                }),
+                Binding::Http(Http {
+                    name: Cow::Borrowed(output),
+                }),
+            ]),
+            invoker: None,
+            manifest_dir: None,
+            file: None,
+        }
+    }
+
+    fn request(method: &str, headers: HashMap<String, String>) -> InvocationRequest {
+        InvocationRequest {
+            invocation_id: "id".to_string(),
+            input_data: vec![ParameterBinding {
+                name: "req".to_string(),
+                data: Some(TypedData {
+                    data: Some(Data::Http(Box::new(RpcHttp {
+                        method: method.to_string(),
+                        headers,
+                        ..Default::default()
+                    }))),
+                }),
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn http(data: Option<&TypedData>) -> &RpcHttp {
+        match data {
+            Some(TypedData {
+                data: Some(Data::Http(http)),
+            }) => http,
+            _ => panic!("expected HTTP data"),
+        }
+    }
+
+    #[test]
+    fn it_responds_to_an_allowed_preflight_request() {
+        let cors = Cors::new()
+            .allow_origin("https://example.com")
+            .allow_method("put")
+            .allow_header("Content-Type")
+            .max_age(600);
+
+        let response = cors.preflight(&headers(&[
+            ("origin", "https://example.com"),
+            ("access-control-request-method", "PUT"),
+            ("access-control-request-headers", "content-type"),
+        ]));
+
+        assert_eq!(response.status(), Status::NoContent);
+        assert_eq!(
+            response.headers(),
+            &headers(&[
+                ("Access-Control-Allow-Origin", "https://example.com"),
+                ("Access-Control-Allow-Methods", "PUT"),
+                ("Access-Control-Allow-Headers", "content-type"),
+                ("Access-Control-Max-Age", "600"),
+                ("Vary", "Origin"),
+            ])
+        );
+    }
+
+    #[test]
+    fn it_responds_to_a_disallowed_preflight_request_without_cors_headers() {
+        let cors = Cors::new().allow_origin("https://example.com");
+
+        for request in &[
+            headers(&[
+                ("origin", "https://other.com"),
+                ("access-control-request-method", "GET"),
+            ]),
+            headers(&[
+                ("origin", "https://example.com"),
+                ("access-control-request-method", "DELETE"),
+            ]),
+            headers(&[
+                ("origin", "https://example.com"),
+                ("access-control-request-method", "GET"),
+                ("access-control-request-headers", "x-custom"),
+            ]),
+        ] {
+            let response = cors.preflight(request);
+
+            assert_eq!(response.status(), Status::NoContent);
+            assert!(response.headers().is_empty());
+        }
+    }
+
+    #[test]
+    fn it_allows_any_origin() {
+        let mut headers = HashMap::new();
+        Cors::new()
+            .allow_any_origin()
+            .expose_header("ETag")
+            .add_headers("https://example.com", &mut headers);
+
+        assert_eq!(headers["Access-Control-Allow-Origin"], "*");
+        assert_eq!(headers["Access-Control-Expose-Headers"], "ETag");
+        assert!(!headers.contains_key("Vary"));
+    }
+
+    #[test]
+    fn it_echoes_the_origin_when_credentials_are_allowed() {
+        let mut headers = headers(&[("Vary", "Accept")]);
+        Cors::new()
+            .allow_any_origin()
+            .allow_credentials(true)
+            .add_headers("https://example.com", &mut headers);
+
+        assert_eq!(
+            headers["Access-Control-Allow-Origin"],
+            "https://example.com"
+        );
+        assert_eq!(headers["Access-Control-Allow-Credentials"], "true");
+        assert_eq!(headers["Vary"], "Accept, Origin");
+    }
+
+    #[test]
+    fn it_answers_a_preflight_invocation() {
+        let policy = Arc::new(Cors::new().allow_origin("https://example.com"));
+        let req = request(
+            "OPTIONS",
+            headers(&[
+                ("origin", "https://example.com"),
+                ("access-control-request-method", "POST"),
+            ]),
+        );
+
+        let mut cors = CorsInvocation::new(policy, &function("$return"), &req).unwrap();
+        let res = cors.preflight_response(&req.invocation_id).unwrap();
+
+        assert_eq!(res.invocation_id, "id");
+        let http = http(res.return_value.as_ref());
+        assert_eq!(http.status_code, "204");
+        assert_eq!(
+            http.headers["Access-Control-Allow-Origin"],
+            "https://example.com"
+        );
+    }
+
+    #[test]
+    fn it_adds_headers_to_the_output_of_an_invocation() {
+        let policy = Arc::new(Cors::new().allow_origin("https://example.com"));
+        let req = request("GET", headers(&[("origin", "https://example.com")]));
+
+        let mut cors = CorsInvocation::new(policy, &function("output"), &req).unwrap();
+        assert!(cors.preflight_response(&req.invocation_id).is_none());
+
+        let mut res = InvocationResponse {
+            output_data: vec![ParameterBinding {
+                name: "output".to_string(),
+                data: Some(HttpResponse::from("hello").into()),
+            }],
+            ..Default::default()
+        };
+        cors.apply(&mut res);
+
+        let http = http(res.output_data[0].data.as_ref());
+        assert_eq!(
+            http.headers["Access-Control-Allow-Origin"],
+            "https://example.com"
+        );
+    }
+
+    #[test]
+    fn it_ignores_same_origin_requests() {
+        let policy = Arc::new(Cors::new().allow_any_origin());
+        let req = request("GET", HashMap::new());
+
+        assert!(CorsInvocation::new(policy, &function("$return"), &req).is_none());
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{
+    bindings::HttpResponse,
+    http::{HttpError, Status},
+};
+use std::fmt;
+
+/// Represents an error extracting typed data from a HTTP request.
+///
+/// An extraction error converts into a `400 Bad Request` response describing the error.
+///
+/// # Examples
+///
+/// ```rust
+/// use azure_functions::bindings::{HttpRequest, HttpResponse};
+/// use azure_functions::func;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Page {
+///     offset: u32,
+///     limit: u32,
+/// }
+///
+/// #[func]
+/// pub fn list(request: HttpRequest) -> HttpResponse {
+///     let page: Page = match request.query() {
+///         Ok(page) => page,
+///         Err(e) => return e.into(),
+///     };
+///
+///     format!("Listing {} items from {}", page.limit, page.offset).into()
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExtractError {
+    /// The query parameters could not be deserialized.
+    Query(String),
+    /// The route parameters could not be deserialized.
+    Route(String),
+    /// The request does not have a form body.
+    NotForm,
+    /// The form body could not be deserialized.
+    Form(String),
+    /// The request is missing a header.
+    MissingHeader(String),
+    /// A header could not be parsed.
+    Header {
+        /// The name of the header.
+        name: String,
+        /// The parse error message.
+        message: String,
+    },
+}
+
+impl fmt::Display for ExtractError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExtractError::Query(message) => write!(f, "invalid query parameters: {}", message),
+            ExtractError::Route(message) => write!(f, "invalid route parameters: {}", message),
+            ExtractError::NotForm => write!(
+                f,
+                "expected a request body with a content type of 'application/x-www-form-urlencoded'"
+            ),
+            ExtractError::Form(message) => write!(f, "invalid form body: {}", message),
+            ExtractError::MissingHeader(name) => write!(f, "missing header '{}'", name),
+            ExtractError::Header { name, message } => {
+                write!(f, "invalid value for header '{}': {}", name, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExtractError {}
+
+impl HttpError for ExtractError {
+    fn status(&self) -> Status {
+        Status::BadRequest
+    }
+}
+
+impl From<ExtractError> for HttpResponse {
+    fn from(error: ExtractError) -> Self {
+        HttpResponse::build()
+            .status(error.status())
+            .body(error.to_string())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::ProblemDetails;
+
+    #[test]
+    fn it_converts_to_a_bad_request() {
+        let response: HttpResponse = ExtractError::Query("missing field `id`".to_string()).into();
+
+        assert_eq!(response.status(), Status::BadRequest);
+        assert_eq!(
+            response.body().as_str().unwrap(),
+            "invalid query parameters: missing field `id`"
+        );
+    }
+
+    #[test]
+    fn it_converts_to_problem_details() {
+        let problem: ProblemDetails = ExtractError::MissingHeader("x-id".to_string()).into();
+
+        assert_eq!(problem.status, Status::BadRequest);
+        assert_eq!(problem.detail.unwrap(), "missing header 'x-id'");
+    }
+}
@@ -0,0 +1,441 @@
+use crate::{
+    bindings::HttpResponse,
+    http::{HttpError, Status},
+};
+use std::{collections::HashMap, fmt, str::from_utf8};
+
+const CRLF: &[u8] = b"\r\n";
+const HEADERS_END: &[u8] = b"\r\n\r\n";
+const MAX_BOUNDARY_LENGTH: usize = 70;
+
+/// Represents the limits enforced when parsing a `multipart/form-data` body.
+///
+/// # Examples
+///
+/// ```rust
+/// use azure_functions::http::MultipartLimits;
+///
+/// let limits = MultipartLimits {
+///     max_part_size: 1024 * 1024,
+///     ..Default::default()
+/// };
+///
+/// assert_eq!(limits.max_parts, 100);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MultipartLimits {
+    /// The maximum number of parts in the body.
+    pub max_parts: usize,
+    /// The maximum size, in bytes, of the content of a single part.
+    pub max_part_size: usize,
+    /// The maximum size, in bytes, of the entire body.
+    pub max_body_size: usize,
+}
+
+impl Default for MultipartLimits {
+    fn default() -> Self {
+        MultipartLimits {
+            max_parts: 100,
+            max_part_size: 10 * 1024 * 1024,
+            max_body_size: 100 * 1024 * 1024,
+        }
+    }
+}
+
+/// Represents an error parsing a `multipart/form-data` body.
+///
+/// A multipart error converts into a `415 Unsupported Media Type` response for requests that
+/// are not multipart, a `413 Payload Too Large` response for limit violations, and a
+/// `400 Bad Request` response otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MultipartError {
+    /// The request does not have a `multipart/form-data` content type.
+    NotMultipart,
+    /// The content type is missing a valid boundary parameter.
+    InvalidBoundary,
+    /// The body is malformed.
+    Malformed(String),
+    /// The body has more parts than allowed.
+    TooManyParts(usize),
+    /// The content of a part is larger than allowed.
+    PartTooLarge {
+        /// The name of the part.
+        name: String,
+        /// The maximum size of a part.
+        limit: usize,
+    },
+    /// The body is larger than allowed.
+    BodyTooLarge(usize),
+}
+
+impl fmt::Display for MultipartError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MultipartError::NotMultipart => write!(
+                f,
+                "expected a request body with a content type of 'multipart/form-data'"
+            ),
+            MultipartError::InvalidBoundary => {
+                write!(f, "the multipart content type has an invalid boundary")
+            }
+            MultipartError::Malformed(message) => {
+                write!(f, "malformed multipart body: {}", message)
+            }
+            MultipartError::TooManyParts(limit) => {
+                write!(f, "the multipart body has more than {} parts", limit)
+            }
+            MultipartError::PartTooLarge { name, limit } => write!(
+                f,
+                "the multipart part '{}' is larger than {} bytes",
+                name, limit
+            ),
+            MultipartError::BodyTooLarge(limit) => {
+                write!(f, "the multipart body is larger than {} bytes", limit)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MultipartError {}
+
+impl HttpError for MultipartError {
+    fn status(&self) -> Status {
+        match self {
+            MultipartError::NotMultipart => Status::UnsupportedMediaType,
+            MultipartError::TooManyParts(_)
+            | MultipartError::PartTooLarge { .. }
+            | MultipartError::BodyTooLarge(_) => Status::PayloadTooLarge,
+            _ => Status::BadRequest,
+        }
+    }
+}
+
+impl From<MultipartError> for HttpResponse {
+    fn from(error: MultipartError) -> Self {
+        HttpResponse::build()
+            .status(error.status())
+            .body(error.to_string())
+            .finish()
+    }
+}
+
+/// Represents a part of a `multipart/form-data` body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Part {
+    /// The name of the form field of the part.
+    pub name: String,
+    /// The filename of an uploaded file.
+    pub filename: Option<String>,
+    /// The content type of the part.
+    pub content_type: Option<String>,
+    /// The headers of the part.
+    ///
+    /// The header keys are lower-cased.
+    pub headers: HashMap<String, String>,
+    /// The content of the part.
+    pub content: Vec<u8>,
+}
+
+impl Part {
+    /// Determines if the part is an uploaded file.
+    pub fn is_file(&self) -> bool {
+        self.filename.is_some()
+    }
+
+    /// Gets the content of the part as a string.
+    ///
+    /// Returns None if the content is not valid UTF-8.
+    pub fn as_str(&self) -> Option<&str> {
+        from_utf8(&self.content).ok()
+    }
+}
+
+/// Gets the boundary of a `multipart/form-data` content type.
+pub(crate) fn get_boundary(content_type: &str) -> Result<&str, MultipartError> {
+    let mut params = content_type.split(';');
+
+    if !params.next().map_or(false, |t| {
+        t.trim().eq_ignore_ascii_case("multipart/form-data")
+    }) {
+        return Err(MultipartError::NotMultipart);
+    }
+
+    let boundary = params
+        .filter_map(|p| {
+            let mut kv = p.splitn(2, '=');
+            match (kv.next(), kv.next()) {
+                (Some(k), Some(v)) if k.trim().eq_ignore_ascii_case("boundary") => {
+                    Some(v.trim().trim_matches('"'))
+                }
+                _ => None,
+            }
+        })
+        .next()
+        .ok_or(MultipartError::InvalidBoundary)?;
+
+    if boundary.is_empty() || boundary.len() > MAX_BOUNDARY_LENGTH {
+        return Err(MultipartError::InvalidBoundary);
+    }
+
+    Ok(boundary)
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    if from > haystack.len() {
+        return None;
+    }
+
+    haystack[from..]
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|i| i + from)
+}
+
+fn get_disposition_params(value: &str) -> HashMap<String, String> {
+    value
+        .split(';')
+        .skip(1)
+        .filter_map(|p| {
+            let mut kv = p.splitn(2, '=');
+            let key = kv.next()?.trim().to_lowercase();
+            let value = kv.next()?.trim();
+            let value = if value.len() > 1 && value.starts_with('"') && value.ends_with('"') {
+                value[1..value.len() - 1].replace("\\\"", "\"")
+            } else {
+                value.to_owned()
+            };
+
+            Some((key, value))
+        })
+        .collect()
+}
+
+fn parse_part(headers: &[u8], content: &[u8]) -> Result<Part, MultipartError> {
+    let headers = from_utf8(headers)
+        .map_err(|_| MultipartError::Malformed("part headers are not valid UTF-8".to_owned()))?;
+
+    let headers: HashMap<String, String> = headers
+        .split("\r\n")
+        .filter(|l| !l.is_empty())
+        .map(|l| {
+            let mut kv = l.splitn(2, ':');
+            match (kv.next(), kv.next()) {
+                (Some(k), Some(v)) => Ok((k.trim().to_lowercase(), v.trim().to_owned())),
+                _ => Err(MultipartError::Malformed(format!(
+                    "invalid part header '{}'",
+                    l
+                ))),
+            }
+        })
+        .collect::<Result<_, _>>()?;
+
+    let mut disposition = headers
+        .get("content-disposition")
+        .map(|d| get_disposition_params(d))
+        .ok_or_else(|| {
+            MultipartError::Malformed("part is missing a Content-Disposition header".to_owned())
+        })?;
+
+    let name = disposition.remove("name").ok_or_else(|| {
+        MultipartError::Malformed("part is missing a name in its Content-Disposition".to_owned())
+    })?;
+
+    Ok(Part {
+        name,
+        filename: disposition.remove("filename"),
+        content_type: headers.get("content-type").cloned(),
+        headers,
+        content: content.to_vec(),
+    })
+}
+
+/// Parses the parts of a `multipart/form-data` body with the given boundary.
+pub(crate) fn parse(
+    body: &[u8],
+    boundary: &str,
+    limits: &MultipartLimits,
+) -> Result<Vec<Part>, MultipartError> {
+    if body.len() > limits.max_body_size {
+        return Err(MultipartError::BodyTooLarge(limits.max_body_size));
+    }
+
+    let delimiter = [b"--", boundary.as_bytes()].concat();
+    let close_delimiter = [CRLF, &delimiter].concat();
+
+    let mut pos = find(body, &delimiter, 0).ok_or_else(|| {
+        MultipartError::Malformed("the body does not contain the boundary".to_owned())
+    })? + delimiter.len();
+
+    let mut parts = Vec::new();
+    loop {
+        if body[pos..].starts_with(b"--") {
+            return Ok(parts);
+        }
+
+        if !body[pos..].starts_with(CRLF) {
+            return Err(MultipartError::Malformed(
+                "expected a line break after the boundary".to_owned(),
+            ));
+        }
+        pos += CRLF.len();
+
+        if parts.len() == limits.max_parts {
+            return Err(MultipartError::TooManyParts(limits.max_parts));
+        }
+
+        // Parts without headers start immediately with the blank line.
+        let (headers, content_start) = if body[pos..].starts_with(CRLF) {
+            (&body[pos..pos], pos + CRLF.len())
+        } else {
+            let end = find(body, HEADERS_END, pos).ok_or_else(|| {
+                MultipartError::Malformed("part headers are not terminated".to_owned())
+            })?;
+            (&body[pos..end], end + HEADERS_END.len())
+        };
+
+        let content_end = find(body, &close_delimiter, content_start).ok_or_else(|| {
+            MultipartError::Malformed("the body is missing the closing boundary".to_owned())
+        })?;
+
+        let part = parse_part(headers, &body[content_start..content_end])?;
+        if part.content.len() > limits.max_part_size {
+            return Err(MultipartError::PartTooLarge {
+                name: part.name,
+                limit: limits.max_part_size,
+            });
+        }
+
+        parts.push(part);
+        pos = content_end + close_delimiter.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &[u8] = b"preamble\r\n--XyZ\r\n\
+Content-Disposition: form-data; name=\"title\"\r\n\
+\r\n\
+hello world\r\n\
+--XyZ\r\n\
+Content-Disposition: form-data; name=\"file\"; filename=\"a \\\"b\\\".bin\"\r\n\
+Content-Type: application/octet-stream\r\n\
+\r\n\
+\x00\xff\r\n--X\r\n\
+--XyZ--\r\n";
+
+    #[test]
+    fn it_gets_the_boundary() {
+        assert_eq!(
+            get_boundary("multipart/form-data; boundary=XyZ").unwrap(),
+            "XyZ"
+        );
+        assert_eq!(
+            get_boundary("Multipart/Form-Data; charset=utf-8; boundary=\"a b\"").unwrap(),
+            "a b"
+        );
+        assert_eq!(
+            get_boundary("application/json").unwrap_err(),
+            MultipartError::NotMultipart
+        );
+        assert_eq!(
+            get_boundary("multipart/form-data").unwrap_err(),
+            MultipartError::InvalidBoundary
+        );
+    }
+
+    #[test]
+    fn it_parses_parts() {
+        let parts = parse(BODY, "XyZ", &MultipartLimits::default()).unwrap();
+
+        assert_eq!(parts.len(), 2);
+
+        assert_eq!(parts[0].name, "title");
+        assert_eq!(parts[0].filename, None);
+        assert_eq!(parts[0].content_type, None);
+        assert_eq!(parts[0].as_str(), Some("hello world"));
+        assert!(!parts[0].is_file());
+
+        assert_eq!(parts[1].name, "file");
+        assert_eq!(parts[1].filename.as_ref().unwrap(), "a \"b\".bin");
+        assert_eq!(
+            parts[1].content_type.as_ref().unwrap(),
+            "application/octet-stream"
+        );
+        assert_eq!(parts[1].headers["content-type"], "application/octet-stream");
+        assert_eq!(parts[1].content, b"\x00\xff\r\n--X");
+        assert!(parts[1].is_file());
+    }
+
+    #[test]
+    fn it_enforces_limits() {
+        let limits = MultipartLimits {
+            max_parts: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse(BODY, "XyZ", &limits).unwrap_err(),
+            MultipartError::TooManyParts(1)
+        );
+
+        let limits = MultipartLimits {
+            max_part_size: 5,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse(BODY, "XyZ", &limits).unwrap_err(),
+            MultipartError::PartTooLarge {
+                name: "title".to_owned(),
+                limit: 5
+            }
+        );
+
+        let limits = MultipartLimits {
+            max_body_size: 10,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse(BODY, "XyZ", &limits).unwrap_err(),
+            MultipartError::BodyTooLarge(10)
+        );
+    }
+
+    #[test]
+    fn it_rejects_malformed_bodies() {
+        assert_eq!(
+            parse(
+                b"--XyZ\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\nvalue",
+                "XyZ",
+                &MultipartLimits::default()
+            )
+            .unwrap_err(),
+            MultipartError::Malformed("the body is missing the closing boundary".to_owned())
+        );
+        assert_eq!(
+            parse(
+                b"--XyZ\r\n\r\nvalue\r\n--XyZ--",
+                "XyZ",
+                &MultipartLimits::default()
+            )
+            .unwrap_err(),
+            MultipartError::Malformed("part is missing a Content-Disposition header".to_owned())
+        );
+        assert_eq!(
+            parse(b"no boundary", "XyZ", &MultipartLimits::default()).unwrap_err(),
+            MultipartError::Malformed("the body does not contain the boundary".to_owned())
+        );
+    }
+
+    #[test]
+    fn it_converts_to_a_response() {
+        let response: HttpResponse = MultipartError::BodyTooLarge(10).into();
+        assert_eq!(response.status(), Status::PayloadTooLarge);
+
+        let response: HttpResponse = MultipartError::NotMultipart.into();
+        assert_eq!(response.status(), Status::UnsupportedMediaType);
+
+        let response: HttpResponse = MultipartError::InvalidBoundary.into();
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+}
@@ -0,0 +1,249 @@
+use serde::Serialize;
+use serde_json::{to_string_pretty, to_value, Value};
+use std::borrow::Cow;
+use xml::{
+    writer::{EventWriter, XmlEvent},
+    EmitterConfig,
+};
+
+/// The media types supported by content negotiation, in order of preference.
+const MEDIA_TYPES: &[&str] = &[
+    "application/json",
+    "application/xml",
+    "text/plain",
+    "text/xml",
+];
+
+/// The name of the root element of negotiated XML responses.
+const XML_ROOT_ELEMENT: &str = "response";
+
+/// The name of the elements of an array in negotiated XML responses.
+const XML_ITEM_ELEMENT: &str = "item";
+
+/// Parses a header with a list of values with optional quality parameters (e.g. `Accept` or `Accept-Encoding`).
+///
+/// The values are lower-cased; values with an invalid quality are ignored.
+pub(crate) fn parse_quality_values(header: &str) -> Vec<(String, f32)> {
+    header
+        .split(',')
+        .filter_map(|item| {
+            let mut params = item.split(';');
+            let value = params.next()?.trim().to_lowercase();
+            if value.is_empty() {
+                return None;
+            }
+
+            let mut quality = 1.0;
+            for param in params {
+                let mut parts = param.splitn(2, '=');
+                if parts.next()?.trim().eq_ignore_ascii_case("q") {
+                    quality = parts.next()?.trim().parse::<f32>().ok()?;
+                    if !(0.0..=1.0).contains(&quality) {
+                        return None;
+                    }
+                }
+            }
+
+            Some((value, quality))
+        })
+        .collect()
+}
+
+/// Selects the preferred supported media type for the given `Accept` header.
+///
+/// Returns `None` if none of the supported media types are acceptable.
+pub(crate) fn select_media_type(accept: Option<&str>) -> Option<&'static str> {
+    let ranges = match accept.map(str::trim) {
+        Some(accept) if !accept.is_empty() => parse_quality_values(accept),
+        _ => return Some(MEDIA_TYPES[0]),
+    };
+
+    let mut selected = None;
+    let mut best = 0.0;
+
+    for media_type in MEDIA_TYPES {
+        let wildcard = format!("{}/*", &media_type[..media_type.find('/').unwrap()]);
+
+        let quality = [*media_type, wildcard.as_str(), "*/*"]
+            .iter()
+            .find_map(|range| ranges.iter().find(|(r, _)| r == range).map(|(_, q)| *q))
+            .unwrap_or(0.0);
+
+        if quality > best {
+            selected = Some(*media_type);
+            best = quality;
+        }
+    }
+
+    selected
+}
+
+/// Serializes a value for the given media type.
+pub(crate) fn serialize<T>(value: &T, media_type: &str) -> serde_json::Result<String>
+where
+    T: Serialize,
+{
+    let value = to_value(value)?;
+
+    Ok(match media_type {
+        "application/xml" | "text/xml" => to_xml(&value),
+        "text/plain" => match value {
+            Value::Null => String::new(),
+            Value::String(s) => s,
+            Value::Bool(_) | Value::Number(_) => value.to_string(),
+            _ => to_string_pretty(&value)?,
+        },
+        _ => value.to_string(),
+    })
+}
+
+/// Converts a JSON value to XML.
+///
+/// Object members become child elements and array items become `item` elements.
+fn to_xml(value: &Value) -> String {
+    let mut buffer = Vec::new();
+
+    {
+        let mut writer = EmitterConfig::new().create_writer(&mut buffer);
+        write_element(&mut writer, XML_ROOT_ELEMENT, value);
+    }
+
+    String::from_utf8(buffer).expect("XML should be valid UTF-8")
+}
+
+fn write_element(writer: &mut EventWriter<&mut Vec<u8>>, name: &str, value: &Value) {
+    let name = element_name(name);
+
+    writer
+        .write(XmlEvent::start_element(name.as_ref()))
+        .expect("failed to write XML element");
+
+    match value {
+        Value::Null => {}
+        Value::Bool(b) => writer
+            .write(XmlEvent::characters(&b.to_string()))
+            .expect("failed to write XML characters"),
+        Value::Number(n) => writer
+            .write(XmlEvent::characters(&n.to_string()))
+            .expect("failed to write XML characters"),
+        Value::String(s) => writer
+            .write(XmlEvent::characters(s))
+            .expect("failed to write XML characters"),
+        Value::Array(items) => {
+            for item in items {
+                write_element(writer, XML_ITEM_ELEMENT, item);
+            }
+        }
+        Value::Object(members) => {
+            for (name, value) in members {
+                write_element(writer, name, value);
+            }
+        }
+    };
+
+    writer
+        .write(XmlEvent::end_element())
+        .expect("failed to write XML element");
+}
+
+/// Converts a name into a valid XML element name by replacing invalid characters with underscores.
+fn element_name(name: &str) -> Cow<str> {
+    let is_valid = |(i, c): (usize, char)| {
+        c.is_alphabetic() || c == '_' || (i > 0 && (c.is_numeric() || c == '-' || c == '.'))
+    };
+
+    if !name.is_empty() && name.chars().enumerate().all(is_valid) {
+        return Cow::Borrowed(name);
+    }
+
+    let name: String = name
+        .chars()
+        .enumerate()
+        .map(|(i, c)| if is_valid((i, c)) { c } else { '_' })
+        .collect();
+
+    if name.is_empty() {
+        Cow::Borrowed("_")
+    } else {
+        Cow::Owned(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn it_parses_quality_values() {
+        assert_eq!(
+            parse_quality_values("text/html, application/json;q=0.5, */*; q=0.1, bad;q=2, ;q=1"),
+            vec![
+                ("text/html".to_string(), 1.0),
+                ("application/json".to_string(), 0.5),
+                ("*/*".to_string(), 0.1),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_selects_json_by_default() {
+        assert_eq!(select_media_type(None), Some("application/json"));
+        assert_eq!(select_media_type(Some("")), Some("application/json"));
+        assert_eq!(select_media_type(Some("*/*")), Some("application/json"));
+    }
+
+    #[test]
+    fn it_selects_the_preferred_media_type() {
+        assert_eq!(
+            select_media_type(Some("application/json;q=0.5, application/xml")),
+            Some("application/xml")
+        );
+        assert_eq!(select_media_type(Some("text/*")), Some("text/plain"));
+        assert_eq!(
+            select_media_type(Some("text/*;q=0.2, text/xml")),
+            Some("text/xml")
+        );
+        assert_eq!(
+            select_media_type(Some("text/html, */*;q=0.1")),
+            Some("application/json")
+        );
+    }
+
+    #[test]
+    fn it_selects_nothing_when_not_acceptable() {
+        assert_eq!(select_media_type(Some("text/html")), None);
+        assert_eq!(select_media_type(Some("*/*;q=0")), None);
+    }
+
+    #[test]
+    fn it_serializes_json() {
+        assert_eq!(
+            serialize(&json!({ "a": [1, 2] }), "application/json").unwrap(),
+            r#"{"a":[1,2]}"#
+        );
+    }
+
+    #[test]
+    fn it_serializes_text() {
+        assert_eq!(serialize(&"hello", "text/plain").unwrap(), "hello");
+        assert_eq!(serialize(&42, "text/plain").unwrap(), "42");
+        assert_eq!(serialize(&(), "text/plain").unwrap(), "");
+        assert_eq!(
+            serialize(&json!({ "a": 1 }), "text/plain").unwrap(),
+            "{\n  \"a\": 1\n}"
+        );
+    }
+
+    #[test]
+    fn it_serializes_xml() {
+        assert_eq!(
+            serialize(
+                &json!({ "name": "a & b", "tags": ["x", "y"], "1st key": null, "ok": true }),
+                "application/xml"
+            )
+            .unwrap(),
+            r#"<?xml version="1.0" encoding="utf-8"?><response><_st_key /><name>a &amp; b</name><ok>true</ok><tags><item>x</item><item>y</item></tags></response>"#
+        );
+    }
+}
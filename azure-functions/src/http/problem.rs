@@ -0,0 +1,449 @@
+use crate::{bindings::HttpResponse, context::Context, http::Status};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::{error::Error, fmt};
+
+/// The media type of problem details responses.
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+const ABOUT_BLANK: &str = "about:blank";
+
+/// Represents an error that maps to a HTTP status code.
+///
+/// Errors implementing this trait convert into [ProblemDetails](struct.ProblemDetails.html),
+/// which allows them to be returned from HTTP-triggered functions with a return type of
+/// `Result<HttpResponse, E>`.
+///
+/// The detail of the problem is the error's message for client errors (4xx); server errors
+/// (5xx) have no detail by default so that internal error messages are not sent to clients.
+/// Errors returned from functions are logged, so the message can still be found in the
+/// function's logs by the invocation identifier in the problem's `instance`.
+///
+/// # Examples
+///
+/// ```rust
+/// use azure_functions::bindings::{HttpRequest, HttpResponse};
+/// use azure_functions::func;
+/// use azure_functions::http::{HttpError, Status};
+/// use std::fmt;
+///
+/// #[derive(Debug)]
+/// enum UserError {
+///     NotFound(String),
+///     Database,
+/// }
+///
+/// impl fmt::Display for UserError {
+///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         match self {
+///             UserError::NotFound(id) => write!(f, "user '{}' does not exist", id),
+///             UserError::Database => write!(f, "failed to query the database"),
+///         }
+///     }
+/// }
+///
+/// impl std::error::Error for UserError {}
+///
+/// impl HttpError for UserError {
+///     fn status(&self) -> Status {
+///         match self {
+///             UserError::NotFound(_) => Status::NotFound,
+///             UserError::Database => Status::InternalServerError,
+///         }
+///     }
+/// }
+///
+/// #[func]
+/// pub fn user(request: HttpRequest) -> Result<HttpResponse, UserError> {
+///     let id = request.query_params().get("id").cloned().unwrap_or_default();
+///
+///     Err(UserError::NotFound(id))
+/// }
+/// ```
+pub trait HttpError: Error {
+    /// Gets the HTTP status of the error.
+    ///
+    /// Defaults to `500 Internal Server Error`.
+    fn status(&self) -> Status {
+        Status::InternalServerError
+    }
+
+    /// Gets a URI reference that identifies the type of the problem.
+    ///
+    /// Defaults to `about:blank`.
+    fn problem_type(&self) -> Option<String> {
+        None
+    }
+
+    /// Gets a human-readable explanation of this occurrence of the problem.
+    ///
+    /// Defaults to the error's message for client errors and nothing for server errors.
+    fn detail(&self) -> Option<String> {
+        if self.status().code() < 500 {
+            Some(self.to_string())
+        } else {
+            None
+        }
+    }
+}
+
+/// Represents the details of a problem with a HTTP request as described by [RFC 7807](https://tools.ietf.org/html/rfc7807).
+///
+/// Problem details convert into a HTTP response with a JSON body and a content type of
+/// `application/problem+json`.
+///
+/// If the problem has no `instance` when converted, the invocation identifier of the
+/// current Azure Function invocation is used so that the problem can be correlated with the
+/// function's logs.
+///
+/// # Examples
+///
+/// ```rust
+/// use azure_functions::bindings::HttpResponse;
+/// use azure_functions::http::{ProblemDetails, Status};
+///
+/// let problem = ProblemDetails::new(Status::Forbidden)
+///     .problem_type("https://example.com/probs/out-of-credit")
+///     .title("You do not have enough credit.")
+///     .detail("Your current balance is 30, but that costs 50.")
+///     .extension("balance", 30);
+///
+/// let response: HttpResponse = problem.into();
+///
+/// assert_eq!(response.status(), Status::Forbidden);
+/// assert_eq!(
+///     response.headers().get("Content-Type").unwrap(),
+///     "application/problem+json"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProblemDetails {
+    /// The URI reference that identifies the type of the problem.
+    #[serde(rename = "type", default = "about_blank")]
+    pub problem_type: String,
+    /// The short, human-readable summary of the type of the problem.
+    #[serde(default)]
+    pub title: String,
+    /// The HTTP status of the problem.
+    #[serde(with = "status_code")]
+    pub status: Status,
+    /// The human-readable explanation of this occurrence of the problem.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// The URI reference that identifies this occurrence of the problem.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    /// The additional members of the problem details.
+    #[serde(flatten)]
+    pub extensions: Map<String, Value>,
+}
+
+impl ProblemDetails {
+    /// Creates new problem details for the given status.
+    ///
+    /// The type of the problem is `about:blank` and the title is the status' reason phrase.
+    pub fn new<S: Into<Status>>(status: S) -> Self {
+        let status = status.into();
+
+        ProblemDetails {
+            problem_type: about_blank(),
+            title: ::http::StatusCode::from_u16(status.code())
+                .ok()
+                .and_then(|s| s.canonical_reason())
+                .unwrap_or_default()
+                .to_string(),
+            status,
+            detail: None,
+            instance: None,
+            extensions: Map::new(),
+        }
+    }
+
+    /// Creates new problem details from an error.
+    pub fn from_error<E: HttpError + ?Sized>(error: &E) -> Self {
+        let mut problem = ProblemDetails::new(error.status());
+
+        if let Some(problem_type) = error.problem_type() {
+            problem.problem_type = problem_type;
+        }
+        problem.detail = error.detail();
+        problem
+    }
+
+    /// Sets the URI reference that identifies the type of the problem.
+    pub fn problem_type<T: Into<String>>(mut self, problem_type: T) -> Self {
+        self.problem_type = problem_type.into();
+        self
+    }
+
+    /// Sets the short, human-readable summary of the type of the problem.
+    pub fn title<T: Into<String>>(mut self, title: T) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Sets the human-readable explanation of this occurrence of the problem.
+    pub fn detail<T: Into<String>>(mut self, detail: T) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Sets the URI reference that identifies this occurrence of the problem.
+    pub fn instance<T: Into<String>>(mut self, instance: T) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+
+    /// Adds an extension member to the problem details.
+    ///
+    /// Extension members named `type`, `title`, `status`, `detail`, or `instance` are ignored.
+    pub fn extension<N, V>(mut self, name: N, value: V) -> Self
+    where
+        N: Into<String>,
+        V: Into<Value>,
+    {
+        let name = name.into();
+        match name.as_str() {
+            "type" | "title" | "status" | "detail" | "instance" => {}
+            _ => {
+                self.extensions.insert(name, value.into());
+            }
+        };
+        self
+    }
+}
+
+impl fmt::Display for ProblemDetails {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.detail {
+            Some(detail) => write!(f, "{}: {}", self.title, detail),
+            None => write!(f, "{}", self.title),
+        }
+    }
+}
+
+impl<E: HttpError> From<E> for ProblemDetails {
+    fn from(error: E) -> Self {
+        ProblemDetails::from_error(&error)
+    }
+}
+
+impl From<ProblemDetails> for HttpResponse {
+    fn from(mut problem: ProblemDetails) -> Self {
+        if problem.instance.is_none() {
+            problem.instance = Context::current().map(|c| c.invocation_id().to_string());
+        }
+
+        HttpResponse::build()
+            .status(problem.status)
+            .header("Content-Type", PROBLEM_JSON)
+            .body(serde_json::to_value(&problem).expect("problem details should serialize"))
+            .finish()
+    }
+}
+
+/// Converts an error returned from a HTTP-triggered function into a problem details response.
+///
+/// The error is logged before it is converted because the detail of a server error is not sent
+/// to the client; the invocation identifier in the problem's `instance` correlates the two.
+#[doc(hidden)]
+pub fn error_response<E>(error: E) -> HttpResponse
+where
+    E: Into<ProblemDetails> + fmt::Display,
+{
+    log::error!("{}", error);
+
+    error.into().into()
+}
+
+fn about_blank() -> String {
+    ABOUT_BLANK.to_string()
+}
+
+mod status_code {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(status: &Status, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u16(status.code())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Status, D::Error> {
+        u16::deserialize(deserializer).map(Status::from_code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lazy_static::lazy_static;
+    use log::{Log, Metadata, Record};
+    use serde_json::{from_str, json, to_value};
+    use std::sync::{Mutex, Once};
+
+    static LOGGER: CaptureLogger = CaptureLogger;
+    static INIT_LOGGER: Once = Once::new();
+
+    lazy_static! {
+        static ref MESSAGES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+    }
+
+    struct CaptureLogger;
+
+    impl Log for CaptureLogger {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            MESSAGES
+                .lock()
+                .unwrap()
+                .push(format!("{}: {}", record.level(), record.args()));
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[derive(Debug)]
+    struct TestError(Status);
+
+    impl fmt::Display for TestError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "error with status {}", self.0.code())
+        }
+    }
+
+    impl Error for TestError {}
+
+    impl HttpError for TestError {
+        fn status(&self) -> Status {
+            self.0
+        }
+    }
+
+    #[test]
+    fn it_creates_problem_details_for_a_status() {
+        let problem = ProblemDetails::new(404);
+
+        assert_eq!(problem.problem_type, "about:blank");
+        assert_eq!(problem.title, "Not Found");
+        assert_eq!(problem.status, Status::NotFound);
+        assert_eq!(problem.detail, None);
+        assert_eq!(problem.instance, None);
+        assert!(problem.extensions.is_empty());
+    }
+
+    #[test]
+    fn it_serializes_to_json() {
+        let problem = ProblemDetails::new(Status::Forbidden)
+            .problem_type("https://example.com/probs/out-of-credit")
+            .title("You do not have enough credit.")
+            .detail("Your current balance is 30, but that costs 50.")
+            .instance("/account/12345/msgs/abc")
+            .extension("balance", 30)
+            .extension("status", 200);
+
+        assert_eq!(
+            to_value(&problem).unwrap(),
+            json!({
+                "type": "https://example.com/probs/out-of-credit",
+                "title": "You do not have enough credit.",
+                "status": 403,
+                "detail": "Your current balance is 30, but that costs 50.",
+                "instance": "/account/12345/msgs/abc",
+                "balance": 30
+            })
+        );
+    }
+
+    #[test]
+    fn it_deserializes_from_json() {
+        let problem: ProblemDetails = from_str(r#"{"status":400,"balance":30}"#).unwrap();
+
+        assert_eq!(
+            problem,
+            ProblemDetails::new(Status::BadRequest)
+                .title("")
+                .extension("balance", 30)
+        );
+    }
+
+    #[test]
+    fn it_converts_from_a_client_error() {
+        let problem: ProblemDetails = TestError(Status::Conflict).into();
+
+        assert_eq!(problem.status, Status::Conflict);
+        assert_eq!(problem.title, "Conflict");
+        assert_eq!(problem.detail.unwrap(), "error with status 409");
+    }
+
+    #[test]
+    fn it_hides_the_detail_of_a_server_error() {
+        let problem: ProblemDetails = TestError(Status::BadGateway).into();
+
+        assert_eq!(problem.status, Status::BadGateway);
+        assert_eq!(problem.title, "Bad Gateway");
+        assert_eq!(problem.detail, None);
+    }
+
+    #[test]
+    fn it_converts_to_a_http_response() {
+        let response: HttpResponse = ProblemDetails::new(Status::BadRequest)
+            .detail("bad input")
+            .into();
+
+        assert_eq!(response.status(), Status::BadRequest);
+        assert_eq!(
+            response.headers().get("Content-Type").unwrap(),
+            "application/problem+json"
+        );
+        assert_eq!(
+            response.body().as_json::<Value>().unwrap(),
+            json!({
+                "type": "about:blank",
+                "title": "Bad Request",
+                "status": 400,
+                "detail": "bad input"
+            })
+        );
+    }
+
+    #[test]
+    fn it_uses_the_invocation_id_as_the_instance() {
+        let _guard = Context::set("1234", "5678", "foo", None);
+
+        let response: HttpResponse = ProblemDetails::new(Status::InternalServerError).into();
+        assert_eq!(
+            response.body().as_json::<Value>().unwrap()["instance"],
+            "1234"
+        );
+
+        let response: HttpResponse = ProblemDetails::new(Status::InternalServerError)
+            .instance("/errors/1")
+            .into();
+        assert_eq!(
+            response.body().as_json::<Value>().unwrap()["instance"],
+            "/errors/1"
+        );
+    }
+
+    #[test]
+    fn it_logs_errors_converted_to_responses() {
+        INIT_LOGGER.call_once(|| {
+            log::set_logger(&LOGGER).unwrap();
+            log::set_max_level(log::LevelFilter::Trace);
+        });
+
+        let response = error_response(TestError(Status::ServiceUnavailable));
+
+        assert_eq!(response.status(), Status::ServiceUnavailable);
+        assert_eq!(
+            response.body().as_json::<Value>().unwrap()["detail"],
+            Value::Null
+        );
+        assert!(MESSAGES
+            .lock()
+            .unwrap()
+            .contains(&"ERROR: error with status 503".to_string()));
+    }
+}
@@ -1,5 +1,6 @@
-use crate::bindings::HttpResponse;
-use crate::http::{Body, Status};
+use crate::bindings::{append_vary, HttpRequest, HttpResponse};
+use crate::http::{negotiation, Body, Cookie, Status};
+use serde::Serialize;
 
 /// Represents a builder for HTTP responses.
 #[derive(Default, Debug)]
@@ -56,6 +57,31 @@ impl ResponseBuilder {
         self
     }
 
+    /// Adds a cookie to the response.
+    ///
+    /// The cookie is sent to the client in a `Set-Cookie` header.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use azure_functions::http::ResponseBuilder;
+    /// use azure_functions::http::{Cookie, SameSite};
+    ///
+    /// let response = ResponseBuilder::new()
+    ///     .cookie(
+    ///         Cookie::new("session", "1234")
+    ///             .http_only(true)
+    ///             .same_site(SameSite::Strict),
+    ///     )
+    ///     .finish();
+    ///
+    /// assert_eq!(response.cookies()[0].value, "1234");
+    /// ```
+    pub fn cookie(mut self, cookie: Cookie) -> Self {
+        self.0.data.cookies.push(cookie.into());
+        self
+    }
+
     /// Sets the body of the response.
     ///
     /// This will automatically set a `Content-Type` header for the response depending on the body type.
@@ -99,6 +125,80 @@ impl ResponseBuilder {
         self
     }
 
+    /// Sets the body of the response to a value serialized according to the request's `Accept` header.
+    ///
+    /// The value is serialized as JSON (`application/json`), XML (`application/xml` or `text/xml`),
+    /// or plain text (`text/plain`). JSON is used if the request has no `Accept` header.
+    ///
+    /// For XML, the value is written in a `response` element, object members are written as
+    /// child elements, and array items are written as `item` elements. For plain text, strings,
+    /// numbers, and booleans are written as-is and other values are written as JSON.
+    ///
+    /// If none of the media types are acceptable, the status of the response is set to `406 Not Acceptable`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::bindings::{HttpRequest, HttpResponse};
+    /// use azure_functions::func;
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct User {
+    ///     id: u32,
+    ///     name: String,
+    /// }
+    ///
+    /// #[func]
+    /// pub fn user(request: HttpRequest) -> HttpResponse {
+    ///     let user = User {
+    ///         id: 1,
+    ///         name: "Peter".to_string(),
+    ///     };
+    ///
+    ///     HttpResponse::build().negotiate(&request, &user).finish()
+    /// }
+    /// ```
+    pub fn negotiate<T>(mut self, request: &HttpRequest, value: &T) -> Self
+    where
+        T: Serialize,
+    {
+        append_vary(&mut self.0.data.headers, "Accept");
+
+        let media_type = match negotiation::select_media_type(
+            request.headers().get("accept").map(String::as_str),
+        ) {
+            Some(media_type) => media_type,
+            None => return self.status(Status::NotAcceptable),
+        };
+
+        match negotiation::serialize(value, media_type) {
+            Ok(body) => {
+                self.0
+                    .data
+                    .headers
+                    .insert("Content-Type".to_string(), media_type.to_string());
+
+                if media_type == "application/json" {
+                    self.body(Body::Json(body.into()))
+                } else {
+                    self.body(body)
+                }
+            }
+            Err(e) => self
+                .status(Status::InternalServerError)
+                .body(format!("failed to serialize response: {}", e)),
+        }
+    }
+
+    /// Sets whether or not the Azure Functions host performs content negotiation for the response body.
+    ///
+    /// Host content negotiation is disabled by default.
+    pub fn content_negotiation(mut self, enabled: bool) -> Self {
+        self.0.data.enable_content_negotiation = enabled;
+        self
+    }
+
     /// Consumes the builder and returns the HTTP response.
     pub fn finish(self) -> HttpResponse {
         self.0
@@ -130,6 +230,22 @@ mod tests {
         assert_eq!(response.body().as_str().unwrap(), "");
     }
 
+    #[test]
+    fn it_adds_cookies() {
+        let response: HttpResponse = ResponseBuilder::new()
+            .cookie(Cookie::new("foo", "bar").path("/"))
+            .cookie(Cookie::new("baz", "").max_age(chrono::Duration::zero()))
+            .finish();
+
+        let cookies = response.cookies();
+        assert_eq!(cookies.len(), 2);
+        assert_eq!(cookies[0], Cookie::new("foo", "bar").path("/"));
+        assert_eq!(
+            cookies[1],
+            Cookie::new("baz", "").max_age(chrono::Duration::zero())
+        );
+    }
+
     #[test]
     fn it_sets_a_body() {
         let response: HttpResponse = ResponseBuilder::new().body("test").finish();
@@ -139,4 +255,52 @@ mod tests {
         );
         assert_eq!(response.body().as_str().unwrap(), "test");
     }
+
+    #[test]
+    fn it_negotiates_the_body() {
+        let request = |accept: &str| -> HttpRequest {
+            ::http::Request::builder()
+                .header("Accept", accept)
+                .body("")
+                .unwrap()
+                .into()
+        };
+
+        let value = serde_json::json!({ "id": 1 });
+
+        let response: HttpResponse = ResponseBuilder::new()
+            .negotiate(&request("application/json"), &value)
+            .finish();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(
+            response.headers().get("Content-Type").unwrap(),
+            "application/json"
+        );
+        assert_eq!(response.headers().get("Vary").unwrap(), "Accept");
+        assert_eq!(response.body().as_str().unwrap(), r#"{"id":1}"#);
+
+        let response: HttpResponse = ResponseBuilder::new()
+            .negotiate(&request("application/xml"), &value)
+            .finish();
+        assert_eq!(
+            response.headers().get("Content-Type").unwrap(),
+            "application/xml"
+        );
+        assert!(response
+            .body()
+            .as_str()
+            .unwrap()
+            .ends_with("<response><id>1</id></response>"));
+
+        let response: HttpResponse = ResponseBuilder::new()
+            .negotiate(&request("text/html"), &value)
+            .finish();
+        assert_eq!(response.status(), Status::NotAcceptable);
+    }
+
+    #[test]
+    fn it_sets_content_negotiation() {
+        let response: HttpResponse = ResponseBuilder::new().content_negotiation(true).finish();
+        assert!(response.data.enable_content_negotiation);
+    }
 }
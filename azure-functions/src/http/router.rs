@@ -0,0 +1,649 @@
+use crate::{
+    bindings::{HttpRequest, HttpResponse},
+    http::Status,
+};
+
+type Handler = Box<dyn Fn(HttpRequest) -> HttpResponse + Send + Sync>;
+
+/// Represents middleware that runs around the handlers of a [Router](struct.Router.html).
+///
+/// Middleware is implemented for any function or closure taking a request and the
+/// [Next](struct.Next.html) handler in the chain.
+///
+/// # Examples
+///
+/// ```rust
+/// use azure_functions::bindings::{HttpRequest, HttpResponse};
+/// use azure_functions::http::{Next, Router};
+///
+/// fn server_header(request: HttpRequest, next: Next) -> HttpResponse {
+///     let mut response = next.run(request);
+///     response.set_header("Server", "azure-functions-rs");
+///     response
+/// }
+///
+/// let router = Router::new()
+///     .middleware(server_header)
+///     .get("/api/hello", |_| "Hello!".into());
+/// ```
+pub trait Middleware: Send + Sync {
+    /// Handles a request, calling the next handler in the chain as needed.
+    fn handle(&self, request: HttpRequest, next: Next) -> HttpResponse;
+}
+
+impl<F> Middleware for F
+where
+    F: Fn(HttpRequest, Next) -> HttpResponse + Send + Sync,
+{
+    fn handle(&self, request: HttpRequest, next: Next) -> HttpResponse {
+        (self)(request, next)
+    }
+}
+
+/// Represents the remainder of a middleware chain.
+pub struct Next<'a> {
+    middleware: &'a [&'a dyn Middleware],
+    endpoint: &'a dyn Fn(HttpRequest) -> HttpResponse,
+}
+
+impl Next<'_> {
+    /// Runs the remainder of the middleware chain and the route's handler.
+    pub fn run(self, request: HttpRequest) -> HttpResponse {
+        match self.middleware.split_first() {
+            Some((middleware, rest)) => middleware.handle(
+                request,
+                Next {
+                    middleware: rest,
+                    endpoint: self.endpoint,
+                },
+            ),
+            None => (self.endpoint)(request),
+        }
+    }
+}
+
+enum Segment {
+    Literal(String),
+    Param(String),
+    CatchAll(String),
+}
+
+struct Template(Vec<Segment>);
+
+impl Template {
+    fn parse(template: &str) -> Self {
+        let segments: Vec<&str> = template.split('/').filter(|s| !s.is_empty()).collect();
+        let count = segments.len();
+
+        Template(
+            segments
+                .into_iter()
+                .enumerate()
+                .map(|(i, segment)| {
+                    if segment.starts_with('{') && segment.ends_with('}') {
+                        let name = &segment[1..segment.len() - 1];
+                        let catch_all = name.starts_with('*');
+                        let name = name.trim_start_matches('*');
+
+                        if name.is_empty() || name.contains(&['{', '}'][..]) {
+                            panic!(
+                                "invalid parameter '{}' in route template '{}'.",
+                                segment, template
+                            );
+                        }
+
+                        if catch_all {
+                            if i != count - 1 {
+                                panic!(
+                                    "catch-all parameter '{}' must be the last segment of route template '{}'.",
+                                    segment, template
+                                );
+                            }
+                            return Segment::CatchAll(name.to_owned());
+                        }
+                        return Segment::Param(name.to_owned());
+                    }
+
+                    if segment.contains(&['{', '}'][..]) {
+                        panic!(
+                            "invalid segment '{}' in route template '{}'.",
+                            segment, template
+                        );
+                    }
+
+                    Segment::Literal(segment.to_owned())
+                })
+                .collect(),
+        )
+    }
+
+    /// Matches the template against the start of a path, returning the number of path segments matched.
+    fn matches(&self, path: &[String], params: &mut Vec<(String, String)>) -> Option<usize> {
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                Segment::Literal(literal) => {
+                    if !path.get(i)?.eq_ignore_ascii_case(literal) {
+                        return None;
+                    }
+                }
+                Segment::Param(name) => params.push((name.clone(), path.get(i)?.clone())),
+                Segment::CatchAll(name) => {
+                    params.push((name.clone(), path.get(i..).unwrap_or(&[]).join("/")));
+                    return Some(path.len());
+                }
+            }
+        }
+
+        Some(self.0.len())
+    }
+}
+
+enum Route {
+    Endpoint {
+        method: String,
+        template: Template,
+        handler: Handler,
+    },
+    Nested {
+        prefix: Template,
+        router: Router,
+    },
+}
+
+#[derive(Default)]
+struct Resolution<'a> {
+    params: Vec<(String, String)>,
+    middleware: Vec<&'a dyn Middleware>,
+    allowed: Vec<String>,
+}
+
+/// Represents a router that dispatches HTTP requests to handlers based on the request's method and path.
+///
+/// Route templates are made up of literal segments, parameter segments (e.g. `{id}`), and an optional
+/// trailing catch-all segment (e.g. `{*path}`). Literal segments are matched case-insensitively.
+///
+/// Routes are matched in the order they are added. Matched parameters are added to the
+/// request's route parameters, so they can be accessed with
+/// [route_params](../bindings/struct.HttpRequest.html#method.route_params) or deserialized with
+/// [route](../bindings/struct.HttpRequest.html#method.route).
+///
+/// A request that matches no route results in a `404 Not Found` response; a request that matches
+/// a route's path but not its method results in a `405 Method Not Allowed` response.
+///
+/// # Examples
+///
+/// ```rust
+/// use azure_functions::bindings::{HttpRequest, HttpResponse};
+/// use azure_functions::func;
+/// use azure_functions::http::Router;
+/// use lazy_static::lazy_static;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct User {
+///     id: u32,
+/// }
+///
+/// fn get_user(request: HttpRequest) -> HttpResponse {
+///     match request.route::<User>() {
+///         Ok(user) => format!("User {}", user.id).into(),
+///         Err(e) => e.into(),
+///     }
+/// }
+///
+/// lazy_static! {
+///     static ref ROUTER: Router = Router::new()
+///         .get("/api/users/{id}", get_user)
+///         .post("/api/users", |_| "Created".into());
+/// }
+///
+/// #[func]
+/// #[binding(name = "request", route = "{*rest}")]
+/// pub fn api(request: HttpRequest) -> HttpResponse {
+///     ROUTER.handle(request)
+/// }
+/// ```
+#[derive(Default)]
+pub struct Router {
+    routes: Vec<Route>,
+    middleware: Vec<Box<dyn Middleware>>,
+}
+
+impl Router {
+    /// Creates a new router.
+    pub fn new() -> Self {
+        Router::default()
+    }
+
+    /// Adds a route for the given HTTP method.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the route template is invalid.
+    pub fn route<H>(mut self, method: &str, template: &str, handler: H) -> Self
+    where
+        H: Fn(HttpRequest) -> HttpResponse + Send + Sync + 'static,
+    {
+        self.routes.push(Route::Endpoint {
+            method: method.to_uppercase(),
+            template: Template::parse(template),
+            handler: Box::new(handler),
+        });
+        self
+    }
+
+    /// Adds a route for `GET` requests.
+    pub fn get<H>(self, template: &str, handler: H) -> Self
+    where
+        H: Fn(HttpRequest) -> HttpResponse + Send + Sync + 'static,
+    {
+        self.route("GET", template, handler)
+    }
+
+    /// Adds a route for `POST` requests.
+    pub fn post<H>(self, template: &str, handler: H) -> Self
+    where
+        H: Fn(HttpRequest) -> HttpResponse + Send + Sync + 'static,
+    {
+        self.route("POST", template, handler)
+    }
+
+    /// Adds a route for `PUT` requests.
+    pub fn put<H>(self, template: &str, handler: H) -> Self
+    where
+        H: Fn(HttpRequest) -> HttpResponse + Send + Sync + 'static,
+    {
+        self.route("PUT", template, handler)
+    }
+
+    /// Adds a route for `PATCH` requests.
+    pub fn patch<H>(self, template: &str, handler: H) -> Self
+    where
+        H: Fn(HttpRequest) -> HttpResponse + Send + Sync + 'static,
+    {
+        self.route("PATCH", template, handler)
+    }
+
+    /// Adds a route for `DELETE` requests.
+    pub fn delete<H>(self, template: &str, handler: H) -> Self
+    where
+        H: Fn(HttpRequest) -> HttpResponse + Send + Sync + 'static,
+    {
+        self.route("DELETE", template, handler)
+    }
+
+    /// Nests a router under the given prefix.
+    ///
+    /// The routes of the nested router are relative to the prefix and its middleware
+    /// only runs for requests routed to it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::http::Router;
+    ///
+    /// let users = Router::new()
+    ///     .get("/", |_| "All users".into())
+    ///     .get("/{id}", |request| format!("User {}", request.route_params()["id"]).into());
+    ///
+    /// let router = Router::new().nest("/api/users", users);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the prefix template is invalid or contains a catch-all parameter.
+    pub fn nest(mut self, prefix: &str, router: Router) -> Self {
+        let prefix = Template::parse(prefix);
+        if let Some(Segment::CatchAll(_)) = prefix.0.last() {
+            panic!("a nested router's prefix cannot contain a catch-all parameter.");
+        }
+
+        self.routes.push(Route::Nested { prefix, router });
+        self
+    }
+
+    /// Adds middleware to the router.
+    ///
+    /// Middleware runs in the order it is added, before the handler of the matched route.
+    pub fn middleware<M>(mut self, middleware: M) -> Self
+    where
+        M: Middleware + 'static,
+    {
+        self.middleware.push(Box::new(middleware));
+        self
+    }
+
+    /// Dispatches a request to the handler of the matching route.
+    pub fn handle(&self, mut request: HttpRequest) -> HttpResponse {
+        let path = request_path(request.url());
+        let method = request.method().to_uppercase();
+
+        let mut resolution = Resolution::default();
+        if let Some(handler) = self.resolve(&method, &path, &mut resolution) {
+            request.route_params_mut().extend(resolution.params);
+
+            return Next {
+                middleware: &resolution.middleware,
+                endpoint: &**handler,
+            }
+            .run(request);
+        }
+
+        let allowed = resolution.allowed;
+        let middleware: Vec<&dyn Middleware> = self.middleware.iter().map(|m| &**m).collect();
+
+        Next {
+            middleware: &middleware,
+            endpoint: &move |_| {
+                if allowed.is_empty() {
+                    return HttpResponse::build().status(Status::NotFound).finish();
+                }
+
+                HttpResponse::build()
+                    .status(Status::MethodNotAllowed)
+                    .header("Allow", allowed.join(", "))
+                    .finish()
+            },
+        }
+        .run(request)
+    }
+
+    fn resolve<'a>(
+        &'a self,
+        method: &str,
+        path: &[String],
+        resolution: &mut Resolution<'a>,
+    ) -> Option<&'a Handler> {
+        let params = resolution.params.len();
+        let middleware = resolution.middleware.len();
+
+        resolution
+            .middleware
+            .extend(self.middleware.iter().map(|m| &**m));
+
+        for route in &self.routes {
+            match route {
+                Route::Endpoint {
+                    method: m,
+                    template,
+                    handler,
+                } => {
+                    if template.matches(path, &mut resolution.params) == Some(path.len()) {
+                        if m == method {
+                            return Some(handler);
+                        }
+                        if !resolution.allowed.contains(m) {
+                            resolution.allowed.push(m.clone());
+                        }
+                    }
+                }
+                Route::Nested { prefix, router } => {
+                    if let Some(matched) = prefix.matches(path, &mut resolution.params) {
+                        if let Some(handler) = router.resolve(method, &path[matched..], resolution)
+                        {
+                            return Some(handler);
+                        }
+                    }
+                }
+            }
+
+            resolution.params.truncate(params);
+        }
+
+        resolution.middleware.truncate(middleware);
+        None
+    }
+}
+
+/// Gets the percent-decoded segments of the path of a request URL.
+fn request_path(url: &str) -> Vec<String> {
+    let path = url
+        .parse::<::http::Uri>()
+        .map(|uri| uri.path().to_owned())
+        .unwrap_or_default();
+
+    path.split('/')
+        .filter(|s| !s.is_empty())
+        .map(percent_decode)
+        .collect()
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let (Some(high), Some(low)) = (hex_value(bytes[i + 1]), hex_value(bytes[i + 2])) {
+                decoded.push(high << 4 | low);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn hex_value(b: u8) -> Option<u8> {
+    (b as char).to_digit(16).map(|d| d as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    fn request(method: &str, url: &str) -> HttpRequest {
+        ::http::Request::builder()
+            .method(method)
+            .uri(url)
+            .body("")
+            .unwrap()
+            .into()
+    }
+
+    fn param(name: &'static str) -> impl Fn(HttpRequest) -> HttpResponse {
+        move |request| request.route_params()[name].clone().into()
+    }
+
+    #[test]
+    fn it_dispatches_by_method_and_path() {
+        let router = Router::new()
+            .get("/api/users", |_| "list".into())
+            .post("/api/users", |_| "create".into())
+            .route("options", "/api/users", |_| "options".into());
+
+        let response = router.handle(request("GET", "https://example.com/api/users?x=1"));
+        assert_eq!(response.body().as_str().unwrap(), "list");
+
+        let response = router.handle(request("POST", "https://example.com/API/Users/"));
+        assert_eq!(response.body().as_str().unwrap(), "create");
+
+        let response = router.handle(request("OPTIONS", "https://example.com/api/users"));
+        assert_eq!(response.body().as_str().unwrap(), "options");
+    }
+
+    #[test]
+    fn it_extracts_typed_parameters() {
+        #[derive(Deserialize)]
+        struct Params {
+            id: u32,
+            name: String,
+        }
+
+        let router = Router::new().get("/users/{id}/{name}", |request| {
+            let params: Params = request.route().unwrap();
+            format!("{} {}", params.id, params.name).into()
+        });
+
+        let response = router.handle(request("GET", "https://example.com/users/42/John%20Smith"));
+        assert_eq!(response.body().as_str().unwrap(), "42 John Smith");
+    }
+
+    #[test]
+    fn it_matches_catch_all_parameters() {
+        let router = Router::new().get("/files/{*path}", param("path"));
+
+        let response = router.handle(request("GET", "https://example.com/files/a/b/c.txt"));
+        assert_eq!(response.body().as_str().unwrap(), "a/b/c.txt");
+
+        let response = router.handle(request("GET", "https://example.com/files"));
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body().as_str().unwrap(), "");
+    }
+
+    #[test]
+    fn it_matches_routes_in_order() {
+        let router = Router::new()
+            .get("/users/me", |_| "me".into())
+            .get("/users/{id}", param("id"));
+
+        let response = router.handle(request("GET", "https://example.com/users/me"));
+        assert_eq!(response.body().as_str().unwrap(), "me");
+
+        let response = router.handle(request("GET", "https://example.com/users/1"));
+        assert_eq!(response.body().as_str().unwrap(), "1");
+    }
+
+    #[test]
+    fn it_responds_with_not_found() {
+        let router = Router::new().get("/users/{id}", param("id"));
+
+        for url in &[
+            "https://example.com/",
+            "https://example.com/users",
+            "https://example.com/users/1/posts",
+        ] {
+            let response = router.handle(request("GET", url));
+            assert_eq!(response.status(), Status::NotFound);
+        }
+    }
+
+    #[test]
+    fn it_responds_with_method_not_allowed() {
+        let router = Router::new()
+            .get("/users/{id}", param("id"))
+            .put("/users/{id}", param("id"))
+            .delete("/users/{id}", param("id"));
+
+        let response = router.handle(request("POST", "https://example.com/users/1"));
+        assert_eq!(response.status(), Status::MethodNotAllowed);
+        assert_eq!(response.headers().get("Allow").unwrap(), "GET, PUT, DELETE");
+    }
+
+    #[test]
+    fn it_dispatches_to_nested_routers() {
+        let users = Router::new()
+            .get("/", |_| "users".into())
+            .patch("/{id}", param("id"));
+
+        let router = Router::new()
+            .nest("/api/{version}/users", users)
+            .get("/api/{version}", param("version"));
+
+        let response = router.handle(request("GET", "https://example.com/api/v1/users"));
+        assert_eq!(response.body().as_str().unwrap(), "users");
+
+        let response = router.handle(request("PATCH", "https://example.com/api/v2/users/7"));
+        assert_eq!(response.body().as_str().unwrap(), "7");
+
+        let response = router.handle(request("GET", "https://example.com/api/v3"));
+        assert_eq!(response.body().as_str().unwrap(), "v3");
+
+        let response = router.handle(request("GET", "https://example.com/api/v1/users/7"));
+        assert_eq!(response.status(), Status::MethodNotAllowed);
+        assert_eq!(response.headers().get("Allow").unwrap(), "PATCH");
+    }
+
+    #[test]
+    fn it_runs_middleware_in_order() {
+        fn append(value: &'static str) -> impl Middleware {
+            move |request: HttpRequest, next: Next| {
+                let mut response = next.run(request);
+                let header = response
+                    .headers()
+                    .get("X-Order")
+                    .map_or(value.to_string(), |h| format!("{},{}", h, value));
+                response.set_header("X-Order", header);
+                response
+            }
+        }
+
+        let nested = Router::new()
+            .middleware(append("nested"))
+            .get("/", |_| "nested".into());
+
+        let router = Router::new()
+            .middleware(append("outer"))
+            .middleware(append("inner"))
+            .nest("/nested", nested)
+            .get("/", |_| "root".into());
+
+        let response = router.handle(request("GET", "https://example.com/nested"));
+        assert_eq!(
+            response.headers().get("X-Order").unwrap(),
+            "nested,inner,outer"
+        );
+
+        let response = router.handle(request("GET", "https://example.com/"));
+        assert_eq!(response.headers().get("X-Order").unwrap(), "inner,outer");
+
+        let response = router.handle(request("GET", "https://example.com/missing"));
+        assert_eq!(response.status(), Status::NotFound);
+        assert_eq!(response.headers().get("X-Order").unwrap(), "inner,outer");
+    }
+
+    #[test]
+    fn it_allows_middleware_to_short_circuit() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+
+        let router = Router::new()
+            .middleware(|request: HttpRequest, next: Next| {
+                if request.headers().contains_key("authorization") {
+                    return next.run(request);
+                }
+                HttpResponse::build().status(Status::Unauthorized).finish()
+            })
+            .get("/", move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                "ok".into()
+            });
+
+        let response = router.handle(request("GET", "https://example.com/"));
+        assert_eq!(response.status(), Status::Unauthorized);
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn it_decodes_paths() {
+        assert_eq!(
+            request_path("https://example.com/a%2Fb/%E2%9C%93/%zz/"),
+            vec!["a/b", "\u{2713}", "%zz"]
+        );
+        assert_eq!(
+            request_path("/relative/path?query"),
+            vec!["relative", "path"]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "must be the last segment")]
+    fn it_rejects_a_catch_all_that_is_not_last() {
+        Router::new().get("/{*rest}/more", |_| "".into());
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid parameter")]
+    fn it_rejects_an_empty_parameter() {
+        Router::new().get("/users/{}", |_| "".into());
+    }
+}
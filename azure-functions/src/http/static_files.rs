@@ -0,0 +1,587 @@
+use crate::{
+    bindings::{HttpRequest, HttpResponse},
+    http::Status,
+};
+use std::{
+    borrow::Cow,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+const DEFAULT_INDEX: &str = "index.html";
+const DEFAULT_CACHE_CONTROL: &str = "no-cache";
+const SCRIPT_ROOT_VARIABLE: &str = "AzureWebJobsScriptRoot";
+
+enum Source {
+    Directory(PathBuf),
+    Embedded(&'static [(&'static str, &'static [u8])]),
+}
+
+#[derive(Debug, PartialEq)]
+enum Range {
+    Full,
+    Partial(usize, usize),
+    Unsatisfiable,
+}
+
+/// Represents a handler that serves static files from a HTTP-triggered function.
+///
+/// Files are served from either a directory or from files embedded in the worker executable.
+///
+/// The handler supports:
+///
+/// * content types based on file extensions.
+/// * conditional requests with `ETag` and `If-None-Match`.
+/// * single byte range requests with `Range` and `If-Range`.
+/// * serving an index file (`index.html` by default) for directories.
+/// * serving a fallback file for paths without a file extension (e.g. for single page applications).
+///
+/// Only `GET` and `HEAD` requests are served; other methods receive a `405 Method Not Allowed` response.
+///
+/// An `assets` directory next to the application's `Cargo.toml` is copied to the script root
+/// by `cargo func run`.
+///
+/// # Examples
+///
+/// Serving files from the `assets` directory in the script root:
+///
+/// ```rust
+/// use azure_functions::bindings::{HttpRequest, HttpResponse};
+/// use azure_functions::func;
+/// use azure_functions::http::StaticFiles;
+///
+/// #[func]
+/// #[binding(name = "request", route = "app/{*path}")]
+/// pub fn app(request: HttpRequest) -> HttpResponse {
+///     let path = request.route_params().get("path").cloned().unwrap_or_default();
+///
+///     StaticFiles::new("assets")
+///         .fallback("index.html")
+///         .serve(&request, &path)
+/// }
+/// ```
+///
+/// Serving files embedded at compile time:
+///
+/// ```rust
+/// use azure_functions::bindings::{HttpRequest, HttpResponse};
+/// use azure_functions::func;
+/// use azure_functions::http::StaticFiles;
+///
+/// static FILES: &[(&str, &[u8])] = &[
+///     ("index.html", b"<html><body>Hello!</body></html>"),
+///     // ("app.js", include_bytes!("../assets/app.js")),
+/// ];
+///
+/// #[func]
+/// #[binding(name = "request", route = "app/{*path}")]
+/// pub fn app(request: HttpRequest) -> HttpResponse {
+///     let path = request.route_params().get("path").cloned().unwrap_or_default();
+///
+///     StaticFiles::embedded(FILES).serve(&request, &path)
+/// }
+/// ```
+pub struct StaticFiles {
+    source: Source,
+    index: Option<String>,
+    fallback: Option<String>,
+    cache_control: String,
+}
+
+impl StaticFiles {
+    /// Creates a handler that serves files from the given directory.
+    ///
+    /// A relative directory is relative to the Azure Functions application script root.
+    pub fn new<P: Into<PathBuf>>(directory: P) -> Self {
+        let directory = directory.into();
+
+        StaticFiles::with_source(Source::Directory(if directory.is_relative() {
+            script_root().join(directory)
+        } else {
+            directory
+        }))
+    }
+
+    /// Creates a handler that serves embedded files.
+    ///
+    /// Each file is a pair of a relative path (e.g. `css/site.css`) and the file's contents.
+    pub fn embedded(files: &'static [(&'static str, &'static [u8])]) -> Self {
+        StaticFiles::with_source(Source::Embedded(files))
+    }
+
+    fn with_source(source: Source) -> Self {
+        StaticFiles {
+            source,
+            index: Some(DEFAULT_INDEX.to_string()),
+            fallback: None,
+            cache_control: DEFAULT_CACHE_CONTROL.to_string(),
+        }
+    }
+
+    /// Sets the file to serve for directories.
+    ///
+    /// Defaults to `index.html`.
+    pub fn index<T: Into<String>>(mut self, index: T) -> Self {
+        self.index = Some(index.into());
+        self
+    }
+
+    /// Disables serving an index file for directories.
+    pub fn no_index(mut self) -> Self {
+        self.index = None;
+        self
+    }
+
+    /// Sets the file to serve when a requested path without a file extension does not exist.
+    pub fn fallback<T: Into<String>>(mut self, fallback: T) -> Self {
+        self.fallback = Some(fallback.into());
+        self
+    }
+
+    /// Sets the value of the `Cache-Control` header for served files.
+    ///
+    /// Defaults to `no-cache`, which requires clients to revalidate files with the `ETag`.
+    pub fn cache_control<T: Into<String>>(mut self, cache_control: T) -> Self {
+        self.cache_control = cache_control.into();
+        self
+    }
+
+    /// Serves the file at the given path for the request.
+    ///
+    /// Paths containing `..` segments are not served.
+    pub fn serve(&self, request: &HttpRequest, path: &str) -> HttpResponse {
+        let is_head = match request.method().to_uppercase().as_str() {
+            "GET" => false,
+            "HEAD" => true,
+            _ => {
+                return HttpResponse::build()
+                    .status(Status::MethodNotAllowed)
+                    .header("Allow", "GET, HEAD")
+                    .finish()
+            }
+        };
+
+        let (path, contents) = match self.find(path) {
+            Some(file) => file,
+            None => return HttpResponse::build().status(Status::NotFound).finish(),
+        };
+
+        let etag = entity_tag(&contents);
+
+        let builder = HttpResponse::build()
+            .header("ETag", etag.as_str())
+            .header("Cache-Control", self.cache_control.as_str());
+
+        if let Some(if_none_match) = request.headers().get("if-none-match") {
+            if matches_entity_tag(if_none_match, &etag) {
+                return builder.status(Status::NotModified).finish();
+            }
+        }
+
+        let range = match request.headers().get("range") {
+            Some(range) => match request.headers().get("if-range") {
+                Some(if_range) if if_range.trim() != etag => Range::Full,
+                _ => parse_range(range, contents.len()),
+            },
+            None => Range::Full,
+        };
+
+        let builder = builder
+            .header("Content-Type", content_type(&path))
+            .header("Accept-Ranges", "bytes");
+
+        let (builder, body) = match range {
+            Range::Full => (builder, &contents[..]),
+            Range::Partial(start, end) => (
+                builder.status(Status::PartialContent).header(
+                    "Content-Range",
+                    format!("bytes {}-{}/{}", start, end, contents.len()),
+                ),
+                &contents[start..=end],
+            ),
+            Range::Unsatisfiable => {
+                return builder
+                    .status(Status::RangeNotSatisfiable)
+                    .header("Content-Range", format!("bytes */{}", contents.len()))
+                    .finish()
+            }
+        };
+
+        if is_head {
+            builder.finish()
+        } else {
+            builder.body(body.to_vec()).finish()
+        }
+    }
+
+    fn find(&self, path: &str) -> Option<(String, Cow<'static, [u8]>)> {
+        let path = normalize_path(path)?;
+
+        if let Some(file) = self.find_file(&path) {
+            return Some(file);
+        }
+
+        match &self.fallback {
+            Some(fallback) if Path::new(&path).extension().is_none() => {
+                self.find_file(&normalize_path(fallback)?)
+            }
+            _ => None,
+        }
+    }
+
+    fn find_file(&self, path: &str) -> Option<(String, Cow<'static, [u8]>)> {
+        let index = self.index.as_ref().map(|index| {
+            if path.is_empty() {
+                index.clone()
+            } else {
+                format!("{}/{}", path, index)
+            }
+        });
+
+        match &self.source {
+            Source::Directory(directory) => {
+                let mut file = directory.join(path);
+                let mut path = path.to_string();
+
+                if file.is_dir() {
+                    path = index?;
+                    file = directory.join(&path);
+                }
+
+                fs::read(&file)
+                    .ok()
+                    .map(|contents| (path, Cow::Owned(contents)))
+            }
+            Source::Embedded(files) => {
+                let find = |path: &str| {
+                    files
+                        .iter()
+                        .find(|(name, _)| name.trim_start_matches('/') == path)
+                        .map(|(_, contents)| (path.to_string(), Cow::Borrowed(*contents)))
+                };
+
+                if path.is_empty() {
+                    return find(&index?);
+                }
+
+                find(path).or_else(|| find(&index?))
+            }
+        }
+    }
+}
+
+/// Gets the Azure Functions application script root.
+///
+/// The worker executable is located in the `workers/rust` directory of the script root.
+fn script_root() -> PathBuf {
+    if let Ok(root) = env::var(SCRIPT_ROOT_VARIABLE) {
+        return PathBuf::from(root);
+    }
+
+    env::current_exe()
+        .ok()
+        .and_then(|exe| exe.ancestors().nth(3).map(ToOwned::to_owned))
+        .unwrap_or_else(|| env::current_dir().expect("failed to get current directory"))
+}
+
+/// Normalizes a request path into a relative path with '/' separators.
+///
+/// Returns `None` if the path contains a parent directory segment.
+fn normalize_path(path: &str) -> Option<String> {
+    let mut segments = Vec::new();
+
+    for segment in path.split(&['/', '\\'][..]) {
+        match segment {
+            "" | "." => {}
+            ".." => return None,
+            s if s.contains(':') => return None,
+            s => segments.push(s),
+        }
+    }
+
+    Some(segments.join("/"))
+}
+
+/// Gets the content type of a file based on its extension.
+fn content_type(path: &str) -> String {
+    let mime = mime_guess::from_path(path).first_or_octet_stream();
+
+    if mime.type_() == mime_guess::mime::TEXT {
+        format!("{}; charset=utf-8", mime)
+    } else {
+        mime.to_string()
+    }
+}
+
+/// Computes a strong entity tag for the file contents (FNV-1a).
+fn entity_tag(contents: &[u8]) -> String {
+    let hash = contents.iter().fold(0xcbf2_9ce4_8422_2325u64, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+
+    format!("\"{:016x}\"", hash)
+}
+
+/// Determines if an `If-None-Match` header matches an entity tag using weak comparison.
+fn matches_entity_tag(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+/// Parses a `Range` header for a file of the given length.
+///
+/// Only a single byte range is supported; other ranges are ignored.
+fn parse_range(range: &str, len: usize) -> Range {
+    let range = range.trim();
+    if range
+        .get(..6)
+        .map_or(true, |p| !p.eq_ignore_ascii_case("bytes="))
+        || range.contains(',')
+    {
+        return Range::Full;
+    }
+
+    let mut parts = range[6..].splitn(2, '-').map(str::trim);
+    let (start, end) = match (parts.next(), parts.next()) {
+        (Some(start), Some(end)) => (start, end),
+        _ => return Range::Full,
+    };
+
+    if start.is_empty() {
+        return match end.parse::<usize>() {
+            Ok(0) => Range::Unsatisfiable,
+            Ok(_) if len == 0 => Range::Unsatisfiable,
+            Ok(suffix) => Range::Partial(len.saturating_sub(suffix), len - 1),
+            Err(_) => Range::Full,
+        };
+    }
+
+    let start = match start.parse::<usize>() {
+        Ok(start) => start,
+        Err(_) => return Range::Full,
+    };
+
+    let end = if end.is_empty() {
+        len.saturating_sub(1)
+    } else {
+        match end.parse::<usize>() {
+            Ok(end) if end >= start => end.min(len.saturating_sub(1)),
+            _ => return Range::Full,
+        }
+    };
+
+    if start >= len {
+        return Range::Unsatisfiable;
+    }
+
+    Range::Partial(start, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    static FILES: &[(&str, &[u8])] = &[
+        ("index.html", b"<html>root</html>"),
+        ("docs/index.html", b"<html>docs</html>"),
+        ("css/site.css", b"body { color: red; }"),
+        ("/logo.png", &[0x89, 0x50, 0x4e, 0x47]),
+    ];
+
+    fn request(method: &str, headers: &[(&str, &str)]) -> HttpRequest {
+        let mut builder = ::http::Request::builder();
+        builder.method(method).uri("https://example.com/app");
+        for (name, value) in headers {
+            builder.header(*name, *value);
+        }
+        builder.body("").unwrap().into()
+    }
+
+    #[test]
+    fn it_serves_embedded_files() {
+        let files = StaticFiles::embedded(FILES);
+
+        let response = files.serve(&request("GET", &[]), "css/site.css");
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(
+            response.headers().get("Content-Type").unwrap(),
+            "text/css; charset=utf-8"
+        );
+        assert_eq!(response.headers().get("Cache-Control").unwrap(), "no-cache");
+        assert_eq!(response.headers().get("Accept-Ranges").unwrap(), "bytes");
+        assert_eq!(response.body().as_bytes(), b"body { color: red; }");
+
+        let response = files.serve(&request("GET", &[]), "/logo.png");
+        assert_eq!(response.headers().get("Content-Type").unwrap(), "image/png");
+        assert_eq!(response.body().as_bytes(), &[0x89, 0x50, 0x4e, 0x47]);
+    }
+
+    #[test]
+    fn it_serves_index_files() {
+        let files = StaticFiles::embedded(FILES);
+
+        let response = files.serve(&request("GET", &[]), "");
+        assert_eq!(response.body().as_bytes(), b"<html>root</html>");
+        assert_eq!(
+            response.headers().get("Content-Type").unwrap(),
+            "text/html; charset=utf-8"
+        );
+
+        let response = files.serve(&request("GET", &[]), "docs/");
+        assert_eq!(response.body().as_bytes(), b"<html>docs</html>");
+
+        let response = files.no_index().serve(&request("GET", &[]), "docs");
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[test]
+    fn it_serves_the_fallback_file() {
+        let files = StaticFiles::embedded(FILES).fallback("index.html");
+
+        let response = files.serve(&request("GET", &[]), "users/1");
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body().as_bytes(), b"<html>root</html>");
+
+        let response = files.serve(&request("GET", &[]), "missing.js");
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[test]
+    fn it_does_not_serve_parent_directories() {
+        let files = StaticFiles::embedded(FILES);
+
+        assert_eq!(
+            files
+                .serve(&request("GET", &[]), "docs/../index.html")
+                .status(),
+            Status::NotFound
+        );
+        assert_eq!(normalize_path("./a//b\\c"), Some("a/b/c".to_string()));
+        assert_eq!(normalize_path("c:/windows"), None);
+    }
+
+    #[test]
+    fn it_only_serves_get_and_head_requests() {
+        let files = StaticFiles::embedded(FILES);
+
+        let response = files.serve(&request("HEAD", &[]), "index.html");
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(
+            response.headers().get("Content-Type").unwrap(),
+            "text/html; charset=utf-8"
+        );
+        assert!(response.body().as_bytes().is_empty());
+
+        let response = files.serve(&request("POST", &[]), "index.html");
+        assert_eq!(response.status(), Status::MethodNotAllowed);
+        assert_eq!(response.headers().get("Allow").unwrap(), "GET, HEAD");
+    }
+
+    #[test]
+    fn it_handles_conditional_requests() {
+        let files = StaticFiles::embedded(FILES).cache_control("public, max-age=60");
+
+        let response = files.serve(&request("GET", &[]), "index.html");
+        let etag = response.headers().get("ETag").unwrap().clone();
+        assert_eq!(etag, entity_tag(b"<html>root</html>"));
+
+        let response = files.serve(
+            &request(
+                "GET",
+                &[("If-None-Match", &format!("\"other\", W/{}", etag))],
+            ),
+            "index.html",
+        );
+        assert_eq!(response.status(), Status::NotModified);
+        assert_eq!(response.headers().get("ETag").unwrap(), &etag);
+        assert_eq!(
+            response.headers().get("Cache-Control").unwrap(),
+            "public, max-age=60"
+        );
+        assert!(response.body().as_bytes().is_empty());
+
+        let response = files.serve(
+            &request("GET", &[("If-None-Match", "\"other\"")]),
+            "index.html",
+        );
+        assert_eq!(response.status(), Status::Ok);
+    }
+
+    #[test]
+    fn it_handles_range_requests() {
+        let files = StaticFiles::embedded(FILES);
+
+        let response = files.serve(&request("GET", &[("Range", "bytes=0-3")]), "css/site.css");
+        assert_eq!(response.status(), Status::PartialContent);
+        assert_eq!(
+            response.headers().get("Content-Range").unwrap(),
+            "bytes 0-3/20"
+        );
+        assert_eq!(response.body().as_bytes(), b"body");
+
+        let response = files.serve(&request("GET", &[("Range", "bytes=100-")]), "css/site.css");
+        assert_eq!(response.status(), Status::RangeNotSatisfiable);
+        assert_eq!(
+            response.headers().get("Content-Range").unwrap(),
+            "bytes */20"
+        );
+
+        let response = files.serve(
+            &request("GET", &[("Range", "bytes=0-3"), ("If-Range", "\"stale\"")]),
+            "css/site.css",
+        );
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body().as_bytes(), b"body { color: red; }");
+
+        let response = files.serve(&request("GET", &[("Range", "bytesé=0-1")]), "css/site.css");
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body().as_bytes(), b"body { color: red; }");
+    }
+
+    #[test]
+    fn it_parses_ranges() {
+        assert_eq!(parse_range("bytes=0-0", 10), Range::Partial(0, 0));
+        assert_eq!(parse_range("bytes=5-", 10), Range::Partial(5, 9));
+        assert_eq!(parse_range("bytes=5-100", 10), Range::Partial(5, 9));
+        assert_eq!(parse_range("bytes=-3", 10), Range::Partial(7, 9));
+        assert_eq!(parse_range("bytes=-30", 10), Range::Partial(0, 9));
+        assert_eq!(parse_range("bytes=-0", 10), Range::Unsatisfiable);
+        assert_eq!(parse_range("bytes=10-", 10), Range::Unsatisfiable);
+        assert_eq!(parse_range("bytes=5-3", 10), Range::Full);
+        assert_eq!(parse_range("bytes=0-1,3-4", 10), Range::Full);
+        assert_eq!(parse_range("items=0-1", 10), Range::Full);
+        assert_eq!(parse_range("bytesé=0-1", 10), Range::Full);
+        assert_eq!(parse_range("éé", 10), Range::Full);
+        assert_eq!(parse_range("bytes=é-1", 10), Range::Full);
+    }
+
+    #[test]
+    fn it_serves_files_from_a_directory() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("docs")).unwrap();
+        fs::write(
+            dir.path().join("docs").join("index.html"),
+            b"<html>docs</html>",
+        )
+        .unwrap();
+        fs::write(dir.path().join("data.json"), b"{}").unwrap();
+
+        let files = StaticFiles::new(dir.path());
+
+        let response = files.serve(&request("GET", &[]), "data.json");
+        assert_eq!(
+            response.headers().get("Content-Type").unwrap(),
+            "application/json"
+        );
+        assert_eq!(response.body().as_bytes(), b"{}");
+
+        let response = files.serve(&request("GET", &[]), "docs");
+        assert_eq!(response.body().as_bytes(), b"<html>docs</html>");
+
+        let response = files.serve(&request("GET", &[]), "missing.txt");
+        assert_eq!(response.status(), Status::NotFound);
+    }
+}
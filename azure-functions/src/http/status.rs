@@ -97,6 +97,21 @@ impl Status {
     }
 }
 
+impl Status {
+    /// Gets the numeric code of the status.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::http::Status;
+    ///
+    /// assert_eq!(Status::NotFound.code(), 404);
+    /// ```
+    pub fn code(self) -> u16 {
+        self.0
+    }
+}
+
 impl ToString for Status {
     fn to_string(&self) -> String {
         self.0.to_string()
@@ -174,8 +174,48 @@ pub fn worker_main_with_extensions(
     functions: &[&'static codegen::Function],
     extensions: &[(&str, &str)],
 ) {
-    let registry = Registry::new(functions);
+    run_worker(args, Registry::new(functions), extensions)
+}
 
+/// The main entry point for the Azure Functions for Rust worker.
+///
+/// This entry point applies a CORS policy to every HTTP-triggered function that does not
+/// specify its own policy with the `cors_*` arguments of the `func` attribute.
+///
+/// Preflight requests are only received by functions whose HTTP trigger accepts the `OPTIONS`
+/// method (i.e. functions without a `methods` argument or with `options` in the list of methods).
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use azure_functions::http::Cors;
+///
+/// fn main() {
+///     azure_functions::worker_main_with_cors(
+///         ::std::env::args(),
+///         functions::EXPORTS,
+///         &[],
+///         Cors::new().allow_origin("https://example.com").allow_method("PUT"),
+///     );
+/// }
+/// ```
+pub fn worker_main_with_cors(
+    args: impl Iterator<Item = String>,
+    functions: &[&'static codegen::Function],
+    extensions: &[(&str, &str)],
+    cors: http::Cors,
+) {
+    let mut registry = Registry::new(functions);
+    registry.set_default_cors(cors);
+
+    run_worker(args, registry, extensions)
+}
+
+fn run_worker(
+    args: impl Iterator<Item = String>,
+    registry: Registry<'static>,
+    extensions: &[(&str, &str)],
+) {
     let app = App::new("Azure Functions for Rust worker")
         .version(env!("CARGO_PKG_VERSION"))
         .about("Implements the Azure Functions for Rust worker.")
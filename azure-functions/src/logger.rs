@@ -1,8 +1,10 @@
 use crate::{
+    durable::ReplayInfo,
     rpc::{rpc_log, streaming_message::Content, RpcLog, StreamingMessage},
     worker::Sender,
 };
 use log::{Level, Log, Metadata, Record};
+use serde_json::json;
 
 pub struct Logger {
     level: Level,
@@ -25,6 +27,12 @@ impl Log for Logger {
             return;
         }
 
+        // Orchestration functions replay from the start, so only log records that are not being replayed
+        let replay = ReplayInfo::current();
+        if replay.as_ref().map(|r| r.is_replaying).unwrap_or(false) {
+            return;
+        }
+
         let mut event = RpcLog {
             level: match record.level() {
                 Level::Trace => rpc_log::Level::Trace,
@@ -39,6 +47,14 @@ impl Log for Logger {
 
         event.invocation_id = crate::context::CURRENT.with(|c| c.borrow().invocation_id.clone());
 
+        if let Some(replay) = replay {
+            event.properties = json!({
+                "instanceId": replay.instance_id,
+                "historyPosition": replay.history_position,
+            })
+            .to_string();
+        }
+
         self.sender
             .unbounded_send(StreamingMessage {
                 content: Some(Content::RpcLog(event)),
@@ -49,3 +65,80 @@ impl Log for Logger {
 
     fn flush(&self) {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bindings::DurableOrchestrationContext;
+    use crate::durable::OrchestrationTester;
+    use futures::channel::mpsc::unbounded;
+    use serde_json::Value;
+
+    fn log(logger: &Logger, message: &str) {
+        logger.log(
+            &Record::builder()
+                .args(format_args!("{}", message))
+                .level(Level::Info)
+                .build(),
+        );
+    }
+
+    #[test]
+    fn it_suppresses_records_while_replaying() {
+        let (sender, mut receiver) = unbounded();
+        let logger = Logger::new(Level::Info, sender);
+
+        let mut tester = OrchestrationTester::new("orchestration", Value::Null);
+        let orchestration = |context: DurableOrchestrationContext| {
+            let logger = &logger;
+            async move {
+                log(logger, "before");
+                let _ = context.call_activity("activity", Value::Null).await;
+                log(logger, "after");
+            }
+        };
+
+        tester.run(orchestration);
+        tester.complete_activity("activity", "done");
+        tester.run(orchestration);
+
+        let messages: Vec<_> = std::iter::from_fn(|| receiver.try_next().ok().flatten())
+            .map(|m| match m.content {
+                Some(Content::RpcLog(log)) => (log.message, log.properties),
+                _ => panic!("expected a log message"),
+            })
+            .collect();
+
+        assert_eq!(
+            messages,
+            vec![
+                (
+                    "before".to_string(),
+                    r#"{"historyPosition":0,"instanceId":"00000000000000000000000000000000"}"#
+                        .to_string()
+                ),
+                (
+                    "after".to_string(),
+                    r#"{"historyPosition":4,"instanceId":"00000000000000000000000000000000"}"#
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_logs_outside_of_orchestrations() {
+        let (sender, mut receiver) = unbounded();
+        let logger = Logger::new(Level::Info, sender);
+
+        log(&logger, "hello");
+
+        match receiver.try_next().unwrap().unwrap().content {
+            Some(Content::RpcLog(log)) => {
+                assert_eq!(log.message, "hello");
+                assert_eq!(log.properties, "");
+            }
+            _ => panic!("expected a log message"),
+        }
+    }
+}
@@ -1,7 +1,13 @@
-use crate::codegen::{bindings, Function};
+use crate::{
+    codegen::{bindings, Function},
+    http::Cors,
+};
 use lazy_static::lazy_static;
 use semver::Version;
-use std::collections::{hash_map::Iter, HashMap};
+use std::{
+    collections::{hash_map::Iter, HashMap},
+    sync::Arc,
+};
 
 // Note: package names are expected to be lowercase.
 const STORAGE_PACKAGE_NAME: &str = "microsoft.azure.webjobs.extensions.storage";
@@ -110,6 +116,8 @@ lazy_static! {
 pub struct Registry<'a> {
     functions: HashMap<String, &'a Function>,
     registered: HashMap<String, &'a Function>,
+    cors: HashMap<String, Arc<Cors>>,
+    default_cors: Option<Arc<Cors>>,
 }
 
 impl<'a> Registry<'a> {
@@ -125,9 +133,29 @@ impl<'a> Registry<'a> {
                     map
                 }),
             registered: HashMap::new(),
+            cors: functions
+                .iter()
+                .filter_map(|func| {
+                    func.cors
+                        .as_ref()
+                        .map(|cors| (func.name.clone().into_owned(), Arc::new(cors.into())))
+                })
+                .collect(),
+            default_cors: None,
         }
     }
 
+    pub fn set_default_cors(&mut self, cors: Cors) {
+        self.default_cors = Some(Arc::new(cors));
+    }
+
+    pub fn cors(&self, func: &Function) -> Option<Arc<Cors>> {
+        self.cors
+            .get(func.name.as_ref())
+            .or(self.default_cors.as_ref())
+            .cloned()
+    }
+
     pub fn register(&mut self, id: &str, name: &str) -> bool {
         match self.functions.get(name) {
             Some(info) => {
@@ -198,6 +226,11 @@ mod tests {
             &Function {
                 name: Cow::Borrowed("function1"),
                 disabled: false,
+                version: None,
+                retry: None,
+                retry_options: None,
+                roles: Cow::Borrowed(&[]),
+                cors: None,
                 bindings: Cow::Borrowed(&[]),
                 invoker: None,
                 manifest_dir: None,
@@ -206,6 +239,11 @@ mod tests {
             &Function {
                 name: Cow::Borrowed("function2"),
                 disabled: false,
+                version: None,
+                retry: None,
+                retry_options: None,
+                roles: Cow::Borrowed(&[]),
+                cors: None,
                 bindings: Cow::Borrowed(&[]),
                 invoker: None,
                 manifest_dir: None,
@@ -214,6 +252,11 @@ mod tests {
             &Function {
                 name: Cow::Borrowed("function3"),
                 disabled: false,
+                version: None,
+                retry: None,
+                retry_options: None,
+                roles: Cow::Borrowed(&[]),
+                cors: None,
                 bindings: Cow::Borrowed(&[]),
                 invoker: None,
                 manifest_dir: None,
@@ -231,6 +274,11 @@ mod tests {
         let mut registry = Registry::new(&[&Function {
             name: Cow::Borrowed("function1"),
             disabled: false,
+            version: None,
+            retry: None,
+            retry_options: None,
+            roles: Cow::Borrowed(&[]),
+            cors: None,
             bindings: Cow::Borrowed(&[]),
             invoker: None,
             manifest_dir: None,
@@ -251,6 +299,11 @@ mod tests {
         let mut registry = Registry::new(&[&Function {
             name: Cow::Borrowed("function1"),
             disabled: false,
+            version: None,
+            retry: None,
+            retry_options: None,
+            roles: Cow::Borrowed(&[]),
+            cors: None,
             bindings: Cow::Borrowed(&[]),
             invoker: None,
             manifest_dir: None,
@@ -261,11 +314,72 @@ mod tests {
         assert_eq!(registry.register("id", "not_present"), false);
     }
 
+    #[test]
+    fn it_gets_the_cors_policy_of_a_function() {
+        let with_cors = Function {
+            name: Cow::Borrowed("with_cors"),
+            disabled: false,
+            version: None,
+            retry: None,
+            retry_options: None,
+            roles: Cow::Borrowed(&[]),
+            cors: Some(crate::codegen::Cors {
+                allowed_origins: Cow::Borrowed(&[Cow::Borrowed("https://example.com")]),
+                allowed_methods: Cow::Borrowed(&[]),
+                allowed_headers: Cow::Borrowed(&[]),
+                exposed_headers: Cow::Borrowed(&[]),
+                allow_credentials: false,
+                max_age: None,
+            }),
+            bindings: Cow::Borrowed(&[]),
+            invoker: None,
+            manifest_dir: None,
+            file: None,
+        };
+        let without_cors = Function {
+            name: Cow::Borrowed("without_cors"),
+            disabled: false,
+            version: None,
+            retry: None,
+            retry_options: None,
+            roles: Cow::Borrowed(&[]),
+            cors: None,
+            bindings: Cow::Borrowed(&[]),
+            invoker: None,
+            manifest_dir: None,
+            file: None,
+        };
+
+        let mut registry = Registry::new(&[&with_cors, &without_cors]);
+
+        assert_eq!(
+            *registry.cors(&with_cors).unwrap(),
+            Cors::new().allow_origin("https://example.com")
+        );
+        assert!(registry.cors(&without_cors).is_none());
+
+        registry.set_default_cors(Cors::new().allow_any_origin());
+
+        assert_eq!(
+            *registry.cors(&with_cors).unwrap(),
+            Cors::new().allow_origin("https://example.com")
+        );
+        assert_eq!(
+            *registry.cors(&without_cors).unwrap(),
+            Cors::new().allow_any_origin()
+        );
+    }
+
     #[test]
     fn it_builds_an_extensions_map() {
         let registry = Registry::new(&[&Function {
             name: Cow::Borrowed("function1"),
             disabled: false,
+            version: None,
+            retry: None,
+            retry_options: None,
+            roles: Cow::Borrowed(&[]),
+            cors: None,
             bindings: Cow::Borrowed(&[
                 Binding::Http(bindings::Http {
                     name: Cow::Borrowed("binding1"),
@@ -300,6 +414,11 @@ mod tests {
         let registry = Registry::new(&[&Function {
             name: Cow::Borrowed("function"),
             disabled: false,
+            version: None,
+            retry: None,
+            retry_options: None,
+            roles: Cow::Borrowed(&[]),
+            cors: None,
             bindings: Cow::Borrowed(&[Binding::Queue(bindings::Queue {
                 name: Cow::Borrowed("binding"),
                 queue_name: Cow::Borrowed("some_queue"),
@@ -321,6 +440,11 @@ mod tests {
         let registry = Registry::new(&[&Function {
             name: Cow::Borrowed("function1"),
             disabled: false,
+            version: None,
+            retry: None,
+            retry_options: None,
+            roles: Cow::Borrowed(&[]),
+            cors: None,
             bindings: Cow::Borrowed(&[Binding::Http(bindings::Http {
                 name: Cow::Borrowed("binding1"),
             })]),
@@ -2,13 +2,14 @@ use crate::{
     backtrace::Backtrace,
     codegen::{Function, InvokerFn},
     context::Context,
+    http::{cors::CorsInvocation, Cors},
     logger,
     registry::Registry,
     rpc::{
         client::FunctionRpcClient, status_result::Status, streaming_message::Content,
         FunctionLoadRequest, FunctionLoadResponse, InvocationRequest, InvocationResponse,
-        StartStream, StatusResult, StreamingMessage, WorkerInitResponse, WorkerStatusRequest,
-        WorkerStatusResponse,
+        RetryContext, StartStream, StatusResult, StreamingMessage, WorkerInitResponse,
+        WorkerStatusRequest, WorkerStatusResponse,
     },
 };
 use futures::{channel::mpsc::unbounded, future::FutureExt, stream::StreamExt};
@@ -19,6 +20,7 @@ use std::{
     future::Future,
     panic::{catch_unwind, set_hook, AssertUnwindSafe, PanicInfo},
     pin::Pin,
+    sync::Arc,
     task::Poll,
 };
 use tokio::future::poll_fn;
@@ -32,6 +34,8 @@ struct ContextFuture<F> {
     invocation_id: String,
     function_id: String,
     function_name: &'static str,
+    retry_context: Option<RetryContext>,
+    cors: Option<CorsInvocation>,
     sender: Sender,
 }
 
@@ -41,6 +45,8 @@ impl<F> ContextFuture<F> {
         invocation_id: String,
         function_id: String,
         function_name: &'static str,
+        retry_context: Option<RetryContext>,
+        cors: Option<CorsInvocation>,
         sender: Sender,
     ) -> Self {
         ContextFuture {
@@ -48,6 +54,8 @@ impl<F> ContextFuture<F> {
             invocation_id,
             function_id,
             function_name,
+            retry_context,
+            cors,
             sender,
         }
     }
@@ -57,9 +65,14 @@ impl<F: Future<Output = InvocationResponse> + Unpin> Future for ContextFuture<F>
     type Output = ();
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut std::task::Context) -> Poll<Self::Output> {
-        let _guard = Context::set(&self.invocation_id, &self.function_id, self.function_name);
-
-        let res = match catch_unwind(AssertUnwindSafe(|| self.inner.poll_unpin(cx))) {
+        let _guard = Context::set(
+            &self.invocation_id,
+            &self.function_id,
+            self.function_name,
+            self.retry_context.as_ref(),
+        );
+
+        let mut res = match catch_unwind(AssertUnwindSafe(|| self.inner.poll_unpin(cx))) {
             Ok(p) => match p {
                 Poll::Ready(res) => res,
                 Poll::Pending => return Poll::Pending,
@@ -75,6 +88,10 @@ impl<F: Future<Output = InvocationResponse> + Unpin> Future for ContextFuture<F>
             },
         };
 
+        if let Some(cors) = self.cors.as_ref() {
+            cors.apply(&mut res);
+        }
+
         self.sender
             .unbounded_send(StreamingMessage {
                 content: Some(Content::InvocationResponse(res)),
@@ -162,11 +179,14 @@ impl Worker {
                     .unbounded_send(StreamingMessage {
                         content: Some(Content::WorkerInitResponse(WorkerInitResponse {
                             worker_version: env!("CARGO_PKG_VERSION").to_owned(),
+                            capabilities: [("RawHttpBodyBytes".to_owned(), "true".to_owned())]
+                                .iter()
+                                .cloned()
+                                .collect(),
                             result: Some(StatusResult {
                                 status: Status::Success as i32,
                                 ..Default::default()
                             }),
-                            ..Default::default()
                         })),
                         ..Default::default()
                     })
@@ -234,7 +254,7 @@ impl Worker {
         req: InvocationRequest,
     ) {
         if let Some(func) = registry.get(&req.function_id) {
-            Worker::invoke_function(func, sender, req);
+            Worker::invoke_function(func, registry.cors(func), sender, req);
             return;
         }
 
@@ -265,7 +285,26 @@ impl Worker {
             .expect("failed to send worker status response");
     }
 
-    fn invoke_function(func: &'static Function, sender: Sender, req: InvocationRequest) {
+    fn invoke_function(
+        func: &'static Function,
+        cors: Option<Arc<Cors>>,
+        sender: Sender,
+        req: InvocationRequest,
+    ) {
+        let mut cors = cors.and_then(|policy| CorsInvocation::new(policy, func, &req));
+        if let Some(res) = cors
+            .as_mut()
+            .and_then(|cors| cors.preflight_response(&req.invocation_id))
+        {
+            sender
+                .unbounded_send(StreamingMessage {
+                    content: Some(Content::InvocationResponse(res)),
+                    ..Default::default()
+                })
+                .expect("failed to send invocation response");
+            return;
+        }
+
         match func
             .invoker
             .as_ref()
@@ -277,6 +316,7 @@ impl Worker {
                 // Wrap the request with a RefCell so we can move the request to the invoked function
                 let id = req.invocation_id.clone();
                 let func_id = req.function_id.clone();
+                let retry_context = req.retry_context.clone();
                 let req = RefCell::new(Some(req));
 
                 tokio::spawn(ContextFuture::new(
@@ -291,18 +331,23 @@ impl Worker {
                     id,
                     func_id,
                     &func.name,
+                    retry_context,
+                    cors,
                     sender,
                 ));
             }
             InvokerFn::Async(invoker_fn) => {
                 let id = req.invocation_id.clone();
                 let func_id = req.function_id.clone();
+                let retry_context = req.retry_context.clone();
 
                 tokio::spawn(ContextFuture::new(
                     invoker_fn.expect("invoker must have a callback")(req),
                     id,
                     func_id,
                     &func.name,
+                    retry_context,
+                    cors,
                     sender,
                 ));
             }
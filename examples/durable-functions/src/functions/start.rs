@@ -16,7 +16,7 @@ pub async fn start(req: HttpRequest, client: DurableOrchestrationClient) -> Http
         )
         .await
     {
-        Ok(data) => data.into(),
+        Ok(data) => client.create_check_status_response(&data.instance_id),
         Err(e) => format!("Failed to start orchestration: {}", e).into(),
     }
 }
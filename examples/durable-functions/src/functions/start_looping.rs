@@ -6,7 +6,7 @@ use azure_functions::{
 #[func]
 pub async fn start_looping(_req: HttpRequest, client: DurableOrchestrationClient) -> HttpResponse {
     match client.start_new("looping", None, 0).await {
-        Ok(data) => data.into(),
+        Ok(data) => client.create_check_status_response(&data.instance_id),
         Err(e) => format!("Failed to start orchestration: {}", e).into(),
     }
 }
@@ -7,10 +7,10 @@ pub async fn wait_for_event(context: DurableOrchestrationContext) -> Orchestrati
         info!("Waiting for event named 'event'.");
     }
 
-    let v = context.wait_for_event("event").await.unwrap();
+    let v: String = context.wait_for_event("event").await.unwrap();
 
     if !context.is_replaying() {
-        info!("Event was raised with value: {}.", v.as_str().unwrap());
+        info!("Event was raised with value: {}.", v);
     }
 
     v.into()